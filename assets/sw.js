@@ -0,0 +1,45 @@
+// Service worker template. scripts/service-worker.sh fills in the build's
+// files and version after every trunk build, see Trunk.toml.
+const VERSION = "__VERSION__";
+const ASSETS = [__ASSETS__];
+const CACHE = `keypress-${VERSION}`;
+
+// Cache the whole build up front; the dictionaries and key sounds are
+// embedded in the wasm, so this is everything practice needs offline.
+self.addEventListener("install", (event) => {
+  event.waitUntil(caches.open(CACHE).then((cache) => cache.addAll(ASSETS)));
+});
+
+self.addEventListener("activate", (event) => {
+  event.waitUntil(
+    caches.keys().then((keys) =>
+      Promise.all(
+        keys
+          .filter((key) => key.startsWith("keypress-") && key !== CACHE)
+          .map((key) => caches.delete(key))
+      )
+    )
+  );
+});
+
+// A new build waits until the page asks for it from the update banner.
+self.addEventListener("message", (event) => {
+  if (event.data === "skip-waiting") {
+    self.skipWaiting();
+  }
+});
+
+self.addEventListener("fetch", (event) => {
+  const request = event.request;
+  if (request.method !== "GET" || new URL(request.url).origin !== self.location.origin) {
+    return;
+  }
+  // Pages always come from this build, so the page and its wasm match
+  const lookup = request.mode === "navigate" ? "./index.html" : request;
+  event.respondWith(
+    caches
+      .open(CACHE)
+      .then((cache) => cache.match(lookup))
+      .then((cached) => cached || fetch(request))
+  );
+});
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const DICT_PROGRAMMER: &str = include_str!("../../src/content/dicts/it-words.json");
+const DICT_CET4: &str = include_str!("../../src/content/dicts/CET4_T.json");
+const DICT_CET6: &str = include_str!("../../src/content/dicts/CET6_T.json");
+const DICT_TOEFL: &str = include_str!("../../src/content/dicts/TOEFL_T.json");
+const DICT_PHRASES: &str = include_str!("../../src/content/dicts/phrases.json");
+
+pub const CHAPTER_SIZE: usize = 20;
+
+lazy_static::lazy_static! {
+    pub static ref DICT_INDEX: Vec<&'static str> = vec![
+        "Programmer",
+        "CET4",
+        "CET6",
+        "TOEFL",
+        "Phrases",
+    ];
+
+    pub static ref DICT_MAP: HashMap<String, &'static str> =
+    {
+        let mut map = HashMap::new();
+        map.insert(DICT_INDEX[0].to_string(), DICT_PROGRAMMER);
+        map.insert(DICT_INDEX[1].to_string(), DICT_CET4);
+        map.insert(DICT_INDEX[2].to_string(), DICT_CET6);
+        map.insert(DICT_INDEX[3].to_string(), DICT_TOEFL);
+        map.insert(DICT_INDEX[4].to_string(), DICT_PHRASES);
+        map
+    };
+}
+
+pub fn nr_chapter(nr_word: usize) -> usize {
+    nr_word.div_ceil(CHAPTER_SIZE)
+}
+
+/// One dictionary entry as stored in `src/content/dicts`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Word {
+    pub name: String,
+    pub trans: Vec<String>,
+    pub usphone: String,
+    pub ukphone: String,
+    pub examples: Vec<Example>,
+}
+
+/// An example sentence using a dictionary word.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Example {
+    pub text: String,
+    pub trans: String,
+}
+
+impl Word {
+    /// Case-insensitive substring match over the word and its translations.
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        query.is_empty()
+            || self.name.to_lowercase().contains(&query)
+            || self.trans.iter().any(|t| t.to_lowercase().contains(&query))
+    }
+}
+
+pub fn words(level: &str) -> Vec<Word> {
+    DICT_MAP
+        .get(level)
+        .and_then(|text| serde_json::from_str(text).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_dictionary_parses() {
+        for level in DICT_INDEX.iter() {
+            let words = words(level);
+            assert!(!words.is_empty(), "{} is empty", level);
+            assert!(words.iter().all(|w| !w.name.is_empty()), "{}", level);
+        }
+        assert_eq!(nr_chapter(41), 3);
+    }
+}
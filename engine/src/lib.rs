@@ -0,0 +1,5 @@
+//! The embedded dictionaries and the rules for typing them, shared by the
+//! web app and the terminal frontend.
+
+pub mod dict;
+pub mod typing;
@@ -0,0 +1,316 @@
+use serde::{Deserialize, Serialize};
+use std::slice::Iter;
+
+/// How a separator inside a multi-word entry has to be typed.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub enum Policy {
+    #[default]
+    Strict, // must be typed
+    Optional, // may be typed or left out
+    AutoSkip, // filled in automatically
+}
+
+impl Policy {
+    pub fn iterator() -> Iter<'static, Policy> {
+        static POLICY_S: [Policy; 3] = [Policy::Strict, Policy::Optional, Policy::AutoSkip];
+        POLICY_S.iter()
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Policy::Strict => "strict",
+            Policy::Optional => "optional",
+            Policy::AutoSkip => "auto-skip",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Policy> {
+        Policy::iterator().find(|p| p.code() == code).copied()
+    }
+}
+
+/// How the case of typed letters is compared with the entry.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub enum CasePolicy {
+    #[default]
+    Exact,
+    Insensitive,
+    AutoCapitalise, // the first letter may be typed in lower case
+}
+
+impl CasePolicy {
+    pub fn iterator() -> Iter<'static, CasePolicy> {
+        static CASE_S: [CasePolicy; 3] = [
+            CasePolicy::Exact,
+            CasePolicy::Insensitive,
+            CasePolicy::AutoCapitalise,
+        ];
+        CASE_S.iter()
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            CasePolicy::Exact => "exact",
+            CasePolicy::Insensitive => "insensitive",
+            CasePolicy::AutoCapitalise => "auto-capitalise",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<CasePolicy> {
+        CasePolicy::iterator().find(|c| c.code() == code).copied()
+    }
+
+    fn matches(&self, expected: char, key: char, first: bool) -> bool {
+        let same_letter = || expected.to_lowercase().eq(key.to_lowercase());
+        expected == key
+            || match self {
+                CasePolicy::Exact => false,
+                CasePolicy::Insensitive => same_letter(),
+                CasePolicy::AutoCapitalise => first && expected.is_uppercase() && same_letter(),
+            }
+    }
+}
+
+/// Policies for the separators found in phrases like "log in",
+/// "well-known" and "don't".
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(default)]
+pub struct Separators {
+    pub space: Policy,
+    pub hyphen: Policy,
+    pub apostrophe: Policy,
+}
+
+impl Separators {
+    fn policy(&self, c: char) -> Policy {
+        match c {
+            ' ' => self.space,
+            '-' => self.hyphen,
+            '\'' => self.apostrophe,
+            _ => Policy::Strict,
+        }
+    }
+
+    /// Length in bytes of the separators at the start of `rest` that need
+    /// not be typed, either only auto-skipped ones or optional ones too.
+    fn skippable(&self, rest: &str, optional: bool) -> usize {
+        rest.chars()
+            .take_while(|c| match self.policy(*c) {
+                Policy::Strict => false,
+                Policy::Optional => optional,
+                Policy::AutoSkip => true,
+            })
+            .map(char::len_utf8)
+            .sum()
+    }
+}
+
+/// Everything that decides whether a key press is correct.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct Rules {
+    pub case: CasePolicy,
+    pub separators: Separators,
+}
+
+impl Rules {
+    /// `typed` extended by `key` if it is correct for `target`, together
+    /// with any separators it skipped or that are filled in after it. The
+    /// result is always a prefix of `target`, whatever case was typed.
+    pub fn accept(&self, target: &str, typed: &str, key: char) -> Option<String> {
+        let rest = target.strip_prefix(typed)?;
+        let first = !typed.chars().any(char::is_alphanumeric);
+        let matches = |s: &str| {
+            s.chars()
+                .next()
+                .is_some_and(|c| self.case.matches(c, key, first))
+        };
+        let skip = if matches(rest) {
+            0
+        } else {
+            self.separators.skippable(rest, true)
+        };
+        let expected = rest[skip..].chars().next()?;
+        if !matches(&rest[skip..]) {
+            return None;
+        }
+
+        let end = typed.len() + skip + expected.len_utf8();
+        let fill = self.separators.skippable(&target[end..], false);
+        Some(target[..end + fill].to_string())
+    }
+}
+
+/// Words per minute using the usual five characters per word.
+pub fn wpm(chars: u32, seconds: f64) -> f64 {
+    if seconds <= 0.0 {
+        return 0.0;
+    }
+    chars as f64 / 5.0 / (seconds / 60.0)
+}
+
+pub fn accuracy(keystrokes: u32, mistakes: u32) -> f64 {
+    if keystrokes == 0 {
+        return 100.0;
+    }
+    keystrokes.saturating_sub(mistakes) as f64 * 100.0 / keystrokes as f64
+}
+
+/// The word or sentence currently being typed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Attempt {
+    pub typed: String,
+    pub started: Option<f64>, // ms, at the first key
+    pub keystrokes: u32,
+    pub mistakes: u32,
+}
+
+impl Attempt {
+    /// Type `key` at `now` ms; true if `rules` accept it for `target`.
+    pub fn key(&mut self, rules: &Rules, target: &str, key: char, now: f64) -> bool {
+        self.started.get_or_insert(now);
+        self.keystrokes += 1;
+        match rules.accept(target, &self.typed, key) {
+            Some(typed) => {
+                self.typed = typed;
+                true
+            }
+            None => {
+                self.mistakes += 1;
+                false
+            }
+        }
+    }
+
+    pub fn is_complete(&self, target: &str) -> bool {
+        self.typed.len() == target.len()
+    }
+
+    /// Speed and accuracy of the attempt if it ended at `now` ms.
+    pub fn score(&self, now: f64) -> (f64, f64) {
+        let chars = self.keystrokes - self.mistakes;
+        let seconds = (now - self.started.unwrap_or(now)) / 1000.0;
+        (
+            wpm(chars, seconds),
+            accuracy(self.keystrokes, self.mistakes),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_all(rules: &Rules, target: &str, keys: &str) -> Option<String> {
+        keys.chars().try_fold(String::new(), |typed, key| {
+            rules.accept(target, &typed, key)
+        })
+    }
+
+    fn with(separators: Separators) -> Rules {
+        Rules {
+            separators,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn strict_requires_every_separator() {
+        let strict = Rules::default();
+        assert_eq!(
+            type_all(&strict, "log in", "log in"),
+            Some(String::from("log in"))
+        );
+        assert_eq!(type_all(&strict, "log in", "logi"), None);
+        assert_eq!(type_all(&strict, "don't", "dont"), None);
+        assert_eq!(strict.accept("cat", "c", 'x'), None);
+    }
+
+    #[test]
+    fn optional_separators_may_be_left_out() {
+        let optional = with(Separators {
+            space: Policy::Optional,
+            hyphen: Policy::Optional,
+            apostrophe: Policy::Strict,
+        });
+        assert_eq!(
+            type_all(&optional, "well-known", "wellknown"),
+            Some(String::from("well-known"))
+        );
+        assert_eq!(
+            type_all(&optional, "well-known", "well-known"),
+            Some(String::from("well-known"))
+        );
+        assert_eq!(
+            type_all(&optional, "rock 'n' roll", "rock'n' roll"),
+            Some(String::from("rock 'n' roll"))
+        );
+        assert_eq!(type_all(&optional, "don't", "dont"), None);
+    }
+
+    #[test]
+    fn auto_skip_fills_separators() {
+        let skip = with(Separators {
+            space: Policy::AutoSkip,
+            hyphen: Policy::AutoSkip,
+            apostrophe: Policy::AutoSkip,
+        });
+        assert_eq!(skip.accept("log in", "lo", 'g'), Some(String::from("log ")));
+        assert_eq!(
+            type_all(&skip, "state-of-the-art", "stateoftheart"),
+            Some(String::from("state-of-the-art"))
+        );
+        assert_eq!(
+            type_all(&skip, "rock 'n' roll", "rocknroll"),
+            Some(String::from("rock 'n' roll"))
+        );
+        assert_eq!(skip.accept("'tis", "", 't'), Some(String::from("'t")));
+        assert_eq!(skip.accept("log in", "log ", ' '), None);
+    }
+
+    #[test]
+    fn case_policies() {
+        let exact = Rules::default();
+        assert_eq!(type_all(&exact, "Internet", "internet"), None);
+        assert_eq!(
+            type_all(&exact, "Internet", "Internet"),
+            Some(String::from("Internet"))
+        );
+
+        let insensitive = Rules {
+            case: CasePolicy::Insensitive,
+            ..Default::default()
+        };
+        assert_eq!(
+            type_all(&insensitive, "JavaScript", "JAVASCRIPT"),
+            Some(String::from("JavaScript"))
+        );
+
+        let capitalise = Rules {
+            case: CasePolicy::AutoCapitalise,
+            ..Default::default()
+        };
+        assert_eq!(
+            type_all(&capitalise, "Internet", "internet"),
+            Some(String::from("Internet"))
+        );
+        assert_eq!(type_all(&capitalise, "JavaScript", "javascript"), None);
+        assert_eq!(type_all(&capitalise, "cat", "Cat"), None);
+    }
+
+    #[test]
+    fn attempts_count_keys_and_mistakes() {
+        let rules = Rules::default();
+        let mut attempt = Attempt::default();
+        assert!(attempt.key(&rules, "cat", 'c', 1000.0));
+        assert!(!attempt.key(&rules, "cat", 'x', 1500.0));
+        assert!(attempt.key(&rules, "cat", 'a', 2000.0));
+        assert!(!attempt.is_complete("cat"));
+        assert!(attempt.key(&rules, "cat", 't', 2500.0));
+        assert!(attempt.is_complete("cat"));
+
+        let (wpm, accuracy) = attempt.score(37_000.0);
+        assert_eq!((attempt.keystrokes, attempt.mistakes), (4, 1));
+        assert_eq!(wpm, 1.0);
+        assert_eq!(accuracy, 75.0);
+    }
+}
@@ -0,0 +1,4 @@
+//! Wire formats shared by the KeyPress app and its companion servers.
+
+pub mod race;
+pub mod sync;
@@ -0,0 +1,95 @@
+//! Messages between the practice page and `keypress-race-server`, sent as
+//! one JSON text frame each.
+
+use serde::{Deserialize, Serialize};
+
+pub type PlayerId = u32;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type")]
+pub enum ClientMsg {
+    /// Enter `room`, creating it if needed. Must be the first message.
+    Join {
+        room: String,
+        name: String,
+    },
+    /// Ask everyone in the room to type this chapter now.
+    Start {
+        dict: String,
+        chapter: usize,
+    },
+    /// Words and characters typed so far in the current race.
+    Progress {
+        words: usize,
+        chars: usize,
+    },
+    /// The chapter was completed after `ms` milliseconds.
+    Finish {
+        ms: u32,
+    },
+    Leave,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Player {
+    pub id: PlayerId,
+    pub name: String,
+    pub words: usize,
+    pub chars: usize,
+    pub finished: Option<u32>, // ms
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type")]
+pub enum ServerMsg {
+    /// Sent to a player who joined, with everyone already in the room.
+    Welcome {
+        id: PlayerId,
+        room: String,
+        players: Vec<Player>,
+    },
+    Joined {
+        player: Player,
+    },
+    Left {
+        id: PlayerId,
+    },
+    /// A race began; every player's progress has been reset.
+    Started {
+        dict: String,
+        chapter: usize,
+    },
+    Progress {
+        id: PlayerId,
+        words: usize,
+        chars: usize,
+    },
+    Finished {
+        id: PlayerId,
+        ms: u32,
+        place: usize,
+    },
+    Error {
+        message: String,
+    },
+}
+
+impl ClientMsg {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        serde_json::from_str(text).map_err(|e| e.to_string())
+    }
+}
+
+impl ServerMsg {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        serde_json::from_str(text).map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,246 @@
+//! JSON API of `keypress-sync-server`, which keeps one copy of a user's
+//! progress for all their devices.
+//!
+//! A device sends a `Push` to `POST /api/sync` with `Authorization: Bearer
+//! <token>` and gets back a `Pull` with whatever it has not seen yet. The
+//! state document is last-writer-wins by modification time, while history
+//! entries are append-only and merged by key.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+pub const SYNC_PATH: &str = "/api/sync";
+
+/// One append-only record, e.g. a finished practice session.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub key: String,
+    pub data: Value,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Push {
+    pub since: u64,           // last revision this device saw
+    pub modified: f64,        // ms since the epoch, when `state` last changed
+    pub state: Option<Value>, // only sent when it changed locally
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Pull {
+    pub revision: u64,
+    pub modified: f64,
+    pub state: Option<Value>, // only sent when the device's copy is stale
+    pub entries: Vec<Entry>,  // added since the device's last revision
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct Stored {
+    revision: u64,
+    entry: Entry,
+}
+
+/// Everything the server keeps for one user.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Account {
+    revision: u64,
+    modified: f64,
+    state_revision: u64,
+    state: Option<Value>,
+    entries: Vec<Stored>,
+}
+
+impl Account {
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Merge a device's changes and answer with what it is missing.
+    pub fn push(&mut self, push: Push) -> Pull {
+        let (mut taken, mut rejected, mut state_changed) = (false, false, false);
+        if let Some(state) = push.state {
+            if self.state.is_none() || push.modified > self.modified {
+                state_changed = self.state.as_ref() != Some(&state);
+                self.state = Some(state);
+                self.modified = push.modified;
+                taken = true;
+            } else {
+                rejected = true;
+            }
+        }
+
+        let mut changed = state_changed;
+        let next = self.revision + 1;
+        let mut known: BTreeSet<String> =
+            self.entries.iter().map(|s| s.entry.key.clone()).collect();
+        let sent: BTreeSet<String> = push.entries.iter().map(|e| e.key.clone()).collect();
+        for entry in push.entries {
+            if known.insert(entry.key.clone()) {
+                self.entries.push(Stored {
+                    revision: next,
+                    entry,
+                });
+                changed = true;
+            }
+        }
+        if changed {
+            self.revision = next;
+            if state_changed {
+                self.state_revision = next;
+            }
+        }
+
+        let mut pull = self.pull(push.since);
+        pull.entries.retain(|e| !sent.contains(&e.key));
+        // A rejected state is answered with the newer one
+        if taken {
+            pull.state = None;
+        } else if rejected {
+            pull.state = self.state.clone();
+        }
+        pull
+    }
+
+    /// What a device that saw revision `since` is missing.
+    pub fn pull(&self, since: u64) -> Pull {
+        Pull {
+            revision: self.revision,
+            modified: self.modified,
+            state: self.state.clone().filter(|_| self.state_revision > since),
+            entries: self
+                .entries
+                .iter()
+                .filter(|s| s.revision > since)
+                .map(|s| s.entry.clone())
+                .collect(),
+        }
+    }
+}
+
+/// A device's view of its account, kept between syncs so it can work
+/// offline and send only what changed when it reconnects.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Replica {
+    pub revision: u64,
+    pub modified: f64,
+    pub synced: Option<Value>, // state as of the last sync
+    pub known: BTreeSet<String>,
+}
+
+impl Replica {
+    /// The request for the current local `state` and `entries`; a state
+    /// that differs from the last synced one counts as modified at `now`.
+    /// Before the first sync it is older than anything on the server, so a
+    /// new device doesn't wipe out the account's settings.
+    pub fn push(&self, state: Value, entries: &[Entry], now: f64) -> Push {
+        let changed = self.synced.as_ref() != Some(&state);
+        Push {
+            since: self.revision,
+            modified: if changed && self.synced.is_some() {
+                now
+            } else {
+                self.modified
+            },
+            state: Some(state).filter(|_| changed),
+            entries: entries
+                .iter()
+                .filter(|e| !self.known.contains(&e.key))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Record a successful sync of `push`. Returns the state to adopt if the
+    /// server's copy won, and the entries that are new to this device.
+    pub fn pulled(&mut self, push: Push, pull: Pull) -> (Option<Value>, Vec<Entry>) {
+        self.revision = pull.revision;
+        self.known.extend(push.entries.into_iter().map(|e| e.key));
+        let entries: Vec<Entry> = pull
+            .entries
+            .into_iter()
+            .filter(|e| self.known.insert(e.key.clone()))
+            .collect();
+
+        let adopted = match pull.state {
+            Some(state) => {
+                self.synced = Some(state.clone());
+                self.modified = pull.modified;
+                Some(state)
+            }
+            None => {
+                if push.state.is_some() {
+                    self.synced = push.state;
+                    self.modified = push.modified;
+                }
+                None
+            }
+        };
+        (adopted, entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(key: &str) -> Entry {
+        Entry {
+            key: String::from(key),
+            data: json!({ "key": key }),
+        }
+    }
+
+    fn sync(
+        account: &mut Account,
+        replica: &mut Replica,
+        state: Value,
+        entries: &[Entry],
+        now: f64,
+    ) -> (Option<Value>, Vec<Entry>) {
+        let push = replica.push(state, entries, now);
+        let pull = account.push(push.clone());
+        replica.pulled(push, pull)
+    }
+
+    #[test]
+    fn history_is_merged_from_every_device() {
+        let mut account = Account::default();
+        let (mut a, mut b) = (Replica::default(), Replica::default());
+
+        let (_, new) = sync(&mut account, &mut a, json!(1), &[entry("a1")], 100.0);
+        assert!(new.is_empty());
+        let (_, new) = sync(&mut account, &mut b, json!(1), &[entry("b1")], 200.0);
+        assert_eq!(new, vec![entry("a1")]);
+        let (_, new) = sync(&mut account, &mut a, json!(1), &[entry("a1")], 300.0);
+        assert_eq!(new, vec![entry("b1")]);
+
+        // Nothing left to send or receive
+        let push = a.push(json!(1), &[entry("a1"), entry("b1")], 400.0);
+        assert!(push.state.is_none() && push.entries.is_empty());
+        assert_eq!(account.push(push), account.pull(account.revision()));
+        assert_eq!(account.revision(), 2);
+    }
+
+    #[test]
+    fn last_writer_wins() {
+        let mut account = Account::default();
+        let (mut a, mut b) = (Replica::default(), Replica::default());
+        sync(&mut account, &mut a, json!("a"), &[], 100.0);
+        let (state, _) = sync(&mut account, &mut b, json!("b"), &[], 50.0);
+        assert_eq!(state, Some(json!("a")));
+        assert_eq!(b.synced, Some(json!("a")));
+
+        // Both change offline; the later change is kept everywhere
+        let (state, _) = sync(&mut account, &mut b, json!("b2"), &[], 300.0);
+        assert_eq!(state, None);
+        let (state, _) = sync(&mut account, &mut a, json!("a2"), &[], 200.0);
+        assert_eq!(state, Some(json!("b2")));
+        assert_eq!(account.pull(0).state, Some(json!("b2")));
+    }
+}
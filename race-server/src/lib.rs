@@ -0,0 +1,261 @@
+//! Rooms where several browsers race through the same chapter.
+//!
+//! `Hub` holds all room state and turns each client message into the
+//! messages to send back, so the protocol can be tested without sockets.
+//! `serve` runs it behind a WebSocket listener, one thread per connection.
+
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use keypress_protocol::race::{ClientMsg, Player, PlayerId, ServerMsg};
+use tungstenite::{Message, WebSocket};
+
+pub type Outbox = Vec<(PlayerId, ServerMsg)>;
+
+#[derive(Debug, Default)]
+struct Room {
+    players: Vec<Player>, // in join order
+}
+
+impl Room {
+    fn broadcast(&self, msg: &ServerMsg, except: Option<PlayerId>, out: &mut Outbox) {
+        for p in self.players.iter().filter(|p| Some(p.id) != except) {
+            out.push((p.id, msg.clone()));
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Hub {
+    next_id: PlayerId,
+    rooms: BTreeMap<String, Room>,
+    members: HashMap<PlayerId, String>, // player to room name
+}
+
+impl Hub {
+    pub fn new() -> Self {
+        Hub::default()
+    }
+
+    /// A new connection; ids are handed out in order starting from 1.
+    pub fn connect(&mut self) -> PlayerId {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    pub fn disconnect(&mut self, id: PlayerId) -> Outbox {
+        let mut out = Outbox::new();
+        self.leave(id, &mut out);
+        out
+    }
+
+    pub fn handle(&mut self, id: PlayerId, msg: ClientMsg) -> Outbox {
+        let mut out = Outbox::new();
+        match msg {
+            ClientMsg::Join { room, name } => self.join(id, room, name, &mut out),
+            ClientMsg::Leave => self.leave(id, &mut out),
+            msg => match self.members.get(&id).cloned() {
+                Some(room) => self.play(id, &room, msg, &mut out),
+                None => out.push((id, error("join a room first"))),
+            },
+        }
+        out
+    }
+
+    fn join(&mut self, id: PlayerId, room: String, name: String, out: &mut Outbox) {
+        let room = room.trim().to_string();
+        if room.is_empty() {
+            out.push((id, error("room name is empty")));
+            return;
+        }
+        self.leave(id, out);
+
+        let name = match name.trim() {
+            "" => format!("Player {}", id),
+            name => name.to_string(),
+        };
+        let player = Player {
+            id,
+            name,
+            ..Default::default()
+        };
+        let entry = self.rooms.entry(room.clone()).or_default();
+        entry.broadcast(
+            &ServerMsg::Joined {
+                player: player.clone(),
+            },
+            None,
+            out,
+        );
+        entry.players.push(player);
+        out.push((
+            id,
+            ServerMsg::Welcome {
+                id,
+                room: room.clone(),
+                players: entry.players.clone(),
+            },
+        ));
+        self.members.insert(id, room);
+    }
+
+    fn leave(&mut self, id: PlayerId, out: &mut Outbox) {
+        let name = match self.members.remove(&id) {
+            Some(name) => name,
+            None => return,
+        };
+        if let Some(room) = self.rooms.get_mut(&name) {
+            room.players.retain(|p| p.id != id);
+            room.broadcast(&ServerMsg::Left { id }, None, out);
+            if room.players.is_empty() {
+                self.rooms.remove(&name);
+            }
+        }
+    }
+
+    fn play(&mut self, id: PlayerId, name: &str, msg: ClientMsg, out: &mut Outbox) {
+        let room = self.rooms.get_mut(name).unwrap();
+        match msg {
+            ClientMsg::Start { dict, chapter } => {
+                for p in room.players.iter_mut() {
+                    p.words = 0;
+                    p.chars = 0;
+                    p.finished = None;
+                }
+                room.broadcast(&ServerMsg::Started { dict, chapter }, None, out);
+            }
+            ClientMsg::Progress { words, chars } => {
+                let player = room.players.iter_mut().find(|p| p.id == id).unwrap();
+                player.words = words;
+                player.chars = chars;
+                room.broadcast(&ServerMsg::Progress { id, words, chars }, Some(id), out);
+            }
+            ClientMsg::Finish { ms } => {
+                let place = room.players.iter().filter(|p| p.finished.is_some()).count() + 1;
+                let player = room.players.iter_mut().find(|p| p.id == id).unwrap();
+                if player.finished.is_some() {
+                    return;
+                }
+                player.finished = Some(ms);
+                room.broadcast(&ServerMsg::Finished { id, ms, place }, None, out);
+            }
+            ClientMsg::Join { .. } | ClientMsg::Leave => unreachable!(),
+        }
+    }
+
+    pub fn players(&self, room: &str) -> Vec<Player> {
+        self.rooms
+            .get(room)
+            .map(|r| r.players.clone())
+            .unwrap_or_default()
+    }
+}
+
+fn error(message: &str) -> ServerMsg {
+    ServerMsg::Error {
+        message: String::from(message),
+    }
+}
+
+type WsResult = Result<(), Box<dyn Error>>;
+
+/// Shared state of a running server.
+#[derive(Default)]
+struct Server {
+    hub: Mutex<Hub>,
+    clients: Mutex<HashMap<PlayerId, Sender<ServerMsg>>>,
+}
+
+impl Server {
+    fn deliver(&self, out: Outbox) {
+        let clients = self.clients.lock().unwrap();
+        for (id, msg) in out {
+            if let Some(tx) = clients.get(&id) {
+                let _ = tx.send(msg);
+            }
+        }
+    }
+
+    fn run(&self, stream: TcpStream) -> WsResult {
+        let mut ws = tungstenite::accept(stream).map_err(|e| match e {
+            tungstenite::HandshakeError::Failure(e) => e,
+            tungstenite::HandshakeError::Interrupted(_) => {
+                tungstenite::Error::Io(io::ErrorKind::WouldBlock.into())
+            }
+        })?;
+        // Reads time out so queued messages for this client get written
+        ws.get_ref()
+            .set_read_timeout(Some(Duration::from_millis(20)))?;
+
+        let (tx, rx) = mpsc::channel();
+        let id = self.hub.lock().unwrap().connect();
+        self.clients.lock().unwrap().insert(id, tx);
+
+        let result = self.pump(id, &mut ws, &rx);
+
+        self.clients.lock().unwrap().remove(&id);
+        let out = self.hub.lock().unwrap().disconnect(id);
+        self.deliver(out);
+        result
+    }
+
+    fn pump(
+        &self,
+        id: PlayerId,
+        ws: &mut WebSocket<TcpStream>,
+        rx: &Receiver<ServerMsg>,
+    ) -> WsResult {
+        loop {
+            match ws.read() {
+                Ok(Message::Text(text)) => {
+                    let out = match ClientMsg::parse(&text) {
+                        Ok(msg) => self.hub.lock().unwrap().handle(id, msg),
+                        Err(e) => vec![(id, error(&e))],
+                    };
+                    self.deliver(out);
+                }
+                Ok(Message::Close(_)) => return Ok(()),
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(e))
+                    if matches!(
+                        e.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) => {}
+                Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+                Err(e) => return Err(e.into()),
+            }
+            while let Ok(msg) = rx.try_recv() {
+                ws.write(Message::Text(msg.to_json()))?;
+            }
+            ws.flush()?;
+        }
+    }
+}
+
+/// Accept connections on `listener`, serving each on its own thread; a
+/// connection that fails to be accepted is logged and skipped.
+pub fn serve(listener: TcpListener) -> io::Result<()> {
+    let server = Arc::new(Server::default());
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("failed to accept a connection: {}", e);
+                continue;
+            }
+        };
+        let server = server.clone();
+        thread::spawn(move || {
+            if let Err(e) = server.run(stream) {
+                eprintln!("connection closed: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
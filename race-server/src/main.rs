@@ -0,0 +1,13 @@
+use std::env;
+use std::net::TcpListener;
+
+const DEFAULT_ADDR: &str = "127.0.0.1:9001";
+
+fn main() -> std::io::Result<()> {
+    let addr = env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_ADDR.to_string());
+    let listener = TcpListener::bind(&addr)?;
+    println!("keypress race server listening on ws://{}", addr);
+    keypress_race_server::serve(listener)
+}
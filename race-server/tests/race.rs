@@ -0,0 +1,228 @@
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+use keypress_protocol::race::{ClientMsg, Player, ServerMsg};
+use keypress_race_server::{serve, Hub};
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+fn join(room: &str, name: &str) -> ClientMsg {
+    ClientMsg::Join {
+        room: String::from(room),
+        name: String::from(name),
+    }
+}
+
+fn player(id: u32, name: &str) -> Player {
+    Player {
+        id,
+        name: String::from(name),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn join_announces_players_in_order() {
+    let mut hub = Hub::new();
+    let alice = hub.connect();
+    let bob = hub.connect();
+    assert_eq!((alice, bob), (1, 2));
+
+    let out = hub.handle(alice, join("lab", "alice"));
+    assert_eq!(
+        out,
+        vec![(
+            alice,
+            ServerMsg::Welcome {
+                id: alice,
+                room: String::from("lab"),
+                players: vec![player(alice, "alice")],
+            }
+        )]
+    );
+
+    let out = hub.handle(bob, join(" lab ", " "));
+    assert_eq!(
+        out,
+        vec![
+            (
+                alice,
+                ServerMsg::Joined {
+                    player: player(bob, "Player 2")
+                }
+            ),
+            (
+                bob,
+                ServerMsg::Welcome {
+                    id: bob,
+                    room: String::from("lab"),
+                    players: vec![player(alice, "alice"), player(bob, "Player 2")],
+                }
+            ),
+        ]
+    );
+}
+
+#[test]
+fn race_progress_and_places() {
+    let mut hub = Hub::new();
+    let a = hub.connect();
+    let b = hub.connect();
+    hub.handle(a, join("lab", "a"));
+    hub.handle(b, join("lab", "b"));
+
+    let started = ServerMsg::Started {
+        dict: String::from("CET4"),
+        chapter: 3,
+    };
+    let out = hub.handle(
+        b,
+        ClientMsg::Start {
+            dict: String::from("CET4"),
+            chapter: 3,
+        },
+    );
+    assert_eq!(out, vec![(a, started.clone()), (b, started)]);
+
+    let out = hub.handle(a, ClientMsg::Progress { words: 1, chars: 6 });
+    assert_eq!(
+        out,
+        vec![(
+            b,
+            ServerMsg::Progress {
+                id: a,
+                words: 1,
+                chars: 6
+            }
+        )]
+    );
+
+    let out = hub.handle(b, ClientMsg::Finish { ms: 9000 });
+    let first = ServerMsg::Finished {
+        id: b,
+        ms: 9000,
+        place: 1,
+    };
+    assert_eq!(out, vec![(a, first.clone()), (b, first)]);
+    assert!(hub.handle(b, ClientMsg::Finish { ms: 1 }).is_empty());
+
+    let out = hub.handle(a, ClientMsg::Finish { ms: 9500 });
+    assert_eq!(out.len(), 2);
+    assert!(matches!(out[0].1, ServerMsg::Finished { place: 2, .. }));
+    assert_eq!(hub.players("lab")[1].finished, Some(9000));
+}
+
+#[test]
+fn leaving_and_errors() {
+    let mut hub = Hub::new();
+    let a = hub.connect();
+    let b = hub.connect();
+
+    let out = hub.handle(a, ClientMsg::Progress { words: 1, chars: 1 });
+    assert!(matches!(out[..], [(1, ServerMsg::Error { .. })]));
+    let out = hub.handle(a, join("  ", "a"));
+    assert!(matches!(out[..], [(1, ServerMsg::Error { .. })]));
+
+    hub.handle(a, join("lab", "a"));
+    hub.handle(b, join("lab", "b"));
+    // Joining another room leaves the first one
+    let out = hub.handle(a, join("den", "a"));
+    assert_eq!(out[0], (b, ServerMsg::Left { id: a }));
+    assert_eq!(hub.players("lab"), vec![player(b, "b")]);
+
+    assert_eq!(hub.disconnect(b), vec![]);
+    assert!(hub.players("lab").is_empty());
+    assert_eq!(hub.handle(a, ClientMsg::Leave), vec![]);
+    assert!(hub.players("den").is_empty());
+}
+
+type Client = WebSocket<MaybeTlsStream<TcpStream>>;
+
+fn connect(port: u16) -> Client {
+    let url = format!("ws://127.0.0.1:{}", port);
+    tungstenite::connect(url).unwrap().0
+}
+
+fn send(ws: &mut Client, msg: ClientMsg) {
+    ws.send(Message::Text(msg.to_json())).unwrap();
+}
+
+fn recv(ws: &mut Client) -> ServerMsg {
+    loop {
+        if let Message::Text(text) = ws.read().unwrap() {
+            return ServerMsg::parse(&text).unwrap();
+        }
+    }
+}
+
+#[test]
+fn websocket_round_trip() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || serve(listener));
+
+    let mut a = connect(port);
+    send(&mut a, join("lab", "a"));
+    let a_id = match recv(&mut a) {
+        ServerMsg::Welcome { id, players, .. } => {
+            assert_eq!(players.len(), 1);
+            id
+        }
+        msg => panic!("unexpected {:?}", msg),
+    };
+
+    let mut b = connect(port);
+    send(&mut b, join("lab", "b"));
+    let b_id = match recv(&mut b) {
+        ServerMsg::Welcome { id, players, .. } => {
+            assert_eq!(players.len(), 2);
+            id
+        }
+        msg => panic!("unexpected {:?}", msg),
+    };
+    assert!(matches!(recv(&mut a), ServerMsg::Joined { player } if player.id == b_id));
+
+    send(
+        &mut a,
+        ClientMsg::Start {
+            dict: String::from("CET6"),
+            chapter: 2,
+        },
+    );
+    for ws in [&mut a, &mut b] {
+        assert_eq!(
+            recv(ws),
+            ServerMsg::Started {
+                dict: String::from("CET6"),
+                chapter: 2
+            }
+        );
+    }
+
+    send(
+        &mut b,
+        ClientMsg::Progress {
+            words: 2,
+            chars: 11,
+        },
+    );
+    assert_eq!(
+        recv(&mut a),
+        ServerMsg::Progress {
+            id: b_id,
+            words: 2,
+            chars: 11
+        }
+    );
+
+    ws_text(&mut a, "not json");
+    assert!(matches!(recv(&mut a), ServerMsg::Error { .. }));
+
+    b.close(None).unwrap();
+    assert_eq!(recv(&mut a), ServerMsg::Left { id: b_id });
+    assert_ne!(a_id, b_id);
+}
+
+fn ws_text(ws: &mut Client, text: &str) {
+    ws.send(Message::Text(String::from(text))).unwrap();
+}
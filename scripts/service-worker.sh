@@ -0,0 +1,10 @@
+#!/bin/sh
+# Trunk post-build hook: write sw.js listing the files of this build, with a
+# version that changes whenever any of them does.
+set -e
+cd "$TRUNK_STAGING_DIR"
+files=$(find . -type f ! -name sw.js | sort)
+assets=$(printf '%s\n' $files | sed 's|.*|"&",|' | tr -d '\n')
+version=$(cat $files | cksum | cut -d ' ' -f 1)
+sed -e "s|__VERSION__|$version|" -e "s|__ASSETS__|$assets|" \
+    "$TRUNK_SOURCE_DIR/assets/sw.js" > sw.js
@@ -0,0 +1,358 @@
+use std::fmt::{Display, Formatter, Result};
+
+use crate::common::dict::Word;
+use crate::common::i18n::t;
+
+/// Anki's plain-text import; `.txt` is what it offers to open.
+pub const MIME: &str = "text/plain";
+
+#[derive(Debug, PartialEq)]
+pub enum AnkiError {
+    Empty,
+}
+
+impl Display for AnkiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            AnkiError::Empty => write!(f, "{}", t("anki.error_empty")),
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// One field, quoted if it holds a quote, as Anki's reader expects.
+fn field(text: &str) -> String {
+    let text = text.replace(['\t', '\n', '\r'], " ");
+    if text.contains('"') {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+fn phonetics(word: &Word) -> String {
+    match (word.usphone.as_str(), word.ukphone.as_str()) {
+        ("", "") => String::new(),
+        (us, uk) if us == uk || uk.is_empty() => format!("/{}/", us),
+        ("", uk) => format!("/{}/", uk),
+        (us, uk) => format!("US /{}/ UK /{}/", us, uk),
+    }
+}
+
+/// `words` as an Anki text file with word, translation and phonetics
+/// fields, every note tagged `tag`.
+pub fn export(words: &[Word], tag: &str) -> String {
+    let tag: String = tag
+        .chars()
+        .map(|c| if c.is_whitespace() { '_' } else { c })
+        .collect();
+    let mut out = String::from(
+        "#separator:tab\n#html:true\n#columns:Word\tTranslation\tPhonetics\tTags\n#tags column:4\n",
+    );
+    for word in words {
+        let trans: Vec<String> = word.trans.iter().map(|t| escape_html(t)).collect();
+        let fields = [
+            field(&escape_html(&word.name)),
+            field(&trans.join("<br>")),
+            field(&escape_html(&phonetics(word))),
+            field(&tag),
+        ];
+        out.push_str(&fields.join("\t"));
+        out.push('\n');
+    }
+    out
+}
+
+/// A dictionary name for the file `file`, without its extension.
+pub fn dict_name(file: &str) -> String {
+    let stem = match file.rsplit_once('.') {
+        Some((stem, _)) if !stem.is_empty() => stem,
+        _ => file,
+    };
+    match stem.trim() {
+        "" => String::from("Anki"),
+        name => name.to_string(),
+    }
+}
+
+/// Header lines at the top of a file exported from Anki.
+#[derive(Default)]
+struct Header {
+    separator: Option<char>,
+    html: bool,
+    skip: Vec<usize>, // 0-based guid, note type, deck and tag columns
+}
+
+fn separator(name: &str) -> Option<char> {
+    match name {
+        "tab" | "Tab" | "\t" => Some('\t'),
+        "comma" | "Comma" | "," => Some(','),
+        "semicolon" | "Semicolon" | ";" => Some(';'),
+        "pipe" | "Pipe" | "|" => Some('|'),
+        "space" | "Space" | " " => Some(' '),
+        "colon" | "Colon" | ":" => Some(':'),
+        _ => None,
+    }
+}
+
+/// Split off the header, returning it and the rest of the text.
+fn header(text: &str) -> (Header, &str) {
+    let mut header = Header::default();
+    let mut rest = text;
+    while let Some(line) = rest.strip_prefix('#') {
+        let (line, next) = line.split_once('\n').unwrap_or((line, ""));
+        let (key, value) = match line.trim_end_matches('\r').split_once(':') {
+            Some(pair) => pair,
+            None => break,
+        };
+        match key {
+            "separator" => header.separator = separator(value),
+            "html" => header.html = value.trim() == "true",
+            "guid column" | "notetype column" | "deck column" | "tags column" => {
+                if let Some(column) = value.trim().parse::<usize>().ok().filter(|c| *c > 0) {
+                    header.skip.push(column - 1);
+                }
+            }
+            _ => {}
+        }
+        rest = next;
+    }
+    (header, rest)
+}
+
+/// Rows of fields, with quoted fields holding separators, doubled quotes
+/// and line breaks.
+fn records(text: &str, sep: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cur = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                cur.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if cur.is_empty() => quoted = true,
+            _ if quoted => cur.push(c),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut cur));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ if c == sep => row.push(std::mem::take(&mut cur)),
+            _ => cur.push(c),
+        }
+    }
+    if !cur.is_empty() || !row.is_empty() {
+        row.push(cur);
+        rows.push(row);
+    }
+    rows
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Plain text lines of an HTML field, without sound references.
+fn html_lines(text: &str) -> Vec<String> {
+    let mut plain = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        plain.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('>')
+            .map_or(rest.len(), |e| start + e + 1);
+        let tag = rest[start..end].to_lowercase();
+        if tag.starts_with("<br") || tag.starts_with("<div") || tag.starts_with("</div") {
+            plain.push('\n');
+        }
+        rest = &rest[end..];
+    }
+    plain.push_str(rest);
+    lines(&decode_entities(&plain))
+}
+
+fn lines(text: &str) -> Vec<String> {
+    let mut text = text.to_string();
+    while let Some(start) = text.find("[sound:") {
+        let end = text[start..]
+            .find(']')
+            .map_or(text.len(), |e| start + e + 1);
+        text.replace_range(start..end, "");
+    }
+    text.lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|l| !l.is_empty())
+        .collect()
+}
+
+/// US and UK phonetics from "US /x/ UK /y/" or a single "/x/".
+fn parse_phonetics(text: &str) -> (String, String) {
+    let marked: Vec<&str> = text.split('/').skip(1).step_by(2).collect();
+    match marked.as_slice() {
+        [] if text.is_empty() => (String::new(), String::new()),
+        [] => (text.to_string(), text.to_string()),
+        [one] => (one.to_string(), one.to_string()),
+        [us, uk, ..] => (us.to_string(), uk.to_string()),
+    }
+}
+
+/// Words of an Anki text export: the first field is the word, the second
+/// its translations and the third, if any, its phonetics.
+pub fn parse(text: &str) -> std::result::Result<Vec<Word>, AnkiError> {
+    let (header, body) = header(text.trim_start_matches('\u{feff}'));
+    let first = body.lines().next().unwrap_or_default();
+    let sep = header.separator.unwrap_or_else(|| {
+        ['\t', ';', ',']
+            .into_iter()
+            .find(|c| first.contains(*c))
+            .unwrap_or('\t')
+    });
+    let read = |field: &str| {
+        if header.html {
+            html_lines(field)
+        } else {
+            lines(field)
+        }
+    };
+
+    let words: Vec<Word> = records(body, sep)
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .enumerate()
+                .filter(|(i, _)| !header.skip.contains(i))
+                .map(|(_, f)| f)
+                .collect::<Vec<_>>()
+        })
+        .filter_map(|fields| {
+            let name = read(fields.first()?).join(" ");
+            if name.is_empty() {
+                return None;
+            }
+            let phone = fields.get(2).map(|f| read(f).join(" ")).unwrap_or_default();
+            let (usphone, ukphone) = parse_phonetics(&phone);
+            // Practice shows the first translation, so a bare word list
+            // still gets one, if empty
+            let mut trans = fields.get(1).map(|f| read(f)).unwrap_or_default();
+            if trans.is_empty() {
+                trans.push(String::new());
+            }
+            Some(Word {
+                name,
+                trans,
+                usphone,
+                ukphone,
+                ..Default::default()
+            })
+        })
+        .collect();
+    if words.is_empty() {
+        return Err(AnkiError::Empty);
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(name: &str, trans: &[&str], us: &str, uk: &str) -> Word {
+        Word {
+            name: name.to_string(),
+            trans: trans.iter().map(|t| t.to_string()).collect(),
+            usphone: us.to_string(),
+            ukphone: uk.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn export_writes_a_header_and_one_note_per_line() {
+        let words = [
+            word("cancel", &["取消", "撤销"], "'kænsl", "'kænsl"),
+            word("log in", &["登录 <web>"], "", ""),
+            word("say \"hi\"", &["打招呼"], "seɪ", "seɪ haɪ"),
+        ];
+        let text = export(&words, "keypress::My Words");
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "#separator:tab");
+        assert_eq!(lines[1], "#html:true");
+        assert_eq!(
+            lines[4],
+            "cancel\t取消<br>撤销\t/'kænsl/\tkeypress::My_Words"
+        );
+        assert_eq!(lines[5], "log in\t登录 &lt;web&gt;\t\tkeypress::My_Words");
+        assert_eq!(
+            lines[6],
+            "\"say \"\"hi\"\"\"\t打招呼\tUS /seɪ/ UK /seɪ haɪ/\tkeypress::My_Words"
+        );
+    }
+
+    #[test]
+    fn export_round_trips() {
+        let words = vec![
+            word("cancel", &["取消， 撤销", "删去"], "'kænsl", "'kænsl"),
+            word("a & b", &["x < y"], "ə", "eɪ"),
+            word("say \"hi\"", &["打招呼"], "", ""),
+        ];
+        assert_eq!(parse(&export(&words, "CET4")), Ok(words));
+    }
+
+    #[test]
+    fn parses_anki_exports() {
+        // Notes in Plain Text with HTML, guid, note type and deck columns
+        let text =
+            "#separator:tab\n#html:true\n#guid column:1\n#notetype column:2\n#deck column:3\n\
+            #tags column:7\n\
+            f0Ab\tBasic\tEnglish\tabate[sound:abate.mp3]\t减轻<br>减少&nbsp;\t/ə'beɪt/\tvocab\n\
+            f1Cd\tBasic\tEnglish\t<b>abbey</b>\t\"<div>修道院</div><div>大教堂</div>\"\t\t\n\
+            f2Ef\tBasic\tEnglish\t\t空的\t\t\n";
+        assert_eq!(
+            parse(text),
+            Ok(vec![
+                word("abate", &["减轻", "减少"], "ə'beɪt", "ə'beɪt"),
+                word("abbey", &["修道院", "大教堂"], "", ""),
+            ])
+        );
+
+        // Plain text without a header, one field per column
+        let text = "\u{feff}apple;苹果\r\nbanana;\"香蕉\n芭蕉\"\r\n";
+        assert_eq!(
+            parse(text),
+            Ok(vec![
+                word("apple", &["苹果"], "", ""),
+                word("banana", &["香蕉", "芭蕉"], "", ""),
+            ])
+        );
+
+        // A plain word list, or a note without a translation
+        assert_eq!(
+            parse("apple\npear\t\n"),
+            Ok(vec![
+                word("apple", &[""], "", ""),
+                word("pear", &[""], "", "")
+            ])
+        );
+
+        assert_eq!(parse("#separator:tab\n\n"), Err(AnkiError::Empty));
+        assert_eq!(dict_name("My Deck.txt"), "My Deck");
+        assert_eq!(dict_name(".txt"), ".txt");
+        assert_eq!(dict_name(" "), "Anki");
+        assert_eq!(parse(""), Err(AnkiError::Empty));
+    }
+}
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, Cache, Response};
+
+use crate::common::clock;
+use crate::common::storage::{self, KEY_AUDIO};
+
+// Not prefixed like the build caches, which the service worker clears
+const CACHE_NAME: &str = "pronunciations";
+const MAX_ENTRIES: usize = 3000;
+const MAX_BYTES: u64 = 30 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+struct Entry {
+    bytes: u64,
+    used: f64, // ms since the epoch
+}
+
+/// Size and last use of every cached pronunciation, keyed by url, which
+/// holds both the word and the `Pronunc`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct AudioIndex {
+    entries: HashMap<String, Entry>,
+}
+
+impl AudioIndex {
+    pub fn load() -> Self {
+        storage::load(KEY_AUDIO)
+    }
+
+    pub fn save(&self) {
+        storage::save(KEY_AUDIO, self);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.entries.values().map(|e| e.bytes).sum()
+    }
+
+    /// Note that `url` was stored or played at `now`, returning the least
+    /// recently used urls to evict to stay within the limits.
+    pub fn record(&mut self, url: &str, bytes: u64, now: f64) -> Vec<String> {
+        self.record_within(url, bytes, now, MAX_ENTRIES, MAX_BYTES)
+    }
+
+    fn record_within(
+        &mut self,
+        url: &str,
+        bytes: u64,
+        now: f64,
+        max_entries: usize,
+        max_bytes: u64,
+    ) -> Vec<String> {
+        self.entries
+            .insert(url.to_string(), Entry { bytes, used: now });
+        let mut evicted = Vec::new();
+        while self.len() > max_entries || self.bytes() > max_bytes {
+            let oldest = self
+                .entries
+                .iter()
+                .filter(|(key, _)| key.as_str() != url)
+                .min_by(|a, b| a.1.used.total_cmp(&b.1.used))
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(key) => {
+                    self.entries.remove(&key);
+                    evicted.push(key);
+                }
+                None => break,
+            }
+        }
+        evicted
+    }
+}
+
+async fn open() -> Result<Cache, JsValue> {
+    let caches = web_sys::window().unwrap().caches()?;
+    JsFuture::from(caches.open(CACHE_NAME)).await?.dyn_into()
+}
+
+async fn body(response: &Response) -> Result<Blob, JsValue> {
+    JsFuture::from(response.blob()?).await?.dyn_into()
+}
+
+/// The pronunciation at `url`, from the cache or downloaded into it.
+pub async fn fetch(url: &str) -> Result<Blob, JsValue> {
+    let cache = open().await?;
+    let cached = JsFuture::from(cache.match_with_str(url)).await?;
+    let blob = match cached.dyn_into::<Response>() {
+        Ok(response) => body(&response).await?,
+        Err(_) => {
+            let window = web_sys::window().unwrap();
+            let response: Response = JsFuture::from(window.fetch_with_str(url))
+                .await?
+                .dyn_into()?;
+            if !response.ok() {
+                return Err(JsValue::from(response.status()));
+            }
+            let blob = body(&response.clone()?).await?;
+            JsFuture::from(cache.put_with_str(url, &response)).await?;
+            blob
+        }
+    };
+
+    // Loaded right before saving, as a prefetch may be running alongside
+    let mut index = AudioIndex::load();
+    for key in index.record(url, blob.size() as u64, clock::now_ms()) {
+        let _ = cache.delete_with_str(&key);
+    }
+    index.save();
+    Ok(blob)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut index = AudioIndex::default();
+        assert!(index.record_within("a", 10, 1.0, 3, 100).is_empty());
+        assert!(index.record_within("b", 10, 2.0, 3, 100).is_empty());
+        assert!(index.record_within("c", 10, 3.0, 3, 100).is_empty());
+
+        // Playing "a" again keeps it; "b" is now the oldest
+        assert!(index.record_within("a", 10, 4.0, 3, 100).is_empty());
+        assert_eq!(index.record_within("d", 10, 5.0, 3, 100), vec!["b"]);
+        assert_eq!(index.len(), 3);
+
+        assert_eq!(index.record_within("e", 75, 6.0, 3, 100), vec!["c"]);
+        assert_eq!(index.bytes(), 95);
+
+        // Something bigger than the limit stays, alone, until the next one
+        assert_eq!(
+            index.record_within("f", 200, 7.0, 3, 100),
+            vec!["a", "d", "e"]
+        );
+        assert_eq!(index.len(), 1);
+    }
+}
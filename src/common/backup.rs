@@ -0,0 +1,413 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter, Result};
+
+use crate::common::ghost::Runs;
+use crate::common::history::{DailyProgress, History, SessionRecord};
+use crate::common::i18n::{t, tf};
+use crate::common::mastery::Mastery;
+use crate::common::rewards::Rewards;
+use crate::common::settings::Settings;
+use crate::common::snippets::{self, SnippetPack};
+use crate::common::storage::{
+    self, KEY_DAILY, KEY_HISTORY, KEY_MASTERY, KEY_REWARDS, KEY_RUNS, KEY_SETTINGS, KEY_SNIPPETS,
+    KEY_USER_DICTS,
+};
+use crate::common::user_dicts::{self, UserDict};
+
+pub const BACKUP_FORMAT: &str = "keypress-backup";
+pub const BACKUP_VERSION: u32 = 1;
+
+#[derive(Debug, PartialEq)]
+pub enum BackupError {
+    Parse(String),
+    Format,
+    Version(u32),
+}
+
+impl Display for BackupError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            BackupError::Parse(e) => write!(f, "{}", tf("backup.invalid", &[e])),
+            BackupError::Format => write!(f, "{}", t("backup.format")),
+            BackupError::Version(v) => {
+                write!(f, "{}", tf("backup.version", &[v, &BACKUP_VERSION]))
+            }
+        }
+    }
+}
+
+/// All user state in one versioned document.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Backup {
+    pub format: String,
+    pub version: u32,
+    pub exported: String,
+    #[serde(default)]
+    pub settings: Settings,
+    #[serde(default)]
+    pub daily: DailyProgress,
+    #[serde(default)]
+    pub history: History,
+    #[serde(default)]
+    pub snippets: Vec<SnippetPack>, // imported packs only
+    #[serde(default)]
+    pub runs: Runs,
+    #[serde(default)]
+    pub rewards: Rewards,
+    #[serde(default)]
+    pub mastery: Mastery,
+    #[serde(default)]
+    pub user_dicts: Vec<UserDict>,
+    #[serde(skip)]
+    pub dropped: usize, // invalid packs and dictionaries left out on parsing
+}
+
+/// What importing a backup would change, shown before applying it.
+#[derive(Debug, Default, PartialEq)]
+pub struct BackupDiff {
+    pub new_sessions: usize,
+    pub local_only_sessions: usize,
+    pub settings_changed: bool,
+    pub daily_changed: bool,
+    pub new_packs: usize,
+    pub new_dicts: usize,
+    pub new_mastery: usize, // word records newer than this browser's
+    pub dropped: usize,
+}
+
+fn session_key(rec: &SessionRecord) -> (u64, String) {
+    (rec.started.to_bits(), rec.dict.clone())
+}
+
+impl Backup {
+    pub fn new(
+        exported: String,
+        settings: Settings,
+        daily: DailyProgress,
+        history: History,
+    ) -> Self {
+        Backup {
+            format: String::from(BACKUP_FORMAT),
+            version: BACKUP_VERSION,
+            exported,
+            settings,
+            daily,
+            history,
+            snippets: Vec::new(),
+            runs: Runs::default(),
+            rewards: Rewards::default(),
+            mastery: Mastery::default(),
+            user_dicts: Vec::new(),
+            dropped: 0,
+        }
+    }
+
+    /// Snapshot of everything currently in local storage.
+    pub fn collect(exported: String) -> Self {
+        Backup {
+            snippets: snippets::imported(),
+            runs: Runs::load(),
+            rewards: Rewards::load(),
+            mastery: Mastery::load(),
+            user_dicts: user_dicts::imported(),
+            ..Backup::new(
+                exported,
+                Settings::load(),
+                storage::load(KEY_DAILY),
+                History::load(),
+            )
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn parse(text: &str) -> std::result::Result<Backup, BackupError> {
+        let value: serde_json::Value =
+            serde_json::from_str(text).map_err(|e| BackupError::Parse(e.to_string()))?;
+        if value["format"] != BACKUP_FORMAT {
+            return Err(BackupError::Format);
+        }
+        match value["version"].as_u64() {
+            Some(v) if v as u32 > BACKUP_VERSION => return Err(BackupError::Version(v as u32)),
+            Some(_) => {}
+            None => return Err(BackupError::Format),
+        }
+        let mut backup: Backup =
+            serde_json::from_value(value).map_err(|e| BackupError::Parse(e.to_string()))?;
+        backup.drop_invalid();
+        Ok(backup)
+    }
+
+    /// Check snippet packs and dictionaries as their import dialogs do,
+    /// leaving out the ones practice couldn't show, and count them.
+    pub fn drop_invalid(&mut self) {
+        let (packs, dicts) = (self.snippets.len(), self.user_dicts.len());
+        self.snippets = std::mem::take(&mut self.snippets)
+            .into_iter()
+            .filter_map(|p| p.check().ok())
+            .collect();
+        self.user_dicts = std::mem::take(&mut self.user_dicts)
+            .into_iter()
+            .filter_map(UserDict::check)
+            .collect();
+        self.dropped += packs - self.snippets.len() + dicts - self.user_dicts.len();
+    }
+
+    pub fn diff(&self, local: &Backup) -> BackupDiff {
+        let ours: HashSet<_> = local.history.sessions.iter().map(session_key).collect();
+        let theirs: HashSet<_> = self.history.sessions.iter().map(session_key).collect();
+
+        BackupDiff {
+            new_sessions: theirs.difference(&ours).count(),
+            local_only_sessions: ours.difference(&theirs).count(),
+            settings_changed: self.settings != local.settings,
+            daily_changed: self.daily != local.daily,
+            new_packs: self
+                .snippets
+                .iter()
+                .filter(|p| !local.snippets.iter().any(|l| l.name == p.name))
+                .count(),
+            new_dicts: self
+                .user_dicts
+                .iter()
+                .filter(|d| !local.user_dicts.iter().any(|l| l.name == d.name))
+                .count(),
+            new_mastery: local.mastery.updates(&self.mastery),
+            dropped: self.dropped,
+        }
+    }
+
+    /// Combine `self` into `local`: history, snippet packs and imported
+    /// dictionaries are the union of both sides, the faster run of each
+    /// chapter, the better of each reward and the most typed record of each
+    /// word are kept, today's progress takes the larger counts and local
+    /// settings win.
+    pub fn merge_into(&self, local: &Backup) -> Backup {
+        let mut merged = local.clone();
+
+        let mut seen: HashSet<_> = merged.history.sessions.iter().map(session_key).collect();
+        for rec in self.history.sessions.iter() {
+            if seen.insert(session_key(rec)) {
+                merged.history.sessions.push(rec.clone());
+            }
+        }
+        merged
+            .history
+            .sessions
+            .sort_by(|a, b| a.started.total_cmp(&b.started));
+
+        for pack in self.snippets.iter() {
+            if !merged.snippets.iter().any(|p| p.name == pack.name) {
+                merged.snippets.push(pack.clone());
+            }
+        }
+
+        for dict in self.user_dicts.iter() {
+            if !merged.user_dicts.iter().any(|d| d.name == dict.name) {
+                merged.user_dicts.push(dict.clone());
+            }
+        }
+
+        for run in self.runs.best.iter() {
+            merged.runs.offer(run.clone());
+        }
+        merged.rewards.merge(&self.rewards);
+        merged.mastery.merge(&self.mastery);
+
+        if self.daily.date > merged.daily.date {
+            merged.daily = self.daily.clone();
+        } else if self.daily.date == merged.daily.date {
+            merged.daily.seconds = merged.daily.seconds.max(self.daily.seconds);
+            merged.daily.words = merged.daily.words.max(self.daily.words);
+        }
+
+        merged
+    }
+
+    /// Write every section back to local storage.
+    pub fn apply(&self) {
+        storage::save(KEY_SETTINGS, &self.settings);
+        storage::save(KEY_DAILY, &self.daily);
+        storage::save(KEY_HISTORY, &self.history);
+        storage::save(KEY_SNIPPETS, &self.snippets);
+        storage::save(KEY_RUNS, &self.runs);
+        storage::save(KEY_REWARDS, &self.rewards);
+        storage::save(KEY_MASTERY, &self.mastery);
+        storage::save(KEY_USER_DICTS, &self.user_dicts);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::dict::Word;
+    use crate::common::settings::DailyGoal;
+    use crate::common::snippets::{Lang, Snippet};
+
+    fn session(started: f64, dict: &str, words: u32) -> SessionRecord {
+        SessionRecord {
+            date: String::from("2026-10-19"),
+            started,
+            dict: String::from(dict),
+            chapter: 1,
+            seconds: 60,
+            words,
+            chars: words * 5,
+            keystrokes: words * 6,
+            mistakes: words,
+        }
+    }
+
+    fn sample() -> Backup {
+        let settings = Settings {
+            pomodoro: true,
+            daily_goal: DailyGoal::Words(50),
+            ..Default::default()
+        };
+        let daily = DailyProgress {
+            date: String::from("2026-10-19"),
+            seconds: 600,
+            words: 42,
+        };
+        let history = History {
+            sessions: vec![session(1.0, "CET4", 10), session(2.0, "TOEFL", 20)],
+        };
+        Backup::new(String::from("2026-10-19"), settings, daily, history)
+    }
+
+    #[test]
+    fn round_trip() {
+        let backup = sample();
+        let parsed = Backup::parse(&backup.to_json()).unwrap();
+        assert_eq!(parsed, backup);
+    }
+
+    #[test]
+    fn missing_sections_use_defaults() {
+        let text = r#"{ "format": "keypress-backup", "version": 1, "exported": "2026-10-19" }"#;
+        let parsed = Backup::parse(text).unwrap();
+        assert_eq!(parsed.settings, Settings::default());
+        assert!(parsed.history.sessions.is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        assert!(matches!(
+            Backup::parse("not json"),
+            Err(BackupError::Parse(_))
+        ));
+        assert_eq!(
+            Backup::parse(r#"{ "version": 1 }"#),
+            Err(BackupError::Format)
+        );
+        assert_eq!(
+            Backup::parse(r#"{ "format": "keypress-backup" }"#),
+            Err(BackupError::Format)
+        );
+        let newer = format!(
+            r#"{{ "format": "keypress-backup", "version": {}, "exported": "" }}"#,
+            BACKUP_VERSION + 1
+        );
+        assert_eq!(
+            Backup::parse(&newer),
+            Err(BackupError::Version(BACKUP_VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn drops_packs_and_dictionaries_practice_cant_show() {
+        let mut backup = sample();
+        backup.snippets = vec![
+            SnippetPack {
+                name: String::from("Empty"),
+                ..Default::default()
+            },
+            SnippetPack {
+                name: String::from(" Loops "),
+                lang: Lang::Rust,
+                snippets: vec![Snippet {
+                    title: String::from("loop"),
+                    code: String::from("loop {}"),
+                }],
+            },
+        ];
+        let word = |name: &str| Word {
+            name: String::from(name),
+            ..Default::default()
+        };
+        backup.user_dicts = vec![
+            UserDict {
+                name: String::from("Nothing"),
+                words: vec![word(" ")],
+            },
+            UserDict {
+                name: String::from("Deck"),
+                words: vec![word("abate"), word("")],
+            },
+        ];
+
+        let parsed = Backup::parse(&backup.to_json()).unwrap();
+        assert_eq!(parsed.dropped, 2);
+        assert_eq!(parsed.diff(&sample()).dropped, 2);
+        assert_eq!(parsed.snippets.len(), 1);
+        assert_eq!(parsed.snippets[0].name, "Loops");
+        assert_eq!(parsed.user_dicts.len(), 1);
+        assert_eq!(parsed.user_dicts[0].words.len(), 1);
+        assert_eq!(parsed.user_dicts[0].words[0].trans, vec![String::new()]);
+    }
+
+    #[test]
+    fn diff_and_merge() {
+        let local = sample();
+        let mut imported = sample();
+        imported.settings.pomodoro = false;
+        imported.daily.words = 50;
+        imported.history.sessions.remove(0);
+        imported.history.sessions.push(session(3.0, "CET6", 30));
+        imported.snippets.push(SnippetPack {
+            name: String::from("Python basics"),
+            lang: Lang::Python,
+            snippets: Vec::new(),
+        });
+        imported.user_dicts.push(UserDict {
+            name: String::from("My Deck"),
+            words: vec![Word {
+                name: String::from("abate"),
+                ..Default::default()
+            }],
+        });
+        imported.mastery.record("CET4", "apple", 0);
+        imported.mastery.record("CET4", "pear", 1);
+
+        let diff = imported.diff(&local);
+        assert_eq!(
+            diff,
+            BackupDiff {
+                new_sessions: 1,
+                local_only_sessions: 1,
+                settings_changed: true,
+                daily_changed: true,
+                new_packs: 1,
+                new_dicts: 1,
+                new_mastery: 2,
+                dropped: 0,
+            }
+        );
+
+        let merged = imported.merge_into(&local);
+        let started: Vec<f64> = merged.history.sessions.iter().map(|s| s.started).collect();
+        assert_eq!(started, vec![1.0, 2.0, 3.0]);
+        assert!(merged.settings.pomodoro);
+        assert_eq!(merged.daily.words, 50);
+        assert_eq!(merged.daily.seconds, 600);
+        assert_eq!(merged.snippets, imported.snippets);
+        assert_eq!(merged.user_dicts, imported.user_dicts);
+        assert_eq!(merged.mastery, imported.mastery);
+        assert_eq!(merged.diff(&merged), BackupDiff::default());
+
+        assert_eq!(imported.merge_into(&merged), merged);
+    }
+}
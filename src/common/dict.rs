@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result};
+use std::slice::Iter;
+
+pub use keypress_engine::dict::{nr_chapter, words, Word, CHAPTER_SIZE, DICT_INDEX, DICT_MAP};
+
+use crate::common::i18n::t;
+use crate::common::snippets;
+use crate::common::user_dicts;
+
+const CORPUS_DAILY: &str = include_str!("../content/sentences/daily.json");
+const CORPUS_PROGRAMMING: &str = include_str!("../content/sentences/programming.json");
+const CORPUS_PARAGRAPHS: &str = include_str!("../content/sentences/paragraphs.json");
+
+// Appended to a dictionary name to practise the example sentences of its words
+const EXAMPLES_SUFFIX: &str = " examples";
+
+lazy_static::lazy_static! {
+    /// Sentence and paragraph corpora, stored in the same shape as the
+    /// dictionaries with whole sentences as `name`.
+    pub static ref CORPUS_INDEX: Vec<&'static str> = vec![
+        "Daily",
+        "Programming",
+        "Paragraphs",
+    ];
+
+    pub static ref CORPUS_MAP: HashMap<String, &'static str> =
+    {
+        let mut map = HashMap::new();
+        map.insert(CORPUS_INDEX[0].to_string(), CORPUS_DAILY);
+        map.insert(CORPUS_INDEX[1].to_string(), CORPUS_PROGRAMMING);
+        map.insert(CORPUS_INDEX[2].to_string(), CORPUS_PARAGRAPHS);
+        map
+    };
+
+    static ref EXAMPLE_INDEX: Vec<String> = DICT_INDEX
+        .iter()
+        .filter(|level| !examples(level).is_empty())
+        .map(|level| format!("{}{}", level, EXAMPLES_SUFFIX))
+        .collect();
+}
+
+/// What the entries of a level are: single words, sentences or code.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    Words,
+    Sentences,
+    Code,
+}
+
+impl Mode {
+    pub fn iterator() -> Iter<'static, Mode> {
+        static MODE_S: [Mode; 3] = [Mode::Words, Mode::Sentences, Mode::Code];
+        MODE_S.iter()
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Mode::Words => "words",
+            Mode::Sentences => "sentences",
+            Mode::Code => "code",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Mode> {
+        Mode::iterator().find(|m| m.code() == code).copied()
+    }
+
+    /// Every level that can be practised in this mode.
+    pub fn levels(&self) -> Vec<String> {
+        match self {
+            Mode::Words => DICT_INDEX
+                .iter()
+                .map(|d| d.to_string())
+                .chain(user_dicts::names())
+                .collect(),
+            Mode::Sentences => corpora(),
+            Mode::Code => snippets::names(),
+        }
+    }
+}
+
+impl Display for Mode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Mode::Words => write!(f, "{}", t("keyboard.words")),
+            Mode::Sentences => write!(f, "{}", t("keyboard.sentences")),
+            Mode::Code => write!(f, "{}", t("keyboard.code")),
+        }
+    }
+}
+
+pub fn mode(level: &str) -> Mode {
+    if DICT_MAP.contains_key(level) || user_dicts::find(level).is_some() {
+        Mode::Words
+    } else if is_corpus(level) {
+        Mode::Sentences
+    } else {
+        Mode::Code
+    }
+}
+
+/// Parse an embedded dictionary, corpus or snippet pack by the name
+/// listed in `Mode::levels`.
+pub fn load(level: &str) -> Option<serde_json::Value> {
+    if let Some(text) = DICT_MAP.get(level).or_else(|| CORPUS_MAP.get(level)) {
+        return serde_json::from_str(text).ok();
+    }
+    if let Some(pack) = snippets::find(level) {
+        return Some(pack.entries());
+    }
+    if let Some(dict) = user_dicts::find(level) {
+        return serde_json::to_value(dict.words).ok();
+    }
+    let entries = examples(level.strip_suffix(EXAMPLES_SUFFIX)?);
+    if entries.is_empty() {
+        return None;
+    }
+    serde_json::to_value(entries).ok()
+}
+
+/// Words of a built-in or imported dictionary.
+pub fn word_list(level: &str) -> Vec<Word> {
+    if DICT_MAP.contains_key(level) {
+        return words(level);
+    }
+    user_dicts::find(level).map(|d| d.words).unwrap_or_default()
+}
+
+/// Whether `name` is already used by a built-in level.
+pub fn is_builtin(name: &str) -> bool {
+    DICT_MAP.contains_key(name) || is_corpus(name) || snippets::find(name).is_some()
+}
+
+/// Names of every sentence corpus, including the example sentences of
+/// dictionaries that have any.
+pub fn corpora() -> Vec<String> {
+    CORPUS_INDEX
+        .iter()
+        .map(|c| c.to_string())
+        .chain(EXAMPLE_INDEX.iter().cloned())
+        .collect()
+}
+
+/// Whether `level` is practised as sentences rather than single words.
+fn is_corpus(level: &str) -> bool {
+    CORPUS_MAP.contains_key(level) || level.ends_with(EXAMPLES_SUFFIX)
+}
+
+/// Example sentences of a dictionary as corpus entries.
+fn examples(level: &str) -> Vec<Word> {
+    words(level)
+        .into_iter()
+        .flat_map(|w| w.examples)
+        .map(|e| Word {
+            name: e.text,
+            trans: vec![e.trans],
+            ..Default::default()
+        })
+        .collect()
+}
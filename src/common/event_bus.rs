@@ -2,9 +2,23 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use yew::worker::*;
 
-#[derive(Serialize, Deserialize, Debug)]
+use crate::common::rewards::Reward;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Request {
     EventBusMsg(String),
+    SessionStarted,
+    SessionPaused,
+    WordTyped,
+    PauseSession,
+    CapsLock(bool),
+    Composing(bool),                            // an input method is active
+    RaceStart { dict: String, chapter: usize }, // a room race began
+    RaceProgress { words: usize, chars: usize },
+    RaceFinished { ms: u32 },
+    SyncNow,
+    Synced, // another device's state was stored, so reload what is held
+    Rewarded(Reward),
 }
 
 pub struct EventBus {
@@ -16,7 +30,7 @@ impl Agent for EventBus {
     type Reach = Context<Self>;
     type Message = ();
     type Input = Request;
-    type Output = String;
+    type Output = Request;
 
     fn create(link: AgentLink<Self>) -> Self {
         Self {
@@ -28,12 +42,8 @@ impl Agent for EventBus {
     fn update(&mut self, _msg: Self::Message) {}
 
     fn handle_input(&mut self, msg: Self::Input, _id: HandlerId) {
-        match msg {
-            Request::EventBusMsg(s) => {
-                for sub in self.subscribers.iter() {
-                    self.link.respond(*sub, s.clone());
-                }
-            }
+        for sub in self.subscribers.iter() {
+            self.link.respond(*sub, msg.clone());
         }
     }
 
@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+
+use crate::common::storage::{self, KEY_RUNS};
+
+/// One complete run through a chapter. Times are ms since the first key.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Run {
+    pub dict: String,
+    pub chapter: usize,
+    pub date: String,
+    pub keys: Vec<u32>,   // every correct key
+    pub splits: Vec<u32>, // end of every word
+}
+
+impl Run {
+    pub fn total(&self) -> u32 {
+        self.splits.last().copied().unwrap_or(0)
+    }
+
+    pub fn chars_at(&self, ms: u32) -> usize {
+        self.keys.partition_point(|t| *t <= ms)
+    }
+
+    pub fn words_at(&self, ms: u32) -> usize {
+        self.splits.partition_point(|t| *t <= ms)
+    }
+
+    fn is_for(&self, dict: &str, chapter: usize) -> bool {
+        self.dict == dict && self.chapter == chapter
+    }
+}
+
+/// A chapter being raced, recorded as it is typed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Race {
+    pub run: Run,
+    pub words: usize,
+    pub started: Option<f64>, // ms since the epoch
+    pub ghost: Option<Run>,
+}
+
+impl Race {
+    pub fn new(dict: &str, chapter: usize, words: usize, ghost: Option<Run>) -> Self {
+        Race {
+            run: Run {
+                dict: String::from(dict),
+                chapter,
+                ..Default::default()
+            },
+            words,
+            started: None,
+            ghost,
+        }
+    }
+
+    pub fn elapsed(&self, now: f64) -> u32 {
+        self.started.map_or(0, |s| (now - s).max(0.0) as u32)
+    }
+
+    pub fn key(&mut self, now: f64) {
+        self.started.get_or_insert(now);
+        let ms = self.elapsed(now);
+        self.run.keys.push(ms);
+    }
+
+    /// Record the end of a word; true once the whole chapter is done.
+    pub fn split(&mut self, now: f64) -> bool {
+        let ms = self.elapsed(now);
+        self.run.splits.push(ms);
+        self.run.splits.len() >= self.words
+    }
+
+    /// Difference to the ghost at the last split, negative when ahead.
+    pub fn delta(&self) -> Option<i64> {
+        let i = self.run.splits.len().checked_sub(1)?;
+        let ghost = self.ghost.as_ref()?.splits.get(i)?;
+        Some(self.run.splits[i] as i64 - *ghost as i64)
+    }
+}
+
+/// The best run of every chapter raced so far.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Runs {
+    pub best: Vec<Run>,
+}
+
+impl Runs {
+    pub fn load() -> Self {
+        storage::load(KEY_RUNS)
+    }
+
+    pub fn best(&self, dict: &str, chapter: usize) -> Option<&Run> {
+        self.best.iter().find(|r| r.is_for(dict, chapter))
+    }
+
+    /// Keep `run` if it beats the stored best; true if it did.
+    pub fn offer(&mut self, run: Run) -> bool {
+        match self
+            .best
+            .iter_mut()
+            .find(|r| r.is_for(&run.dict, run.chapter))
+        {
+            Some(best) if best.total() <= run.total() => false,
+            Some(best) => {
+                *best = run;
+                true
+            }
+            None => {
+                self.best.push(run);
+                true
+            }
+        }
+    }
+
+    /// Store a finished run, returning the previous best and whether the
+    /// new run replaced it.
+    pub fn record(run: Run) -> (Option<Run>, bool) {
+        let mut runs = Runs::load();
+        let previous = runs.best(&run.dict, run.chapter).cloned();
+        let new_best = runs.offer(run);
+        if new_best {
+            storage::save(KEY_RUNS, &runs);
+        }
+        (previous, new_best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(dict: &str, chapter: usize, splits: &[u32]) -> Run {
+        Run {
+            dict: String::from(dict),
+            chapter,
+            keys: splits.iter().flat_map(|s| [s - 50, *s]).collect(),
+            splits: splits.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn replays_progress() {
+        let ghost = run("CET4", 1, &[1000, 2500, 4000]);
+        assert_eq!(ghost.total(), 4000);
+        assert_eq!(ghost.words_at(0), 0);
+        assert_eq!(ghost.words_at(2500), 2);
+        assert_eq!(ghost.chars_at(2460), 3);
+        assert_eq!(ghost.words_at(10_000), 3);
+    }
+
+    #[test]
+    fn races_against_the_ghost() {
+        let ghost = run("CET4", 1, &[1000, 2500]);
+        let mut race = Race::new("CET4", 1, 2, Some(ghost));
+        assert_eq!(race.delta(), None);
+
+        race.key(10_000.0);
+        race.key(10_400.0);
+        assert!(!race.split(10_800.0));
+        assert_eq!(race.delta(), Some(-200));
+        race.key(13_000.0);
+        assert!(race.split(13_000.0));
+        assert_eq!(race.delta(), Some(500));
+        assert_eq!(race.run.keys, vec![0, 400, 3000]);
+        assert_eq!(race.run.total(), 3000);
+    }
+
+    #[test]
+    fn keeps_only_the_best_run() {
+        let mut runs = Runs::default();
+        assert!(runs.offer(run("CET4", 1, &[3000])));
+        assert!(runs.offer(run("CET4", 2, &[5000])));
+        assert!(!runs.offer(run("CET4", 1, &[3500])));
+        assert!(runs.offer(run("CET4", 1, &[2000])));
+        assert_eq!(runs.best.len(), 2);
+        assert_eq!(runs.best("CET4", 1).unwrap().total(), 2000);
+        assert!(runs.best("CET6", 1).is_none());
+    }
+}
@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub use keypress_engine::typing::{accuracy, wpm};
+
+use crate::common::clock;
+use crate::common::storage::{self, KEY_HISTORY};
+
+/// One finished practice session, from pressing Start until it is paused.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct SessionRecord {
+    pub date: String,
+    pub started: f64, // ms since the epoch
+    pub dict: String,
+    pub chapter: usize,
+    pub seconds: u64,
+    pub words: u32,
+    pub chars: u32, // correctly typed characters
+    pub keystrokes: u32,
+    pub mistakes: u32,
+}
+
+impl SessionRecord {
+    pub fn wpm(&self) -> f64 {
+        wpm(self.chars, self.seconds as f64)
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        accuracy(self.keystrokes, self.mistakes)
+    }
+}
+
+/// Practice done on the current calendar day, kept across reloads.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct DailyProgress {
+    pub date: String,
+    pub seconds: u64,
+    pub words: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Totals {
+    pub sessions: u32,
+    pub seconds: u64,
+    pub words: u32,
+    pub chars: u32,
+    pub keystrokes: u32,
+    pub mistakes: u32,
+}
+
+impl Totals {
+    fn add(&mut self, rec: &SessionRecord) {
+        self.sessions += 1;
+        self.seconds += rec.seconds;
+        self.words += rec.words;
+        self.chars += rec.chars;
+        self.keystrokes += rec.keystrokes;
+        self.mistakes += rec.mistakes;
+    }
+
+    pub fn wpm(&self) -> f64 {
+        wpm(self.chars, self.seconds as f64)
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        accuracy(self.keystrokes, self.mistakes)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct History {
+    pub sessions: Vec<SessionRecord>,
+}
+
+impl History {
+    pub fn load() -> Self {
+        storage::load(KEY_HISTORY)
+    }
+
+    pub fn append(record: SessionRecord) {
+        let mut history = History::load();
+        history.sessions.push(record);
+        storage::save(KEY_HISTORY, &history);
+    }
+
+    /// Totals keyed by day number (days since the epoch).
+    pub fn daily(&self) -> BTreeMap<i64, Totals> {
+        let mut days: BTreeMap<i64, Totals> = BTreeMap::new();
+        for rec in self.sessions.iter() {
+            if let Some(day) = clock::parse_day(&rec.date) {
+                days.entry(day).or_default().add(rec);
+            }
+        }
+        days
+    }
+
+    /// Day numbers with any practice.
+    pub fn days(&self) -> BTreeSet<i64> {
+        self.sessions
+            .iter()
+            .filter_map(|rec| clock::parse_day(&rec.date))
+            .collect()
+    }
+
+    /// Totals keyed by the day number of each week's Monday.
+    pub fn weekly(&self) -> BTreeMap<i64, Totals> {
+        let mut weeks: BTreeMap<i64, Totals> = BTreeMap::new();
+        for rec in self.sessions.iter() {
+            if let Some(day) = clock::parse_day(&rec.date) {
+                weeks.entry(clock::week_start(day)).or_default().add(rec);
+            }
+        }
+        weeks
+    }
+
+    pub fn total(&self) -> Totals {
+        let mut total = Totals::default();
+        self.sessions.iter().for_each(|rec| total.add(rec));
+        total
+    }
+
+    /// Consecutive days of practice ending today, or yesterday if today
+    /// hasn't been practised yet.
+    pub fn streak(&self, today: i64) -> u32 {
+        let days = self.daily();
+        let mut day = if days.contains_key(&today) {
+            today
+        } else {
+            today - 1
+        };
+        let mut streak = 0;
+        while days.contains_key(&day) {
+            streak += 1;
+            day -= 1;
+        }
+        streak
+    }
+
+    pub fn longest_streak(&self) -> u32 {
+        let mut longest = 0;
+        let mut run = 0;
+        let mut prev: Option<i64> = None;
+        for day in self.daily().keys() {
+            run = match prev {
+                Some(p) if p + 1 == *day => run + 1,
+                _ => 1,
+            };
+            longest = longest.max(run);
+            prev = Some(*day);
+        }
+        longest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(dates: &[&str]) -> History {
+        History {
+            sessions: dates
+                .iter()
+                .map(|date| SessionRecord {
+                    date: date.to_string(),
+                    words: 10,
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+
+    fn day(date: &str) -> i64 {
+        clock::parse_day(date).unwrap()
+    }
+
+    #[test]
+    fn streak_runs_across_month_and_year_ends() {
+        let new_year = history(&["2025-12-30", "2025-12-31", "2026-01-01", "2026-01-01"]);
+        assert_eq!(new_year.streak(day("2026-01-01")), 3);
+        // Not practised yet today, so the streak still counts to yesterday
+        assert_eq!(new_year.streak(day("2026-01-02")), 3);
+        assert_eq!(new_year.streak(day("2026-01-03")), 0);
+
+        let leap = history(&["2024-02-28", "2024-02-29", "2024-03-01"]);
+        assert_eq!(leap.streak(day("2024-03-01")), 3);
+        assert_eq!(History::default().streak(day("2024-03-01")), 0);
+    }
+
+    #[test]
+    fn longest_streak_skips_gaps() {
+        let history = history(&[
+            "2026-03-30",
+            "2026-03-31",
+            "2026-04-01",
+            "2026-04-03",
+            "2026-04-04",
+            "not a date",
+        ]);
+        assert_eq!(history.longest_streak(), 3);
+        assert_eq!(history.streak(day("2026-04-04")), 2);
+        assert_eq!(History::default().longest_streak(), 0);
+    }
+
+    #[test]
+    fn weekly_totals_start_on_monday() {
+        // Sunday 2026-01-04 ends the week of Monday 2025-12-29
+        let history = history(&["2025-12-29", "2026-01-04", "2026-01-05"]);
+        let weeks = history.weekly();
+        assert_eq!(weeks.len(), 2);
+        assert_eq!(weeks[&day("2025-12-29")].sessions, 2);
+        assert_eq!(weeks[&day("2026-01-05")].words, 10);
+        assert_eq!(history.days().len(), 3);
+    }
+}
@@ -0,0 +1,659 @@
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result};
+use std::slice::Iter;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Locale {
+    En,
+    ZhCn,
+}
+
+impl Locale {
+    pub fn iterator() -> Iter<'static, Locale> {
+        static LOCALE_S: [Locale; 2] = [Locale::En, Locale::ZhCn];
+        LOCALE_S.iter()
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::ZhCn => "zh-CN",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Locale> {
+        Locale::iterator().find(|l| l.code() == code).copied()
+    }
+
+    /// Pick a locale from the browser's preferred language.
+    pub fn detect() -> Locale {
+        let language = web_sys::window()
+            .and_then(|w| w.navigator().language())
+            .unwrap_or_default();
+        if language.to_lowercase().starts_with("zh") {
+            Locale::ZhCn
+        } else {
+            Locale::En
+        }
+    }
+}
+
+impl Display for Locale {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Locale::En => write!(f, "English"),
+            Locale::ZhCn => write!(f, "简体中文"),
+        }
+    }
+}
+
+// Templates use `{}` placeholders, filled in order by `tf`.
+const EN: &[(&str, &str)] = &[
+    ("nav.practice", "Practice"),
+    ("nav.dictionary", "Dictionary"),
+    ("nav.stats", "Stats"),
+    ("nav.settings", "Settings"),
+    ("nav.auto_locale", "Auto language"),
+    ("footer.powered_by", "Powered by "),
+    ("footer.using", " using "),
+    ("footer.crates_from", " and crates from "),
+    ("footer.end", ""),
+    ("keyboard.start", "Start"),
+    ("keyboard.pause", "Pause"),
+    ("keyboard.chapter", "Chapter {}"),
+    ("keyboard.prev", "Prev"),
+    ("keyboard.next", "Next"),
+    ("keyboard.words", "Words"),
+    ("keyboard.sentences", "Sentences"),
+    ("keyboard.code", "Code"),
+    ("keyboard.sentence_result", "Last: {} WPM, {}% accuracy"),
+    ("pronunc.ame", "American pronunciation"),
+    ("pronunc.bre", "British pronunciation"),
+    ("body.practice", "Practice {}"),
+    ("body.pomodoro", "Pomodoro"),
+    ("body.focus", "Focus {}"),
+    ("body.break", "Break {}"),
+    ("body.break_start", "Time for a {} minute break."),
+    ("body.break_over", "Break is over, press Start to continue."),
+    ("body.skip_break", "Skip break"),
+    ("body.unit_min", "min"),
+    ("body.unit_words", "words"),
+    ("goal.off", "No daily goal"),
+    ("goal.minutes", "{} minutes a day"),
+    ("goal.words", "{} words a day"),
+    ("dict.search", "Search words or translations"),
+    ("dict.any_length", "Any length"),
+    ("dict.letters", "{} letters"),
+    ("dict.count", "{} of {} words"),
+    ("dict.chapters", "{} chapters"),
+    ("dict.practise_here", "Practise from here"),
+    ("dict.any_state", "Any progress"),
+    ("dict.state_new", "New"),
+    ("dict.state_learning", "Learning"),
+    ("dict.state_mastered", "Mastered"),
+    ("stats.sessions", "Sessions: {}"),
+    ("stats.time", "Time: {}"),
+    ("stats.words", "Words: {}"),
+    ("stats.streak", "Streak: {} days"),
+    ("stats.longest", "Longest: {} days"),
+    ("stats.day_words", "{}: {} words"),
+    ("stats.days", "Days"),
+    ("stats.weeks", "Weeks"),
+    ("stats.wpm_max", "WPM (max {})"),
+    ("stats.accuracy_axis", "Accuracy (0-100%)"),
+    ("stats.date", "Date"),
+    ("stats.dictionary", "Dictionary"),
+    ("stats.chapter", "Chapter"),
+    ("stats.duration", "Duration"),
+    ("stats.col_words", "Words"),
+    ("stats.wpm", "WPM"),
+    ("stats.accuracy", "Accuracy"),
+    ("settings.practice", "Practice"),
+    ("settings.pomodoro_work", "Pomodoro focus (minutes)"),
+    ("settings.pomodoro_break", "Pomodoro break (minutes)"),
+    ("settings.backup", "Backup"),
+    ("settings.export", "Export backup"),
+    ("settings.exported_on", "Backup exported on {}"),
+    ("settings.new_sessions", "{} sessions not in this browser"),
+    ("settings.new_packs", "{} snippet packs not in this browser"),
+    ("settings.new_dicts", "{} imported dictionaries not in this browser"),
+    ("settings.new_mastery", "{} words practised more than in this browser"),
+    (
+        "settings.dropped",
+        "{} empty snippet packs or dictionaries will be left out",
+    ),
+    (
+        "settings.local_sessions",
+        "{} sessions only in this browser",
+    ),
+    ("settings.settings_changed", "Settings changed"),
+    ("settings.settings_unchanged", "Settings unchanged"),
+    ("settings.daily_changed", "Today's progress changed"),
+    ("settings.daily_unchanged", "Today's progress unchanged"),
+    ("settings.merge", "Merge"),
+    ("settings.replace", "Replace"),
+    ("settings.cancel", "Cancel"),
+    ("settings.merged", "Backup merged."),
+    ("settings.restored", "Backup restored."),
+    ("backup.invalid", "Not a valid backup file: {}"),
+    ("backup.format", "Not a KeyPress backup file"),
+    (
+        "backup.version",
+        "Backup version {} is newer than supported version {}",
+    ),
+    ("settings.appearance", "Appearance"),
+    ("settings.theme", "Theme"),
+    ("theme.auto", "Follow system"),
+    ("theme.light", "Light"),
+    ("theme.dark", "Dark"),
+    ("theme.high_contrast", "High contrast"),
+    ("settings.code", "Code snippets"),
+    ("settings.auto_close", "Auto-close brackets"),
+    ("settings.import_snippets", "Import snippet pack"),
+    ("settings.snippet_pack", "{} ({} snippets)"),
+    (
+        "settings.snippets_imported",
+        "Imported {} snippets into {}.",
+    ),
+    ("settings.snippets_invalid", "Not a valid snippet pack: {}"),
+    ("settings.remove", "Remove"),
+    ("settings.phrases", "Separators in phrases"),
+    ("settings.space", "Space"),
+    ("settings.hyphen", "Hyphen"),
+    ("settings.apostrophe", "Apostrophe"),
+    ("policy.strict", "Must be typed"),
+    ("policy.optional", "Optional"),
+    ("policy.auto_skip", "Skipped automatically"),
+    ("settings.case", "Letter case"),
+    ("settings.audio", "Sound"),
+    ("settings.volume", "Volume"),
+    ("settings.percent", "{}%"),
+    ("settings.clicks", "Key clicks"),
+    ("settings.feedback", "Correct and wrong sounds"),
+    ("settings.pronunciation", "Pronounce words"),
+    ("settings.repeat", "Pronounce each word"),
+    ("settings.times", "{} ×"),
+    ("settings.interval", "Pause between repeats"),
+    ("settings.seconds", "{} s"),
+    ("settings.rate", "Pronunciation speed"),
+    ("settings.key_sounds", "Key sounds"),
+    ("settings.preview", "Preview"),
+    ("settings.import_pack", "Upload your own samples"),
+    ("settings.pack_name", "Pack name"),
+    ("settings.pack_samples", "{} ({} samples)"),
+    ("settings.pack_imported", "Imported {} with {} samples."),
+    ("settings.pack_invalid", "Could not import the sound pack: {}"),
+    ("pack.classic", "Classic"),
+    ("pack.blue", "Mechanical (blue)"),
+    ("pack.brown", "Mechanical (brown)"),
+    ("pack.typewriter", "Typewriter"),
+    ("pack.soft", "Soft"),
+    ("pack.error_name", "give the pack a name"),
+    ("pack.error_count", "choose 1 to {} audio files"),
+    ("pack.error_file", "{} is not an audio file under 1 MB"),
+    ("variation.same", "Same for every key"),
+    ("variation.per_key", "Different per key"),
+    ("variation.random", "Random"),
+    ("case.exact", "Exact"),
+    ("case.insensitive", "Ignore case"),
+    ("case.auto_capitalise", "Capitalise first letter for me"),
+    ("keyboard.caps_lock", "Caps Lock is on"),
+    ("keyboard.race", "Race my best run"),
+    ("keyboard.prefetch", "Download this chapter's audio"),
+    ("keyboard.prefetching", "Downloading {} of {}…"),
+    ("keyboard.prefetched", "{} of {} pronunciations saved for offline use."),
+    ("race.you", "You: {} / {}"),
+    ("race.ghost", "Best run: {} / {}"),
+    ("race.no_ghost", "No earlier run of this chapter yet."),
+    ("race.split", "Word {}: {} s"),
+    ("race.ahead", "{} s ahead"),
+    ("race.behind", "{} s behind"),
+    ("race.finished", "Chapter finished in {} s."),
+    ("race.first_run", "This is now your best run."),
+    ("race.new_best", "New best, {} s faster!"),
+    ("race.best", "Your best is {} s."),
+    ("race.col_word", "Word"),
+    ("race.col_you", "You (s)"),
+    ("race.col_ghost", "Best (s)"),
+    ("race.col_delta", "Difference (s)"),
+    ("multi.title", "Race with friends"),
+    ("multi.server", "Race server, e.g. ws://localhost:9001"),
+    ("multi.room", "Room"),
+    ("multi.name", "Your name"),
+    ("multi.join", "Join"),
+    ("multi.leave", "Leave"),
+    ("multi.start", "Start race"),
+    (
+        "multi.waiting",
+        "Start a race on the current chapter when everyone is here.",
+    ),
+    ("multi.racing", "Racing {} chapter {}."),
+    ("multi.you", "{} (you)"),
+    ("multi.place", "#{} in {} s"),
+    ("multi.lost", "Lost the connection to the race server."),
+    ("multi.no_name", "Enter a name to race under."),
+    ("sync.title", "Sync"),
+    ("sync.server", "Sync server, e.g. http://localhost:9002"),
+    ("sync.token", "Token"),
+    ("sync.save", "Save and sync"),
+    ("sync.hint", "Settings, progress and practice history are synced through your own keypress-sync-server. Practice keeps working offline and catches up when the server is reachable again."),
+    ("sync.never", "Not synced yet."),
+    ("sync.last", "Last synced {}."),
+    ("sync.syncing", "Syncing…"),
+    ("update.available", "A new version of KeyPress is available."),
+    ("update.reload", "Reload"),
+    ("update.later", "Later"),
+    ("nav.profile", "Profile"),
+    ("keyboard.placement", "Not sure where to start? Take the placement test"),
+    ("anki.title", "Anki"),
+    ("anki.import", "Import an Anki text export as a dictionary"),
+    ("anki.hint", "In Anki, use File › Export with \"Notes in Plain Text\". The first field becomes the word, the second its translation and the third its phonetics."),
+    ("anki.imported", "Imported {} words as \"{}\"."),
+    ("anki.invalid", "Could not import: {}"),
+    ("anki.error_empty", "no words found"),
+    ("anki.dict", "{} ({} words)"),
+    ("anki.whole", "Whole dictionary"),
+    ("anki.mistakes", "Words with mistakes"),
+    ("anki.export", "Export for Anki"),
+    ("anki.exported", "Exported {} words."),
+    ("anki.nothing", "Nothing to export."),
+    ("keyboard.skip_mastered", "Skip mastered words with Next"),
+    ("keyboard.level_mastered", "{} · {} mastered"),
+    ("keyboard.chapter_mastered", "{} · {}/{}"),
+    ("keyboard.mastery_tally", "{} new, {} learning, {} mastered"),
+    ("placement.title", "Placement test"),
+    ("placement.intro", "Type {} words from their translations. Words get harder as you recall them and easier when you don't, and the test recommends a dictionary and chapter to start with."),
+    ("placement.start", "Start the test"),
+    ("placement.progress", "Word {} of {}"),
+    ("placement.type", "Type the English word"),
+    ("placement.check", "Check"),
+    ("placement.dont_know", "I don't know"),
+    ("placement.right", "Right, it was \"{}\"."),
+    ("placement.wrong", "It was \"{}\"."),
+    ("placement.result", "Start with {}, chapter {}."),
+    ("placement.practise", "Start practising"),
+    ("placement.retake", "Take it again"),
+    ("profiles.title", "Who is practising"),
+    ("profiles.default", "Default"),
+    ("profiles.manage", "Manage profiles"),
+    ("profiles.name", "Profile name"),
+    ("profiles.create", "Create"),
+    ("profiles.rename", "Rename"),
+    ("profiles.confirm_delete", "Delete the profile {} with all its settings, progress and history?"),
+    ("profiles.error_empty", "Enter a name first."),
+    ("profiles.error_long", "That name is too long."),
+    ("profiles.error_taken", "Another profile already has that name."),
+    ("profiles.error_missing", "That profile no longer exists."),
+    ("profiles.error_last", "The last profile can't be deleted."),
+    ("toast.level_up", "Level up! You reached level {}."),
+    ("toast.unlocked", "Achievement unlocked: {}"),
+    ("toast.freeze_earned", "A week in a row! You earned a streak freeze."),
+    ("toast.freeze_used", "A streak freeze covered {} missed day(s)."),
+    ("profile.level", "Level {}"),
+    ("profile.xp", "{} / {} XP"),
+    ("profile.streak", "Streak: {} days"),
+    ("profile.freezes", "Streak freezes: {} / {}"),
+    ("profile.freezes_hint", "A week of daily practice earns a freeze, which covers a missed day."),
+    ("profile.best_run", "Longest run without mistakes: {} words"),
+    ("profile.best_wpm", "Fastest chapter: {} WPM"),
+    ("profile.chapters", "Chapters finished: {}"),
+    ("profile.achievements", "Achievements"),
+    ("profile.unlocked_on", "Unlocked {}"),
+    ("profile.locked", "Locked"),
+    ("achievement.first_chapter", "First chapter"),
+    ("achievement.first_chapter_desc", "Type every word of a chapter in order."),
+    ("achievement.clean_hundred", "Flawless hundred"),
+    ("achievement.clean_hundred_desc", "Type 100 words in a row without a mistake."),
+    ("achievement.speed80", "Speed demon"),
+    ("achievement.speed80_desc", "Finish a chapter at 80 WPM or faster."),
+    ("achievement.cet4", "CET4 graduate"),
+    ("achievement.cet4_desc", "Finish every chapter of CET4."),
+    ("sync.synced", "Synced at {}"),
+    ("sync.offline", "Sync failed ({}), will retry."),
+    (
+        "keyboard.ime",
+        "An input method is active. Switch to English input to keep practising.",
+    ),
+];
+
+const ZH_CN: &[(&str, &str)] = &[
+    ("nav.practice", "练习"),
+    ("nav.dictionary", "词典"),
+    ("nav.stats", "统计"),
+    ("nav.settings", "设置"),
+    ("nav.auto_locale", "自动选择语言"),
+    ("footer.powered_by", "基于 "),
+    ("footer.using", " 构建，使用 "),
+    ("footer.crates_from", " 和来自 "),
+    ("footer.end", " 的库"),
+    ("keyboard.start", "开始"),
+    ("keyboard.pause", "暂停"),
+    ("keyboard.chapter", "第 {} 章"),
+    ("keyboard.prev", "上一个"),
+    ("keyboard.next", "下一个"),
+    ("keyboard.words", "单词"),
+    ("keyboard.sentences", "句子"),
+    ("keyboard.code", "代码"),
+    ("keyboard.sentence_result", "上一条：{} WPM，正确率 {}%"),
+    ("pronunc.ame", "美式发音"),
+    ("pronunc.bre", "英式发音"),
+    ("body.practice", "练习 {}"),
+    ("body.pomodoro", "番茄钟"),
+    ("body.focus", "专注 {}"),
+    ("body.break", "休息 {}"),
+    ("body.break_start", "该休息 {} 分钟了。"),
+    ("body.break_over", "休息结束，按开始继续。"),
+    ("body.skip_break", "跳过休息"),
+    ("body.unit_min", "分钟"),
+    ("body.unit_words", "词"),
+    ("goal.off", "不设每日目标"),
+    ("goal.minutes", "每天 {} 分钟"),
+    ("goal.words", "每天 {} 个词"),
+    ("dict.search", "搜索单词或释义"),
+    ("dict.any_length", "任意长度"),
+    ("dict.letters", "{} 个字母"),
+    ("dict.count", "{} / {} 个词"),
+    ("dict.chapters", "共 {} 章"),
+    ("dict.practise_here", "从这里开始练习"),
+    ("dict.any_state", "任意进度"),
+    ("dict.state_new", "新词"),
+    ("dict.state_learning", "学习中"),
+    ("dict.state_mastered", "已掌握"),
+    ("stats.sessions", "练习次数：{}"),
+    ("stats.time", "时长：{}"),
+    ("stats.words", "单词：{}"),
+    ("stats.streak", "连续：{} 天"),
+    ("stats.longest", "最长连续：{} 天"),
+    ("stats.day_words", "{}：{} 个词"),
+    ("stats.days", "按天"),
+    ("stats.weeks", "按周"),
+    ("stats.wpm_max", "WPM（最高 {}）"),
+    ("stats.accuracy_axis", "正确率（0-100%）"),
+    ("stats.date", "日期"),
+    ("stats.dictionary", "词典"),
+    ("stats.chapter", "章节"),
+    ("stats.duration", "时长"),
+    ("stats.col_words", "单词"),
+    ("stats.wpm", "WPM"),
+    ("stats.accuracy", "正确率"),
+    ("settings.practice", "练习"),
+    ("settings.pomodoro_work", "番茄钟专注（分钟）"),
+    ("settings.pomodoro_break", "番茄钟休息（分钟）"),
+    ("settings.backup", "备份"),
+    ("settings.export", "导出备份"),
+    ("settings.exported_on", "备份导出于 {}"),
+    ("settings.new_sessions", "{} 条练习记录不在此浏览器中"),
+    ("settings.new_packs", "{} 个代码片段包不在此浏览器中"),
+    ("settings.new_dicts", "{} 本导入的词典不在此浏览器中"),
+    ("settings.new_mastery", "{} 个单词的练习次数多于此浏览器"),
+    ("settings.dropped", "将略过 {} 个空的代码片段包或词典"),
+    ("settings.local_sessions", "{} 条练习记录仅在此浏览器中"),
+    ("settings.settings_changed", "设置有变化"),
+    ("settings.settings_unchanged", "设置无变化"),
+    ("settings.daily_changed", "今日进度有变化"),
+    ("settings.daily_unchanged", "今日进度无变化"),
+    ("settings.merge", "合并"),
+    ("settings.replace", "替换"),
+    ("settings.cancel", "取消"),
+    ("settings.merged", "备份已合并。"),
+    ("settings.restored", "备份已恢复。"),
+    ("backup.invalid", "不是有效的备份文件：{}"),
+    ("backup.format", "不是 KeyPress 备份文件"),
+    ("backup.version", "备份版本 {} 高于支持的版本 {}"),
+    ("settings.appearance", "外观"),
+    ("settings.theme", "主题"),
+    ("theme.auto", "跟随系统"),
+    ("theme.light", "浅色"),
+    ("theme.dark", "深色"),
+    ("theme.high_contrast", "高对比度"),
+    ("settings.code", "代码片段"),
+    ("settings.auto_close", "自动补全括号"),
+    ("settings.import_snippets", "导入代码片段包"),
+    ("settings.snippet_pack", "{}（{} 个片段）"),
+    ("settings.snippets_imported", "已导入 {} 个片段到 {}。"),
+    ("settings.snippets_invalid", "不是有效的代码片段包：{}"),
+    ("settings.remove", "删除"),
+    ("settings.phrases", "词组中的分隔符"),
+    ("settings.space", "空格"),
+    ("settings.hyphen", "连字符"),
+    ("settings.apostrophe", "撇号"),
+    ("policy.strict", "必须输入"),
+    ("policy.optional", "可输入可省略"),
+    ("policy.auto_skip", "自动跳过"),
+    ("settings.case", "大小写"),
+    ("settings.audio", "声音"),
+    ("settings.volume", "音量"),
+    ("settings.percent", "{}%"),
+    ("settings.clicks", "按键音"),
+    ("settings.feedback", "正确和错误提示音"),
+    ("settings.pronunciation", "朗读单词"),
+    ("settings.repeat", "每个单词朗读"),
+    ("settings.times", "{} 遍"),
+    ("settings.interval", "重复间隔"),
+    ("settings.seconds", "{} 秒"),
+    ("settings.rate", "朗读速度"),
+    ("settings.key_sounds", "按键音效"),
+    ("settings.preview", "试听"),
+    ("settings.import_pack", "上传自己的音效"),
+    ("settings.pack_name", "音效包名称"),
+    ("settings.pack_samples", "{}（{} 个音效）"),
+    ("settings.pack_imported", "已导入 {}，共 {} 个音效。"),
+    ("settings.pack_invalid", "无法导入音效包：{}"),
+    ("pack.classic", "经典"),
+    ("pack.blue", "机械键盘（青轴）"),
+    ("pack.brown", "机械键盘（茶轴）"),
+    ("pack.typewriter", "打字机"),
+    ("pack.soft", "柔和"),
+    ("pack.error_name", "请为音效包命名"),
+    ("pack.error_count", "请选择 1 到 {} 个音频文件"),
+    ("pack.error_file", "{} 不是小于 1 MB 的音频文件"),
+    ("variation.same", "所有按键相同"),
+    ("variation.per_key", "每个按键不同"),
+    ("variation.random", "随机"),
+    ("case.exact", "严格匹配"),
+    ("case.insensitive", "忽略大小写"),
+    ("case.auto_capitalise", "自动大写首字母"),
+    ("keyboard.caps_lock", "大写锁定已开启"),
+    ("keyboard.race", "挑战我的最佳成绩"),
+    ("keyboard.prefetch", "下载本章发音"),
+    ("keyboard.prefetching", "正在下载 {}/{}…"),
+    ("keyboard.prefetched", "已保存 {}/{} 个发音，可离线使用。"),
+    ("race.you", "你：{} / {}"),
+    ("race.ghost", "最佳成绩：{} / {}"),
+    ("race.no_ghost", "这一章还没有完成过。"),
+    ("race.split", "第 {} 个词：{} 秒"),
+    ("race.ahead", "领先 {} 秒"),
+    ("race.behind", "落后 {} 秒"),
+    ("race.finished", "本章用时 {} 秒。"),
+    ("race.first_run", "这是你目前的最佳成绩。"),
+    ("race.new_best", "新纪录，快了 {} 秒！"),
+    ("race.best", "你的最佳成绩是 {} 秒。"),
+    ("race.col_word", "单词"),
+    ("race.col_you", "你（秒）"),
+    ("race.col_ghost", "最佳（秒）"),
+    ("race.col_delta", "差值（秒）"),
+    ("multi.title", "和朋友比赛"),
+    ("multi.server", "比赛服务器，例如 ws://localhost:9001"),
+    ("multi.room", "房间"),
+    ("multi.name", "你的名字"),
+    ("multi.join", "加入"),
+    ("multi.leave", "离开"),
+    ("multi.start", "开始比赛"),
+    ("multi.waiting", "人到齐后，用当前章节开始比赛。"),
+    ("multi.racing", "正在比赛：{} 第 {} 章。"),
+    ("multi.you", "{}（你）"),
+    ("multi.place", "第 {} 名，用时 {} 秒"),
+    ("multi.lost", "与比赛服务器的连接已断开。"),
+    ("multi.no_name", "请输入比赛时使用的名字。"),
+    ("sync.title", "同步"),
+    ("sync.server", "同步服务器，例如 http://localhost:9002"),
+    ("sync.token", "令牌"),
+    ("sync.save", "保存并同步"),
+    ("sync.hint", "设置、进度和练习记录通过你自己部署的 keypress-sync-server 同步。离线时可以照常练习，服务器恢复后会自动补上。"),
+    ("sync.never", "尚未同步。"),
+    ("sync.last", "上次同步：{}。"),
+    ("sync.syncing", "正在同步…"),
+    ("update.available", "KeyPress 有新版本可用。"),
+    ("update.reload", "重新加载"),
+    ("update.later", "稍后"),
+    ("nav.profile", "我的"),
+    ("keyboard.placement", "不知道从哪里开始？做个水平测试"),
+    ("anki.title", "Anki"),
+    ("anki.import", "导入 Anki 文本导出作为词典"),
+    ("anki.hint", "在 Anki 中选择 文件 › 导出，格式为“纯文本格式的笔记”。第一个字段作为单词，第二个作为释义，第三个作为音标。"),
+    ("anki.imported", "已导入 {} 个单词，词典名为“{}”。"),
+    ("anki.invalid", "无法导入：{}"),
+    ("anki.error_empty", "没有找到单词"),
+    ("anki.dict", "{}（{} 个单词）"),
+    ("anki.whole", "整个词典"),
+    ("anki.mistakes", "打错过的单词"),
+    ("anki.export", "导出到 Anki"),
+    ("anki.exported", "已导出 {} 个单词。"),
+    ("anki.nothing", "没有可导出的单词。"),
+    ("keyboard.skip_mastered", "点“下一个”时跳过已掌握的单词"),
+    ("keyboard.level_mastered", "{} · 已掌握 {}"),
+    ("keyboard.chapter_mastered", "{} · {}/{}"),
+    ("keyboard.mastery_tally", "新词 {}，学习中 {}，已掌握 {}"),
+    ("placement.title", "水平测试"),
+    ("placement.intro", "根据释义拼写 {} 个单词。答对后单词变难，答错后变简单，最后推荐适合你的词典和章节。"),
+    ("placement.start", "开始测试"),
+    ("placement.progress", "第 {} / {} 个单词"),
+    ("placement.type", "输入英文单词"),
+    ("placement.check", "确认"),
+    ("placement.dont_know", "不认识"),
+    ("placement.right", "正确，是 “{}”。"),
+    ("placement.wrong", "正确答案是 “{}”。"),
+    ("placement.result", "建议从 {} 第 {} 章开始。"),
+    ("placement.practise", "开始练习"),
+    ("placement.retake", "重新测试"),
+    ("profiles.title", "当前练习者"),
+    ("profiles.default", "默认"),
+    ("profiles.manage", "管理用户"),
+    ("profiles.name", "用户名称"),
+    ("profiles.create", "新建"),
+    ("profiles.rename", "重命名"),
+    ("profiles.confirm_delete", "删除用户 {} 及其全部设置、进度和练习记录？"),
+    ("profiles.error_empty", "请先输入名称。"),
+    ("profiles.error_long", "名称太长。"),
+    ("profiles.error_taken", "已有同名用户。"),
+    ("profiles.error_missing", "该用户已不存在。"),
+    ("profiles.error_last", "不能删除最后一个用户。"),
+    ("toast.level_up", "升级了！你达到了 {} 级。"),
+    ("toast.unlocked", "解锁成就：{}"),
+    ("toast.freeze_earned", "连续练习一周！获得一次连续天数保护。"),
+    ("toast.freeze_used", "连续天数保护抵消了 {} 天未练习。"),
+    ("profile.level", "等级 {}"),
+    ("profile.xp", "{} / {} 经验"),
+    ("profile.streak", "连续练习：{} 天"),
+    ("profile.freezes", "连续天数保护：{} / {}"),
+    ("profile.freezes_hint", "每连续练习一周获得一次保护，可抵消一天未练习。"),
+    ("profile.best_run", "最长无错连续：{} 个单词"),
+    ("profile.best_wpm", "最快章节：{} WPM"),
+    ("profile.chapters", "已完成章节：{}"),
+    ("profile.achievements", "成就"),
+    ("profile.unlocked_on", "解锁于 {}"),
+    ("profile.locked", "未解锁"),
+    ("achievement.first_chapter", "第一章"),
+    ("achievement.first_chapter_desc", "按顺序打完一章的所有单词。"),
+    ("achievement.clean_hundred", "百词无误"),
+    ("achievement.clean_hundred_desc", "连续打对 100 个单词，不出一次错。"),
+    ("achievement.speed80", "速度达人"),
+    ("achievement.speed80_desc", "以 80 WPM 或更快的速度完成一章。"),
+    ("achievement.cet4", "四级毕业"),
+    ("achievement.cet4_desc", "完成 CET4 的所有章节。"),
+    ("sync.synced", "已于 {} 同步"),
+    ("sync.offline", "同步失败（{}），稍后重试。"),
+    (
+        "keyboard.ime",
+        "输入法处于中文状态，请切换到英文输入后继续练习。",
+    ),
+];
+
+fn catalogue(locale: Locale) -> &'static [(&'static str, &'static str)] {
+    match locale {
+        Locale::En => EN,
+        Locale::ZhCn => ZH_CN,
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref CATALOGUES: HashMap<Locale, HashMap<&'static str, &'static str>> =
+        Locale::iterator()
+            .map(|l| (*l, catalogue(*l).iter().copied().collect()))
+            .collect();
+}
+
+thread_local! {
+    static LOCALE: Cell<Locale> = const { Cell::new(Locale::En) };
+}
+
+pub fn locale() -> Locale {
+    LOCALE.with(|l| l.get())
+}
+
+pub fn set_locale(locale: Locale) {
+    LOCALE.with(|l| l.set(locale));
+}
+
+/// Look up `key` in the current locale, falling back to English.
+pub fn t(key: &'static str) -> &'static str {
+    CATALOGUES[&locale()]
+        .get(key)
+        .or_else(|| CATALOGUES[&Locale::En].get(key))
+        .copied()
+        .unwrap_or(key)
+}
+
+/// Like `t`, replacing each `{}` with the next argument.
+pub fn tf(key: &'static str, args: &[&dyn Display]) -> String {
+    let mut parts = t(key).split("{}");
+    let mut out = String::from(parts.next().unwrap_or_default());
+    for (i, part) in parts.enumerate() {
+        if let Some(arg) = args.get(i) {
+            out.push_str(&arg.to_string());
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_key_in_every_catalogue() {
+        for locale in Locale::iterator() {
+            for (key, text) in EN.iter() {
+                let translated = CATALOGUES[locale].get(key);
+                assert!(translated.is_some(), "{} missing in {}", key, locale.code());
+                assert_eq!(
+                    translated.unwrap().matches("{}").count(),
+                    text.matches("{}").count(),
+                    "placeholders of {} differ in {}",
+                    key,
+                    locale.code()
+                );
+            }
+            assert_eq!(
+                catalogue(*locale).len(),
+                EN.len(),
+                "{} has keys not in en",
+                locale.code()
+            );
+            assert_eq!(CATALOGUES[locale].len(), EN.len(), "duplicate keys");
+        }
+    }
+
+    #[test]
+    fn formats_placeholders() {
+        set_locale(Locale::En);
+        assert_eq!(tf("dict.count", &[&3, &10]), "3 of 10 words");
+        set_locale(Locale::ZhCn);
+        assert_eq!(tf("keyboard.chapter", &[&12]), "第 12 章");
+        set_locale(Locale::En);
+        assert_eq!(t("no.such.key"), "no.such.key");
+    }
+}
@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::common::storage::{self, KEY_MASTERY};
+
+/// Typings in a row without a mistake that make a word mastered.
+pub const MASTERED_AFTER: u32 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WordState {
+    New,
+    Learning,
+    Mastered,
+}
+
+/// How a word has gone so far.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Record {
+    pub typed: u32,
+    pub clean: u32, // typings in a row without a mistake
+}
+
+impl Record {
+    pub fn state(&self) -> WordState {
+        match self.typed {
+            0 => WordState::New,
+            _ if self.clean >= MASTERED_AFTER => WordState::Mastered,
+            _ => WordState::Learning,
+        }
+    }
+}
+
+/// Words of some dictionary in each state.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Tally {
+    pub new: usize,
+    pub learning: usize,
+    pub mastered: usize,
+}
+
+impl Tally {
+    pub fn total(&self) -> usize {
+        self.new + self.learning + self.mastered
+    }
+
+    /// Share of the words in `count`, in percent.
+    pub fn percent(&self, count: usize) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => count as f64 * 100.0 / total as f64,
+        }
+    }
+}
+
+/// Typing results of every practised word, keyed by dictionary and word.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Mastery {
+    pub dicts: BTreeMap<String, BTreeMap<String, Record>>,
+}
+
+impl Mastery {
+    pub fn load() -> Self {
+        storage::load(KEY_MASTERY)
+    }
+
+    pub fn save(&self) {
+        storage::save(KEY_MASTERY, self);
+    }
+
+    /// Count `word` as typed, returning its new state.
+    pub fn record(&mut self, dict: &str, word: &str, mistakes: u32) -> WordState {
+        let record = self
+            .dicts
+            .entry(dict.to_string())
+            .or_default()
+            .entry(word.to_string())
+            .or_default();
+        record.typed += 1;
+        record.clean = if mistakes == 0 { record.clean + 1 } else { 0 };
+        record.state()
+    }
+
+    pub fn state(&self, dict: &str, word: &str) -> WordState {
+        self.dicts
+            .get(dict)
+            .and_then(|words| words.get(word))
+            .map_or(WordState::New, Record::state)
+    }
+
+    pub fn tally<'a>(&self, dict: &str, words: impl Iterator<Item = &'a str>) -> Tally {
+        let mut tally = Tally::default();
+        for word in words {
+            match self.state(dict, word) {
+                WordState::New => tally.new += 1,
+                WordState::Learning => tally.learning += 1,
+                WordState::Mastered => tally.mastered += 1,
+            }
+        }
+        tally
+    }
+
+    /// Whether `word` has been typed with a mistake and not mastered since,
+    /// which puts it in the list of words to review.
+    pub fn mistaken(&self, dict: &str, word: &str) -> bool {
+        self.dicts
+            .get(dict)
+            .and_then(|words| words.get(word))
+            .is_some_and(|r| r.typed > r.clean && r.state() != WordState::Mastered)
+    }
+
+    pub fn mastered(&self, dict: &str) -> usize {
+        self.dicts.get(dict).map_or(0, |words| {
+            words
+                .values()
+                .filter(|r| r.state() == WordState::Mastered)
+                .count()
+        })
+    }
+
+    /// Word records of `other` that merging would take over.
+    pub fn updates(&self, other: &Mastery) -> usize {
+        other
+            .dicts
+            .iter()
+            .flat_map(|(dict, words)| words.iter().map(move |(word, r)| (dict, word, r)))
+            .filter(|(dict, word, record)| {
+                let ours = self.dicts.get(*dict).and_then(|words| words.get(*word));
+                ours.is_none_or(|ours| record.typed > ours.typed)
+            })
+            .count()
+    }
+
+    /// Combine with results from a backup or another device, keeping the
+    /// record of each word that has been typed more often.
+    pub fn merge(&mut self, other: &Mastery) {
+        for (dict, words) in other.dicts.iter() {
+            let ours = self.dicts.entry(dict.clone()).or_default();
+            for (word, record) in words.iter() {
+                let entry = ours.entry(word.clone()).or_default();
+                if record.typed > entry.typed {
+                    *entry = *record;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clean_typings_master_a_word_and_mistakes_reset_it() {
+        let mut mastery = Mastery::default();
+        assert_eq!(mastery.state("CET4", "apple"), WordState::New);
+        assert_eq!(mastery.record("CET4", "apple", 0), WordState::Learning);
+        assert_eq!(mastery.record("CET4", "apple", 0), WordState::Learning);
+        assert_eq!(mastery.record("CET4", "apple", 0), WordState::Mastered);
+        assert_eq!(mastery.record("CET4", "apple", 2), WordState::Learning);
+        assert_eq!(mastery.state("CET6", "apple"), WordState::New);
+        assert!(mastery.mistaken("CET4", "apple"));
+
+        for _ in 0..MASTERED_AFTER {
+            mastery.record("CET4", "pear", 0);
+        }
+        mastery.record("CET4", "plum", 1);
+        let tally = mastery.tally("CET4", ["apple", "pear", "plum", "fig"].into_iter());
+        assert_eq!(
+            tally,
+            Tally {
+                new: 1,
+                learning: 2,
+                mastered: 1
+            }
+        );
+        assert_eq!(tally.percent(tally.mastered), 25.0);
+        assert_eq!(mastery.mastered("CET4"), 1);
+        assert!(!mastery.mistaken("CET4", "pear"));
+        assert!(!mastery.mistaken("CET4", "fig"));
+    }
+
+    #[test]
+    fn merge_keeps_the_most_typed_record() {
+        let mut ours = Mastery::default();
+        ours.record("CET4", "apple", 0);
+        let mut theirs = Mastery::default();
+        for _ in 0..3 {
+            theirs.record("CET4", "apple", 0);
+        }
+        theirs.record("TOEFL", "abate", 1);
+        assert_eq!(ours.updates(&theirs), 2);
+        assert_eq!(theirs.updates(&ours), 0);
+
+        ours.merge(&theirs);
+        assert_eq!(ours.state("CET4", "apple"), WordState::Mastered);
+        assert_eq!(ours.state("TOEFL", "abate"), WordState::Learning);
+    }
+}
@@ -1,2 +1,25 @@
+pub mod anki;
+pub mod audio_cache;
+pub mod backup;
+pub mod clock;
+pub mod dict;
 pub mod event_bus;
+pub mod file;
+pub mod ghost;
+pub mod history;
+pub mod i18n;
+pub mod mastery;
 pub mod msg;
+pub mod placement;
+pub mod profiles;
+pub mod pwa;
+pub mod rewards;
+pub mod route;
+pub mod settings;
+pub mod snippets;
+pub mod sound_pack;
+pub mod storage;
+pub mod sync;
+pub mod theme;
+pub mod typing;
+pub mod user_dicts;
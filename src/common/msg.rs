@@ -1,12 +1,135 @@
+use yew::format::Text;
+use yew::services::reader::{File, FileData};
+use yew::services::websocket::WebSocketStatus;
+
+use crate::common::event_bus::Request;
+use crate::common::i18n::Locale;
+use crate::common::rewards::Reward;
+use crate::common::route::Route;
+use crate::common::settings::AudioSettings;
+use crate::common::sound_pack::CustomPack;
+use crate::common::theme::Theme;
+use crate::common::typing::{CasePolicy, Policy};
+
+pub enum AppMsg {
+    Navigate(Route),
+    SelectLocale(Option<Locale>),
+}
+
 pub enum Msg {
     UpdateTime,
+    SessionStarted,
+    SessionPaused,
+    WordTyped,
+    TogglePomodoro,
+    SelectGoal(String),
+    SkipBreak,
+    Reload,
 }
 
 pub enum Key {
     SetText(String),
     SelectProunc(u8),
+    SelectMode(String),
     SelectLevel(String),
     SelectChapter(usize),
     WordNextPre(String),
     Submit,
+    Pause,
+    CapsLock(bool),
+    Composing(bool),
+    ToggleRace,
+    ToggleSkipMastered,
+    RaceTick,
+    StartRace(String, usize),
+    Prefetch,
+    Prefetched(bool), // one pronunciation, whether it was saved
+    Reload,           // state was replaced by a sync
+}
+
+pub enum StatsMsg {
+    TrendByWeek(bool),
+}
+
+pub enum SettingsMsg {
+    SetPomodoroWork(u32),
+    SetPomodoroBreak(u32),
+    SelectTheme(Option<Theme>),
+    Export,
+    ImportFile(Option<File>),
+    Imported(FileData),
+    ApplyImport(bool),
+    CancelImport,
+    ToggleAutoClose,
+    SetSeparator(char, Policy),
+    SelectCase(CasePolicy),
+    ImportSnippets(Option<File>),
+    SnippetsRead(FileData),
+    RemoveSnippets(String),
+    ImportAnki(Option<File>),
+    AnkiRead(FileData),
+    RemoveUserDict(String),
+    SetSyncServer(String),
+    SetSyncToken(String),
+    SaveSync,
+    SetAudio(Box<dyn FnOnce(&mut AudioSettings)>),
+    PreviewClick,
+    SetPackName(String),
+    ImportPack(Vec<File>),
+    PackImported(Result<CustomPack, String>),
+    RemovePack(String),
+}
+
+pub enum MultiMsg {
+    SetServer(String),
+    SetRoom(String),
+    SetName(String),
+    Join,
+    Leave,
+    Start,
+    Status(WebSocketStatus),
+    Received(Text),
+    Bus(Request),
+}
+
+pub enum SyncMsg {
+    Sync,
+    Retry,
+    Done(Result<String, String>), // response body
+}
+
+pub enum UpdateMsg {
+    Available,
+    Reload,
+    Dismiss,
+}
+
+pub enum ProfilesMsg {
+    Switch(String),
+    ToggleManage,
+    SetName(String),
+    Create,
+    Rename(String),
+    Delete(String),
+}
+
+pub enum PlacementMsg {
+    Start,
+    SetAnswer(String),
+    Submit,
+    Skip, // the word isn't known
+}
+
+pub enum ToastMsg {
+    Show(Reward),
+    Dismiss(u32), // toast id
+}
+
+pub enum DictMsg {
+    Search(String),
+    FilterLength(String),
+    FilterState(String),
+    Scroll,
+    SetExport(String),
+    Export,
 }
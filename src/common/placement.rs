@@ -0,0 +1,167 @@
+use std::collections::HashSet;
+
+use crate::common::dict::{nr_chapter, DICT_INDEX};
+
+/// Dictionaries graded by difficulty, easiest first.
+const GRADED: [&str; 3] = ["CET4", "CET6", "TOEFL"];
+/// Parts each dictionary is split into, by chapter.
+const BANDS: usize = 3;
+pub const QUESTIONS: usize = 12;
+
+/// One step of the difficulty ladder: a third of a dictionary.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rung {
+    pub dict: &'static str,
+    pub band: usize,
+}
+
+impl Rung {
+    /// The first chapter of the band in a dictionary of `nr_word` words.
+    pub fn chapter(&self, nr_word: usize) -> usize {
+        self.band * nr_chapter(nr_word) / BANDS + 1
+    }
+
+    /// Word indexes of the band in a dictionary of `nr_word` words.
+    fn range(&self, nr_word: usize) -> (usize, usize) {
+        (
+            self.band * nr_word / BANDS,
+            (self.band + 1) * nr_word / BANDS,
+        )
+    }
+}
+
+pub fn ladder() -> Vec<Rung> {
+    DICT_INDEX
+        .iter()
+        .filter(|dict| GRADED.contains(dict))
+        .flat_map(|dict| (0..BANDS).map(move |band| Rung { dict, band }))
+        .collect()
+}
+
+/// Whether `answer` recalls `word`, ignoring case and surrounding spaces.
+pub fn recalls(answer: &str, word: &str) -> bool {
+    answer.trim().eq_ignore_ascii_case(word.trim())
+}
+
+/// A staircase over the ladder: a recalled word moves one rung up, a missed
+/// one a rung down, and where the answers settle is the recommendation.
+#[derive(Clone, Debug)]
+pub struct Placement {
+    ladder: Vec<Rung>,
+    rung: usize,
+    answers: Vec<(usize, bool)>, // rung and whether it was recalled
+    asked: HashSet<(&'static str, usize)>,
+}
+
+impl Default for Placement {
+    fn default() -> Self {
+        let ladder = ladder();
+        Placement {
+            rung: ladder.len() / 2,
+            ladder,
+            answers: Vec::new(),
+            asked: HashSet::new(),
+        }
+    }
+}
+
+impl Placement {
+    pub fn rung(&self) -> Rung {
+        self.ladder[self.rung]
+    }
+
+    pub fn answered(&self) -> usize {
+        self.answers.len()
+    }
+
+    pub fn done(&self) -> bool {
+        self.answers.len() >= QUESTIONS
+    }
+
+    /// Index of a word not asked yet from the current rung of a dictionary
+    /// of `nr_word` words, `random` being in 0..1.
+    pub fn pick(&mut self, nr_word: usize, random: f64) -> usize {
+        let rung = self.rung();
+        let (first, last) = rung.range(nr_word);
+        let size = (last - first).max(1);
+        let start = first + ((random * size as f64) as usize).min(size - 1);
+        let index = (0..size)
+            .map(|i| first + (start - first + i) % size)
+            .find(|i| !self.asked.contains(&(rung.dict, *i)))
+            .unwrap_or(start);
+        self.asked.insert((rung.dict, index));
+        index
+    }
+
+    pub fn answer(&mut self, recalled: bool) {
+        self.answers.push((self.rung, recalled));
+        if recalled {
+            self.rung = (self.rung + 1).min(self.ladder.len() - 1);
+        } else {
+            self.rung = self.rung.saturating_sub(1);
+        }
+    }
+
+    /// The rung the answers settled on: the mean of the second half, where
+    /// the first has found the level.
+    pub fn recommend(&self) -> Rung {
+        let settled = &self.answers[self.answers.len() / 2..];
+        if settled.is_empty() {
+            return self.rung();
+        }
+        let sum: usize = settled.iter().map(|(rung, _)| rung).sum();
+        let mean = sum as f64 / settled.len() as f64;
+        self.ladder[(mean.round() as usize).min(self.ladder.len() - 1)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rung(dict: &'static str, band: usize) -> Rung {
+        Rung { dict, band }
+    }
+
+    fn run(mut knows: impl FnMut(usize) -> bool) -> Rung {
+        let mut placement = Placement::default();
+        while !placement.done() {
+            let step = placement.rung;
+            placement.pick(1000, 0.5);
+            placement.answer(knows(step));
+        }
+        placement.recommend()
+    }
+
+    #[test]
+    fn ladder_grades_the_dictionaries() {
+        let ladder = ladder();
+        assert_eq!(ladder.len(), 9);
+        assert_eq!(ladder[0], rung("CET4", 0));
+        assert_eq!(ladder[4], rung("CET6", 1));
+        assert_eq!(ladder[8], rung("TOEFL", 2));
+        assert_eq!(rung("CET6", 0).chapter(2345), 1);
+        assert_eq!(rung("CET6", 1).chapter(2345), 40);
+        assert_eq!(rung("CET6", 2).chapter(2345), 79);
+    }
+
+    #[test]
+    fn settles_where_recall_starts_failing() {
+        assert_eq!(run(|_| true), rung("TOEFL", 2));
+        assert_eq!(run(|_| false), rung("CET4", 0));
+        // Knows CET4 and the start of CET6 only
+        assert_eq!(run(|step| step < 4), rung("CET6", 1));
+        assert_eq!(run(|step| step < 2), rung("CET4", 2));
+    }
+
+    #[test]
+    fn never_asks_a_word_twice() {
+        let mut placement = Placement::default();
+        let (first, last) = placement.rung().range(30);
+        let picked: HashSet<usize> = (0..10).map(|_| placement.pick(30, 0.99)).collect();
+        assert_eq!(picked.len(), 10);
+        assert!(picked.iter().all(|i| (first..last).contains(i)));
+        assert!(recalls(" Abrupt ", "abrupt"));
+        assert!(!recalls("abrup", "abrupt"));
+    }
+}
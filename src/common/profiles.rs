@@ -0,0 +1,205 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter, Result};
+
+use crate::common::i18n::t;
+use crate::common::storage::{self, KEY_PROFILES};
+
+/// The profile whose data lives under the plain keys, as it did before
+/// there were profiles.
+pub const DEFAULT_ID: &str = "default";
+const MAX_NAME: usize = 32;
+
+#[derive(Debug, PartialEq)]
+pub enum ProfileError {
+    EmptyName,
+    LongName,
+    Taken,
+    Missing,
+    LastProfile,
+}
+
+impl Display for ProfileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let key = match self {
+            ProfileError::EmptyName => "profiles.error_empty",
+            ProfileError::LongName => "profiles.error_long",
+            ProfileError::Taken => "profiles.error_taken",
+            ProfileError::Missing => "profiles.error_missing",
+            ProfileError::LastProfile => "profiles.error_last",
+        };
+        write!(f, "{}", t(key))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LocalProfile {
+    pub id: String, // stable, used in storage keys
+    pub name: String,
+}
+
+/// The people sharing this browser, and who is practising now.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Profiles {
+    pub active: String,
+    pub list: Vec<LocalProfile>,
+    next_id: u32,
+}
+
+impl Default for Profiles {
+    fn default() -> Self {
+        Profiles {
+            active: String::from(DEFAULT_ID),
+            list: vec![LocalProfile {
+                id: String::from(DEFAULT_ID),
+                name: String::new(), // shown as the translated "Default"
+            }],
+            next_id: 1,
+        }
+    }
+}
+
+/// `key` as stored for the profile `id`.
+pub fn scoped_key(key: &str, id: &str) -> String {
+    if id == DEFAULT_ID {
+        key.to_string()
+    } else {
+        format!("{}@{}", key, id)
+    }
+}
+
+impl LocalProfile {
+    pub fn display_name(&self) -> &str {
+        if self.name.is_empty() {
+            t("profiles.default")
+        } else {
+            &self.name
+        }
+    }
+}
+
+impl Profiles {
+    pub fn load() -> Self {
+        storage::load(KEY_PROFILES)
+    }
+
+    pub fn save(&self) {
+        storage::save(KEY_PROFILES, self);
+    }
+
+    pub fn get(&self, id: &str) -> Option<&LocalProfile> {
+        self.list.iter().find(|p| p.id == id)
+    }
+
+    fn check_name(
+        &self,
+        name: &str,
+        except: Option<&str>,
+    ) -> std::result::Result<String, ProfileError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ProfileError::EmptyName);
+        }
+        if name.chars().count() > MAX_NAME {
+            return Err(ProfileError::LongName);
+        }
+        let taken = self
+            .list
+            .iter()
+            .any(|p| Some(p.id.as_str()) != except && p.display_name().eq_ignore_ascii_case(name));
+        if taken {
+            return Err(ProfileError::Taken);
+        }
+        Ok(name.to_string())
+    }
+
+    /// Add a profile named `name`, returning its id.
+    pub fn create(&mut self, name: &str) -> std::result::Result<String, ProfileError> {
+        let name = self.check_name(name, None)?;
+        let id = format!("p{}", self.next_id);
+        self.next_id += 1;
+        self.list.push(LocalProfile {
+            id: id.clone(),
+            name,
+        });
+        Ok(id)
+    }
+
+    pub fn rename(&mut self, id: &str, name: &str) -> std::result::Result<(), ProfileError> {
+        let name = self.check_name(name, Some(id))?;
+        let profile = self
+            .list
+            .iter_mut()
+            .find(|p| p.id == id)
+            .ok_or(ProfileError::Missing)?;
+        profile.name = name;
+        Ok(())
+    }
+
+    pub fn switch(&mut self, id: &str) -> std::result::Result<(), ProfileError> {
+        self.get(id).ok_or(ProfileError::Missing)?;
+        self.active = id.to_string();
+        Ok(())
+    }
+
+    /// Drop the profile `id`; deleting the active one switches to the
+    /// first remaining profile.
+    pub fn remove(&mut self, id: &str) -> std::result::Result<(), ProfileError> {
+        self.get(id).ok_or(ProfileError::Missing)?;
+        if self.list.len() == 1 {
+            return Err(ProfileError::LastProfile);
+        }
+        self.list.retain(|p| p.id != id);
+        if self.active == id {
+            self.active = self.list[0].id.clone();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_profile_keeps_plain_keys() {
+        assert_eq!(
+            scoped_key("keypress.history", DEFAULT_ID),
+            "keypress.history"
+        );
+        assert_eq!(scoped_key("keypress.history", "p3"), "keypress.history@p3");
+    }
+
+    #[test]
+    fn create_rename_switch_and_remove() {
+        let mut profiles = Profiles::default();
+        let alice = profiles.create("  Alice ").unwrap();
+        let bob = profiles.create("Bob").unwrap();
+        assert_eq!(alice, "p1");
+        assert_eq!(profiles.get(&alice).unwrap().name, "Alice");
+
+        assert_eq!(profiles.create(""), Err(ProfileError::EmptyName));
+        assert_eq!(profiles.create("alice"), Err(ProfileError::Taken));
+        assert_eq!(
+            profiles.create(&"x".repeat(40)),
+            Err(ProfileError::LongName)
+        );
+        assert_eq!(profiles.rename(&bob, "ALICE"), Err(ProfileError::Taken));
+        // Keeping its own name, in another case, is fine
+        assert_eq!(profiles.rename(&alice, "ALICE"), Ok(()));
+
+        profiles.switch(&bob).unwrap();
+        assert_eq!(profiles.switch("p9"), Err(ProfileError::Missing));
+        assert_eq!(profiles.active, bob);
+
+        // Ids are never reused, so old data can't leak into a new profile
+        profiles.remove(&bob).unwrap();
+        assert_eq!(profiles.active, DEFAULT_ID);
+        assert_eq!(profiles.create("Carol").unwrap(), "p3");
+
+        profiles.remove(DEFAULT_ID).unwrap();
+        profiles.remove("p3").unwrap();
+        assert_eq!(profiles.active, alice);
+        assert_eq!(profiles.remove(&alice), Err(ProfileError::LastProfile));
+    }
+}
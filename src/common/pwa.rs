@@ -0,0 +1,113 @@
+use std::cell::{Cell, RefCell};
+use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
+use web_sys::{EventTarget, ServiceWorkerContainer, ServiceWorkerRegistration, ServiceWorkerState};
+use yew::Callback;
+
+// Written next to index.html by scripts/service-worker.sh
+const SW_PATH: &str = "sw.js";
+const UPDATE_CHECK_MS: i32 = 60 * 60 * 1000;
+
+thread_local! {
+    static STARTED: Cell<bool> = const { Cell::new(false) };
+    static UPDATING: Cell<bool> = const { Cell::new(false) };
+    static REGISTRATION: RefCell<Option<ServiceWorkerRegistration>> = const { RefCell::new(None) };
+    static ON_UPDATE: RefCell<Option<Callback<()>>> = const { RefCell::new(None) };
+}
+
+fn container() -> Option<ServiceWorkerContainer> {
+    let navigator = web_sys::window()?.navigator();
+    // Missing outside secure contexts, e.g. plain http on a LAN address
+    js_sys::Reflect::has(&navigator, &JsValue::from_str("serviceWorker"))
+        .unwrap_or(false)
+        .then(|| navigator.service_worker())
+}
+
+fn listen(target: &EventTarget, event: &str, handler: impl FnMut(web_sys::Event) + 'static) {
+    let cb = Closure::wrap(Box::new(handler) as Box<dyn FnMut(_)>);
+    target
+        .add_event_listener_with_callback(event, cb.as_ref().unchecked_ref())
+        .unwrap();
+    cb.forget();
+}
+
+/// True if a new build is installed and this page still runs the old one;
+/// the very first install has no controller and is not an update.
+fn update_waiting() -> bool {
+    let controlled = container().and_then(|c| c.controller()).is_some();
+    let waiting = REGISTRATION.with(|r| r.borrow().as_ref().and_then(|reg| reg.waiting()));
+    controlled && waiting.is_some()
+}
+
+fn notify() {
+    if update_waiting() {
+        if let Some(on_update) = ON_UPDATE.with(|c| c.borrow().clone()) {
+            on_update.emit(());
+        }
+    }
+}
+
+/// Register the service worker once; `on_update` is called whenever a new
+/// build is waiting for `activate_update`.
+pub fn register(on_update: Callback<()>) {
+    ON_UPDATE.with(|c| *c.borrow_mut() = Some(on_update));
+    let container = match container() {
+        Some(container) => container,
+        None => return,
+    };
+    if STARTED.with(|s| s.replace(true)) {
+        notify();
+        return;
+    }
+
+    listen(&container, "controllerchange", |_| {
+        if UPDATING.with(Cell::get) {
+            web_sys::window().unwrap().location().reload().unwrap();
+        }
+    });
+    let on_registered = Closure::once(move |reg: JsValue| {
+        let reg: ServiceWorkerRegistration = reg.unchecked_into();
+        REGISTRATION.with(|r| *r.borrow_mut() = Some(reg.clone()));
+        watch(&reg);
+        notify();
+    });
+    let _ = container.register(SW_PATH).then(&on_registered);
+    on_registered.forget();
+}
+
+fn watch(reg: &ServiceWorkerRegistration) {
+    let registration = reg.clone();
+    listen(reg, "updatefound", move |_| {
+        if let Some(worker) = registration.installing() {
+            let installing = worker.clone();
+            listen(&worker, "statechange", move |_| {
+                if installing.state() == ServiceWorkerState::Installed {
+                    notify();
+                }
+            });
+        }
+    });
+
+    // A tab left open on the practice page never navigates, so look for a
+    // new deploy now and then
+    let registration = reg.clone();
+    let check = Closure::wrap(Box::new(move || {
+        let _ = registration.update();
+    }) as Box<dyn FnMut()>);
+    web_sys::window()
+        .unwrap()
+        .set_interval_with_callback_and_timeout_and_arguments_0(
+            check.as_ref().unchecked_ref(),
+            UPDATE_CHECK_MS,
+        )
+        .unwrap();
+    check.forget();
+}
+
+/// Switch to the waiting build; the page reloads once it has taken over.
+pub fn activate_update() {
+    let waiting = REGISTRATION.with(|r| r.borrow().as_ref().and_then(|reg| reg.waiting()));
+    if let Some(worker) = waiting {
+        UPDATING.with(|u| u.set(true));
+        let _ = worker.post_message(&JsValue::from_str("skip-waiting"));
+    }
+}
@@ -0,0 +1,476 @@
+use serde::{Deserialize, Serialize};
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::common::dict::{nr_chapter, CHAPTER_SIZE};
+use crate::common::history::wpm;
+use crate::common::storage::{self, KEY_REWARDS};
+
+/// Freezes held at most; one is earned for every week of streak.
+pub const MAX_FREEZES: u32 = 2;
+const FREEZE_EVERY: u32 = 7;
+const CLEAN_RUN: u32 = 100;
+const FAST_WPM: f64 = 80.0;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Achievement {
+    FirstChapter,
+    CleanHundred, // 100 words in a row without a mistake
+    Speed80,      // a whole chapter at 80 WPM or more
+    FinishedCet4,
+}
+
+impl Achievement {
+    pub fn all() -> [Achievement; 4] {
+        [
+            Achievement::FirstChapter,
+            Achievement::CleanHundred,
+            Achievement::Speed80,
+            Achievement::FinishedCet4,
+        ]
+    }
+
+    /// i18n keys of the title and description.
+    pub fn keys(&self) -> (&'static str, &'static str) {
+        match self {
+            Achievement::FirstChapter => (
+                "achievement.first_chapter",
+                "achievement.first_chapter_desc",
+            ),
+            Achievement::CleanHundred => (
+                "achievement.clean_hundred",
+                "achievement.clean_hundred_desc",
+            ),
+            Achievement::Speed80 => ("achievement.speed80", "achievement.speed80_desc"),
+            Achievement::FinishedCet4 => ("achievement.cet4", "achievement.cet4_desc"),
+        }
+    }
+}
+
+/// Something worth a toast.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Reward {
+    LevelUp(u32),
+    Unlocked(Achievement),
+    FreezeEarned,
+    FreezeUsed(u32), // days covered
+}
+
+/// How much harder a dictionary is than everyday words.
+fn difficulty(dict: &str) -> f64 {
+    match dict {
+        "CET6" => 1.25,
+        "TOEFL" => 1.5,
+        _ => 1.0,
+    }
+}
+
+/// XP for typing `word`: a point per letter, weighted by the dictionary
+/// and halved if it took mistakes.
+pub fn xp_for(word: &str, dict: &str, mistakes: u32) -> u64 {
+    let letters = word.chars().filter(|c| c.is_alphanumeric()).count() as f64;
+    let xp = (letters * difficulty(dict)).round().max(1.0) as u64;
+    if mistakes > 0 {
+        xp.div_ceil(2)
+    } else {
+        xp
+    }
+}
+
+/// Total XP needed to reach `level`; each level takes 100 XP more.
+pub fn xp_for_level(level: u32) -> u64 {
+    let level = level.max(1) as u64;
+    50 * level * (level - 1)
+}
+
+pub fn level_for(xp: u64) -> u32 {
+    let mut level = 1;
+    while xp_for_level(level + 1) <= xp {
+        level += 1;
+    }
+    level
+}
+
+/// One word finished on the practice page.
+pub struct Typed<'a> {
+    pub dict: &'a str,
+    pub nr_word: usize, // in the dictionary
+    pub index: usize,   // of the word
+    pub word: &'a str,
+    pub mistakes: u32,
+    pub started: f64, // ms, first key of the word
+    pub now: f64,
+}
+
+/// A chapter being typed from its first word without skipping.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+struct ChapterRun {
+    dict: String,
+    next: usize, // index of the word expected next
+    started: f64,
+    chars: u32,
+}
+
+/// XP, achievements and streak freezes.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Rewards {
+    pub xp: u64,
+    pub unlocked: BTreeMap<Achievement, String>, // date unlocked
+    pub chapters: BTreeSet<(String, usize)>,     // finished chapters
+    pub clean_run: u32,
+    pub best_clean_run: u32,
+    pub best_chapter_wpm: f64,
+    pub freezes: u32,
+    pub frozen: BTreeSet<i64>, // missed days covered by a freeze
+    pub freeze_earned: Option<i64>,
+    run: Option<ChapterRun>,
+}
+
+impl Rewards {
+    pub fn load() -> Self {
+        storage::load(KEY_REWARDS)
+    }
+
+    /// Reload, apply `f` and store, returning what `f` returned.
+    pub fn modify<T, F: FnOnce(&mut Rewards) -> T>(f: F) -> T {
+        let mut rewards = Rewards::load();
+        let result = f(&mut rewards);
+        storage::save(KEY_REWARDS, &rewards);
+        result
+    }
+
+    pub fn level(&self) -> u32 {
+        level_for(self.xp)
+    }
+
+    fn unlock(&mut self, achievement: Achievement, today: &str, rewards: &mut Vec<Reward>) {
+        if let Entry::Vacant(entry) = self.unlocked.entry(achievement) {
+            entry.insert(today.to_string());
+            rewards.push(Reward::Unlocked(achievement));
+        }
+    }
+
+    /// Count a finished word on `today`.
+    pub fn word(&mut self, typed: &Typed, today: &str) -> Vec<Reward> {
+        let mut rewards = Vec::new();
+        let level = self.level();
+        self.xp += xp_for(typed.word, typed.dict, typed.mistakes);
+        if self.level() > level {
+            rewards.push(Reward::LevelUp(self.level()));
+        }
+
+        if typed.mistakes == 0 {
+            self.clean_run += 1;
+            self.best_clean_run = self.best_clean_run.max(self.clean_run);
+        } else {
+            self.clean_run = 0;
+        }
+        if self.clean_run >= CLEAN_RUN {
+            self.unlock(Achievement::CleanHundred, today, &mut rewards);
+        }
+
+        self.chapter_word(typed, today, &mut rewards);
+        rewards
+    }
+
+    fn chapter_word(&mut self, typed: &Typed, today: &str, rewards: &mut Vec<Reward>) {
+        let first = typed.index / CHAPTER_SIZE * CHAPTER_SIZE;
+        let last = (first + CHAPTER_SIZE).min(typed.nr_word) - 1;
+        let mut run = match self.run.take() {
+            Some(run) if run.dict == typed.dict && run.next == typed.index => run,
+            _ if typed.index == first => ChapterRun {
+                dict: typed.dict.to_string(),
+                next: first,
+                started: typed.started,
+                chars: 0,
+            },
+            _ => return,
+        };
+        run.chars += typed.word.chars().count() as u32;
+        if typed.index < last {
+            run.next = typed.index + 1;
+            self.run = Some(run);
+            return;
+        }
+
+        let chapter = typed.index / CHAPTER_SIZE + 1;
+        self.chapters.insert((typed.dict.to_string(), chapter));
+        self.unlock(Achievement::FirstChapter, today, rewards);
+
+        let speed = wpm(run.chars, (typed.now - run.started) / 1000.0);
+        self.best_chapter_wpm = self.best_chapter_wpm.max(speed);
+        if speed >= FAST_WPM {
+            self.unlock(Achievement::Speed80, today, rewards);
+        }
+
+        let finished = (1..=nr_chapter(typed.nr_word))
+            .all(|c| self.chapters.contains(&(typed.dict.to_string(), c)));
+        if typed.dict == "CET4" && finished {
+            self.unlock(Achievement::FinishedCet4, today, rewards);
+        }
+    }
+
+    /// Consecutive days practised or frozen, ending today, or yesterday if
+    /// today hasn't been practised yet.
+    pub fn streak(&self, practised: &BTreeSet<i64>, today: i64) -> u32 {
+        let kept = |day: i64| practised.contains(&day) || self.frozen.contains(&day);
+        let mut day = if kept(today) { today } else { today - 1 };
+        let mut streak = 0;
+        while kept(day) {
+            streak += 1;
+            day -= 1;
+        }
+        streak
+    }
+
+    /// Spend freezes on the days missed since the last practice, if there
+    /// are enough to cover them all, and earn one for each full week of
+    /// streak practised today.
+    pub fn settle(&mut self, practised: &BTreeSet<i64>, today: i64) -> Vec<Reward> {
+        let mut rewards = Vec::new();
+        let last = practised
+            .iter()
+            .chain(self.frozen.iter())
+            .filter(|day| **day < today)
+            .max()
+            .copied();
+        if let Some(last) = last {
+            let missed = (today - last - 1) as u32;
+            if missed > 0 && missed <= self.freezes {
+                self.frozen.extend(last + 1..today);
+                self.freezes -= missed;
+                rewards.push(Reward::FreezeUsed(missed));
+            }
+        }
+
+        let streak = self.streak(practised, today);
+        if practised.contains(&today)
+            && streak > 0
+            && streak.is_multiple_of(FREEZE_EVERY)
+            && self.freeze_earned != Some(today)
+            && self.freezes < MAX_FREEZES
+        {
+            self.freezes += 1;
+            self.freeze_earned = Some(today);
+            rewards.push(Reward::FreezeEarned);
+        }
+        rewards
+    }
+
+    /// Combine with rewards from a backup or another device, keeping the
+    /// better of each.
+    pub fn merge(&mut self, other: &Rewards) {
+        self.xp = self.xp.max(other.xp);
+        for (achievement, date) in other.unlocked.iter() {
+            let ours = self
+                .unlocked
+                .entry(*achievement)
+                .or_insert_with(|| date.clone());
+            if date < ours {
+                *ours = date.clone();
+            }
+        }
+        self.chapters.extend(other.chapters.iter().cloned());
+        self.best_clean_run = self.best_clean_run.max(other.best_clean_run);
+        self.best_chapter_wpm = self.best_chapter_wpm.max(other.best_chapter_wpm);
+        self.freezes = self.freezes.max(other.freezes);
+        self.frozen.extend(other.frozen.iter());
+        self.freeze_earned = self.freeze_earned.max(other.freeze_earned);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TODAY: &str = "2026-10-19";
+
+    fn typed<'a>(dict: &'a str, index: usize, word: &'a str, mistakes: u32, now: f64) -> Typed<'a> {
+        Typed {
+            dict,
+            nr_word: 45,
+            index,
+            word,
+            mistakes,
+            started: now - 500.0,
+            now,
+        }
+    }
+
+    fn unlocked(rewards: &[Reward]) -> Vec<Achievement> {
+        rewards
+            .iter()
+            .filter_map(|r| match r {
+                Reward::Unlocked(a) => Some(*a),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn xp_and_levels() {
+        assert_eq!(xp_for("apple", "CET4", 0), 5);
+        assert_eq!(xp_for("apple", "TOEFL", 0), 8);
+        assert_eq!(xp_for("apple", "TOEFL", 2), 4);
+        assert_eq!(xp_for("log in", "Phrases", 0), 5);
+        assert_eq!(xp_for("!", "Code", 0), 1);
+
+        assert_eq!(level_for(0), 1);
+        assert_eq!(level_for(99), 1);
+        assert_eq!(level_for(100), 2);
+        assert_eq!(level_for(299), 2);
+        assert_eq!(level_for(300), 3);
+
+        let mut rewards = Rewards {
+            xp: 97,
+            ..Default::default()
+        };
+        assert_eq!(
+            rewards.word(&typed("CET4", 7, "apple", 0, 0.0), TODAY),
+            vec![Reward::LevelUp(2)]
+        );
+        assert_eq!(rewards.xp, 102);
+    }
+
+    #[test]
+    fn clean_hundred_needs_no_mistakes() {
+        let mut rewards = Rewards::default();
+        for i in 0..99 {
+            assert!(
+                rewards
+                    .word(&typed("CET4", 5, "cat", 0, i as f64), TODAY)
+                    .is_empty()
+                    || i > 0
+            );
+        }
+        rewards.word(&typed("CET4", 5, "cat", 1, 0.0), TODAY);
+        assert_eq!(rewards.clean_run, 0);
+        assert_eq!(rewards.best_clean_run, 99);
+        assert!(!rewards.unlocked.contains_key(&Achievement::CleanHundred));
+
+        let mut got = Vec::new();
+        for _ in 0..100 {
+            got.extend(unlocked(
+                &rewards.word(&typed("CET4", 5, "cat", 0, 0.0), TODAY),
+            ));
+        }
+        assert_eq!(got, vec![Achievement::CleanHundred]);
+        assert_eq!(rewards.unlocked[&Achievement::CleanHundred], TODAY);
+    }
+
+    #[test]
+    fn chapters_count_only_when_typed_through() {
+        let mut rewards = Rewards::default();
+        // Starting in the middle doesn't count
+        for i in 5..20 {
+            rewards.word(&typed("CET4", i, "word", 0, 0.0), TODAY);
+        }
+        assert!(rewards.chapters.is_empty());
+
+        // Skipping a word breaks the run
+        for i in (0..20).filter(|i| *i != 10) {
+            rewards.word(&typed("CET4", i, "word", 0, 0.0), TODAY);
+        }
+        assert!(rewards.chapters.is_empty());
+
+        // Chapter 1 slowly: 20 words of 4 letters in 60 s is 16 WPM
+        let mut got = Vec::new();
+        for i in 0..20 {
+            let mut word = typed("CET4", i, "word", 0, 3000.0 * i as f64);
+            word.started = 0.0;
+            got.extend(unlocked(&rewards.word(&word, TODAY)));
+        }
+        assert_eq!(got, vec![Achievement::FirstChapter]);
+        assert!(rewards.chapters.contains(&(String::from("CET4"), 1)));
+        assert!((rewards.best_chapter_wpm - 16.0 * 20.0 / 19.0).abs() < 1e-6);
+
+        // Chapter 2 fast, 80 chars in 10 s is 96 WPM
+        for i in 20..40 {
+            let mut word = typed("CET4", i, "word", 0, 10_000.0);
+            word.started = 0.0;
+            got.extend(unlocked(&rewards.word(&word, TODAY)));
+        }
+        assert_eq!(got, vec![Achievement::FirstChapter, Achievement::Speed80]);
+
+        // The short last chapter finishes the dictionary
+        for i in 40..45 {
+            got.extend(unlocked(
+                &rewards.word(&typed("CET4", i, "word", 0, 0.0), TODAY),
+            ));
+        }
+        assert_eq!(got.last(), Some(&Achievement::FinishedCet4));
+
+        // Other dictionaries never finish CET4
+        let mut other = Rewards::default();
+        for i in 0..45 {
+            got.extend(unlocked(
+                &other.word(&typed("CET6", i, "word", 0, 0.0), TODAY),
+            ));
+        }
+        assert!(!other.unlocked.contains_key(&Achievement::FinishedCet4));
+    }
+
+    #[test]
+    fn freezes_cover_missed_days() {
+        let mut rewards = Rewards::default();
+        let mut practised: BTreeSet<i64> = (1..=7).collect();
+
+        // A week of practice earns one freeze, once
+        assert_eq!(rewards.settle(&practised, 7), vec![Reward::FreezeEarned]);
+        assert!(rewards.settle(&practised, 7).is_empty());
+        assert_eq!(rewards.freezes, 1);
+
+        // Day 8 is missed; coming back on day 9 spends the freeze
+        assert_eq!(rewards.streak(&practised, 8), 7);
+        practised.insert(9);
+        assert_eq!(rewards.settle(&practised, 9), vec![Reward::FreezeUsed(1)]);
+        assert_eq!(rewards.freezes, 0);
+        assert_eq!(rewards.streak(&practised, 9), 9);
+
+        // Two missed days without freezes break the streak
+        practised.insert(12);
+        assert!(rewards.settle(&practised, 12).is_empty());
+        assert_eq!(rewards.streak(&practised, 12), 1);
+
+        // Never more than MAX_FREEZES
+        let mut rewards = Rewards {
+            freezes: MAX_FREEZES,
+            ..Default::default()
+        };
+        let practised: BTreeSet<i64> = (1..=14).collect();
+        assert!(rewards.settle(&practised, 14).is_empty());
+        assert_eq!(rewards.freezes, MAX_FREEZES);
+    }
+
+    #[test]
+    fn merge_keeps_the_best_of_both() {
+        let mut ours = Rewards {
+            xp: 50,
+            best_clean_run: 30,
+            ..Default::default()
+        };
+        ours.unlocked
+            .insert(Achievement::FirstChapter, String::from("2026-10-02"));
+        let mut theirs = Rewards {
+            xp: 80,
+            best_clean_run: 10,
+            ..Default::default()
+        };
+        theirs
+            .unlocked
+            .insert(Achievement::FirstChapter, String::from("2026-10-01"));
+        theirs
+            .unlocked
+            .insert(Achievement::Speed80, String::from("2026-10-03"));
+        theirs.chapters.insert((String::from("CET4"), 3));
+
+        ours.merge(&theirs);
+        assert_eq!(ours.xp, 80);
+        assert_eq!(ours.best_clean_run, 30);
+        assert_eq!(ours.unlocked[&Achievement::FirstChapter], "2026-10-01");
+        assert!(ours.unlocked.contains_key(&Achievement::Speed80));
+        assert_eq!(ours.chapters.len(), 1);
+    }
+}
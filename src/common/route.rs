@@ -0,0 +1,291 @@
+use std::cell::RefCell;
+use wasm_bindgen::JsValue;
+
+use crate::common::dict::CHAPTER_SIZE;
+
+thread_local! {
+    static SHOWN: RefCell<Route> = RefCell::new(Route::default());
+    static LAST_PRACTICE: RefCell<Route> = RefCell::new(Route::default());
+}
+
+/// Client-side pages, addressed through the URL hash so they can be
+/// bookmarked, e.g. `#/practice/CET4/chapter/12` or `#/practice/CET4/word/250`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Route {
+    Practice {
+        dict: Option<String>,
+        chapter: Option<usize>,
+        word: Option<usize>,
+    },
+    Dictionary {
+        dict: Option<String>,
+    },
+    Stats,
+    Profile,
+    Placement,
+    Settings,
+}
+
+impl Default for Route {
+    fn default() -> Self {
+        Route::Practice {
+            dict: None,
+            chapter: None,
+            word: None,
+        }
+    }
+}
+
+/// A path segment with reserved and non-ASCII bytes percent-encoded, as
+/// `encodeURIComponent` does.
+fn encode(segment: &str) -> String {
+    let mut out = String::new();
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+/// A percent-encoded segment, as `location.hash` returns it, decoded like
+/// `decodeURIComponent`; malformed escapes are kept as they are.
+fn decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = segment
+            .get(i + 1..i + 3)
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).unwrap_or_else(|_| segment.to_string())
+}
+
+impl Route {
+    pub fn parse(hash: &str) -> Route {
+        let path = hash.trim_start_matches('#').trim_start_matches('/');
+        let parts: Vec<String> = path
+            .split('/')
+            .filter(|p| !p.is_empty())
+            .map(decode)
+            .collect();
+        let parts: Vec<&str> = parts.iter().map(String::as_str).collect();
+
+        match parts.as_slice() {
+            ["practice", dict, "chapter", chapter] => Route::Practice {
+                dict: Some(dict.to_string()),
+                chapter: chapter.parse().ok(),
+                word: None,
+            },
+            // The word decides the chapter, which the hash leaves out
+            ["practice", dict, "word", word] => {
+                let word: Option<usize> = word.parse().ok();
+                Route::Practice {
+                    dict: Some(dict.to_string()),
+                    chapter: word.map(|w| w / CHAPTER_SIZE + 1),
+                    word,
+                }
+            }
+            ["practice", dict] => Route::Practice {
+                dict: Some(dict.to_string()),
+                chapter: None,
+                word: None,
+            },
+            ["dict", dict] => Route::Dictionary {
+                dict: Some(dict.to_string()),
+            },
+            ["dict"] => Route::Dictionary { dict: None },
+            ["stats"] => Route::Stats,
+            ["profile"] => Route::Profile,
+            ["placement"] => Route::Placement,
+            ["settings"] => Route::Settings,
+            _ => Route::default(),
+        }
+    }
+
+    pub fn to_hash(&self) -> String {
+        match self {
+            Route::Practice {
+                dict: Some(dict),
+                word: Some(word),
+                ..
+            } => format!("#/practice/{}/word/{}", encode(dict), word),
+            Route::Practice {
+                dict: Some(dict),
+                chapter: Some(chapter),
+                ..
+            } => format!("#/practice/{}/chapter/{}", encode(dict), chapter),
+            Route::Practice {
+                dict: Some(dict), ..
+            } => format!("#/practice/{}", encode(dict)),
+            Route::Practice { .. } => String::from("#/practice"),
+            Route::Dictionary { dict: Some(dict) } => format!("#/dict/{}", encode(dict)),
+            Route::Dictionary { dict: None } => String::from("#/dict"),
+            Route::Stats => String::from("#/stats"),
+            Route::Profile => String::from("#/profile"),
+            Route::Placement => String::from("#/placement"),
+            Route::Settings => String::from("#/settings"),
+        }
+    }
+
+    /// The dictionary and chapter a practice route with a dictionary is on,
+    /// the chapter following the word when only that is given.
+    pub fn level(&self) -> Option<(String, usize)> {
+        match self {
+            Route::Practice {
+                dict: Some(dict),
+                chapter,
+                word,
+            } => {
+                let chapter = chapter.or_else(|| word.map(|w| w / CHAPTER_SIZE + 1));
+                Some((dict.clone(), chapter.unwrap_or(1)))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn current() -> Route {
+        let hash = web_sys::window()
+            .unwrap()
+            .location()
+            .hash()
+            .unwrap_or_default();
+        Route::parse(&hash)
+    }
+
+    /// The practice page as last shown, so the way back from another page
+    /// keeps the learner's place.
+    pub fn last_practice() -> Route {
+        LAST_PRACTICE.with(|last| last.borrow().clone())
+    }
+
+    /// The route in the address bar, including changes made by `replace`.
+    pub fn shown() -> Route {
+        SHOWN.with(|shown| shown.borrow().clone())
+    }
+
+    /// Note the route as shown; practice routes become `last_practice`.
+    pub fn remember(&self) {
+        SHOWN.with(|shown| *shown.borrow_mut() = self.clone());
+        if let Route::Practice { .. } = self {
+            LAST_PRACTICE.with(|last| *last.borrow_mut() = self.clone());
+        }
+    }
+
+    /// Rewrite the address bar without adding a history entry or firing
+    /// `hashchange`, for state changes made inside a page.
+    pub fn replace(&self) {
+        self.remember();
+        let window = web_sys::window().unwrap();
+        if let Ok(history) = window.history() {
+            let _ = history.replace_state_with_url(&JsValue::NULL, "", Some(&self.to_hash()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dictionary_names_round_trip() {
+        for name in ["CET4", "My Deck", "我的 生词本", "50% / more"] {
+            let practice = Route::Practice {
+                dict: Some(name.to_string()),
+                chapter: Some(3),
+                word: None,
+            };
+            assert_eq!(Route::parse(&practice.to_hash()), practice);
+            let browse = Route::Dictionary {
+                dict: Some(name.to_string()),
+            };
+            assert_eq!(Route::parse(&browse.to_hash()), browse);
+        }
+        assert_eq!(
+            Route::parse("#/dict/My%20Deck").to_hash(),
+            "#/dict/My%20Deck"
+        );
+        assert_eq!(
+            Route::parse("#/dict/%E7%94%9F%E8%AF%8D"),
+            Route::Dictionary {
+                dict: Some(String::from("生词"))
+            }
+        );
+        assert_eq!(
+            Route::parse("#/dict/100%"),
+            Route::Dictionary {
+                dict: Some(String::from("100%"))
+            }
+        );
+    }
+
+    #[test]
+    fn practice_routes_round_trip() {
+        let at_word = Route::Practice {
+            dict: Some(String::from("CET4")),
+            chapter: Some(3),
+            word: Some(45),
+        };
+        assert_eq!(at_word.to_hash(), "#/practice/CET4/word/45");
+        assert_eq!(Route::parse(&at_word.to_hash()), at_word);
+        let first = Route::parse("#/practice/CET4/word/0");
+        assert_eq!(Route::parse(&first.to_hash()), first);
+        for hash in ["#/practice/CET4/chapter/7", "#/practice/CET4", "#/practice"] {
+            assert_eq!(Route::parse(hash).to_hash(), hash);
+        }
+    }
+
+    #[test]
+    fn practice_routes_name_their_chapter() {
+        let level = |hash| Route::parse(hash).level();
+        assert_eq!(
+            level("#/practice/CET6/word/0"),
+            Some((String::from("CET6"), 1))
+        );
+        assert_eq!(
+            level("#/practice/CET6/word/19"),
+            Some((String::from("CET6"), 1))
+        );
+        assert_eq!(
+            level("#/practice/CET6/word/20"),
+            Some((String::from("CET6"), 2))
+        );
+        assert_eq!(
+            level("#/practice/CET6/chapter/5"),
+            Some((String::from("CET6"), 5))
+        );
+        assert_eq!(level("#/practice/CET6"), Some((String::from("CET6"), 1)));
+        assert_eq!(level("#/practice"), None);
+        assert_eq!(level("#/stats"), None);
+        let unparsed = Route::Practice {
+            dict: Some(String::from("CET4")),
+            chapter: None,
+            word: Some(45),
+        };
+        assert_eq!(unparsed.level(), Some((String::from("CET4"), 3)));
+    }
+
+    #[test]
+    fn remembers_the_last_practice_page() {
+        assert_eq!(Route::last_practice(), Route::default());
+        let practice = Route::parse("#/practice/CET6/chapter/4");
+        practice.remember();
+        Route::Stats.remember();
+        assert_eq!(Route::last_practice(), practice);
+        assert_eq!(Route::shown(), Route::Stats);
+    }
+}
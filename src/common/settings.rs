@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter, Result};
+
+use crate::common::i18n::{t, tf, Locale};
+use crate::common::sound_pack::{SoundPack, Variation};
+use crate::common::storage::{self, KEY_SETTINGS};
+use crate::common::theme::Theme;
+use crate::common::typing::{CasePolicy, Separators};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum DailyGoal {
+    Off,
+    Minutes(u32),
+    Words(u32),
+}
+
+impl DailyGoal {
+    pub fn presets() -> &'static [DailyGoal] {
+        &[
+            DailyGoal::Off,
+            DailyGoal::Minutes(15),
+            DailyGoal::Minutes(30),
+            DailyGoal::Minutes(60),
+            DailyGoal::Words(50),
+            DailyGoal::Words(100),
+            DailyGoal::Words(200),
+        ]
+    }
+
+    pub fn key(&self) -> String {
+        match self {
+            DailyGoal::Off => String::from("off"),
+            DailyGoal::Minutes(n) => format!("min:{}", n),
+            DailyGoal::Words(n) => format!("word:{}", n),
+        }
+    }
+
+    pub fn from_key(key: &str) -> DailyGoal {
+        match key.split_once(':') {
+            Some(("min", n)) => n.parse().map(DailyGoal::Minutes).unwrap_or(DailyGoal::Off),
+            Some(("word", n)) => n.parse().map(DailyGoal::Words).unwrap_or(DailyGoal::Off),
+            _ => DailyGoal::Off,
+        }
+    }
+}
+
+impl Display for DailyGoal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            DailyGoal::Off => write!(f, "{}", t("goal.off")),
+            DailyGoal::Minutes(n) => write!(f, "{}", tf("goal.minutes", &[n])),
+            DailyGoal::Words(n) => write!(f, "{}", tf("goal.words", &[n])),
+        }
+    }
+}
+
+/// Which sounds play on the practice page, and how.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub volume: u32, // percent, for every sound
+    pub clicks: bool,
+    pub pack: SoundPack, // what the clicks sound like
+    pub variation: Variation,
+    pub feedback: bool, // correct and wrong sounds
+    pub pronunciation: bool,
+    pub repeat: u32,   // times each word is pronounced
+    pub interval: u32, // ms between repeats
+    pub rate: u32,     // percent of normal pronunciation speed
+}
+
+impl AudioSettings {
+    pub const REPEATS: [u32; 4] = [1, 2, 3, 5];
+    pub const INTERVALS: [u32; 3] = [500, 1000, 2000];
+    pub const RATES: [u32; 3] = [50, 75, 100];
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings {
+            volume: 100,
+            clicks: true,
+            pack: SoundPack::Classic,
+            variation: Variation::Same,
+            feedback: true,
+            pronunciation: true,
+            repeat: 1,
+            interval: 1000,
+            rate: 100,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub pomodoro: bool,
+    pub pomodoro_work: u32, // minutes
+    pub pomodoro_break: u32,
+    pub daily_goal: DailyGoal,
+    pub locale: Option<Locale>, // None follows the browser
+    pub theme: Option<Theme>,   // None follows prefers-color-scheme
+    pub auto_close: bool,       // skip closing brackets in code mode
+    pub separators: Separators, // spaces, hyphens and apostrophes in phrases
+    pub case: CasePolicy,
+    pub race_ghost: bool,    // race chapters against their best run
+    pub skip_mastered: bool, // Next passes over mastered words
+    pub race_server: String,
+    pub race_name: String,
+    pub audio: AudioSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            pomodoro: false,
+            pomodoro_work: 25,
+            pomodoro_break: 5,
+            daily_goal: DailyGoal::Off,
+            locale: None,
+            theme: None,
+            auto_close: false,
+            separators: Separators::default(),
+            case: CasePolicy::Exact,
+            race_ghost: false,
+            skip_mastered: false,
+            race_server: String::from("ws://localhost:9001"),
+            race_name: String::new(),
+            audio: AudioSettings::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        storage::load(KEY_SETTINGS)
+    }
+
+    /// Reload, apply `f` and store, so components holding an older copy
+    /// don't clobber each other's fields.
+    pub fn modify<F: FnOnce(&mut Settings)>(f: F) -> Self {
+        let mut settings = Settings::load();
+        f(&mut settings);
+        storage::save(KEY_SETTINGS, &settings);
+        settings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn audio_settings_default_when_missing() {
+        let settings: Settings = serde_json::from_str(r#"{"pomodoro": true}"#).unwrap();
+        assert!(settings.pomodoro);
+        assert_eq!(settings.audio, AudioSettings::default());
+
+        let settings: Settings =
+            serde_json::from_str(r#"{"audio": {"volume": 40, "clicks": false}}"#).unwrap();
+        assert_eq!(settings.audio.volume, 40);
+        assert!(!settings.audio.clicks);
+        assert_eq!(settings.audio.repeat, 1);
+        assert_eq!(settings.audio.rate, 100);
+    }
+}
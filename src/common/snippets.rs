@@ -0,0 +1,368 @@
+use serde::{Deserialize, Serialize};
+
+use crate::common::storage::{self, KEY_SNIPPETS};
+
+const PACK_RUST: &str = include_str!("../content/snippets/rust.json");
+const PACK_PYTHON: &str = include_str!("../content/snippets/python.json");
+const PACK_JAVASCRIPT: &str = include_str!("../content/snippets/javascript.json");
+const PACK_SHELL: &str = include_str!("../content/snippets/shell.json");
+
+lazy_static::lazy_static! {
+    static ref BUILTIN_PACKS: Vec<SnippetPack> =
+        [PACK_RUST, PACK_PYTHON, PACK_JAVASCRIPT, PACK_SHELL]
+            .iter()
+            .map(|text| serde_json::from_str(text).unwrap())
+            .collect();
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    Rust,
+    Python,
+    Javascript,
+    Shell,
+    #[default]
+    #[serde(other)]
+    Plain,
+}
+
+impl Lang {
+    fn keywords(&self) -> &'static [&'static str] {
+        match self {
+            Lang::Rust => &[
+                "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else",
+                "enum", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod",
+                "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "trait",
+                "true", "type", "use", "where", "while",
+            ],
+            Lang::Python => &[
+                "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class",
+                "continue", "def", "del", "elif", "else", "except", "finally", "for", "from",
+                "global", "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass",
+                "raise", "return", "try", "while", "with", "yield",
+            ],
+            Lang::Javascript => &[
+                "async",
+                "await",
+                "break",
+                "case",
+                "catch",
+                "class",
+                "const",
+                "continue",
+                "default",
+                "delete",
+                "do",
+                "else",
+                "export",
+                "extends",
+                "false",
+                "finally",
+                "for",
+                "function",
+                "if",
+                "import",
+                "in",
+                "instanceof",
+                "let",
+                "new",
+                "null",
+                "of",
+                "return",
+                "switch",
+                "this",
+                "throw",
+                "true",
+                "try",
+                "typeof",
+                "undefined",
+                "var",
+                "void",
+                "while",
+                "yield",
+            ],
+            Lang::Shell => &[
+                "case", "do", "done", "echo", "elif", "else", "esac", "exit", "export", "fi",
+                "for", "function", "if", "in", "local", "return", "then", "until", "while",
+            ],
+            Lang::Plain => &[],
+        }
+    }
+
+    fn line_comment(&self) -> Option<&'static str> {
+        match self {
+            Lang::Rust | Lang::Javascript => Some("//"),
+            Lang::Python | Lang::Shell => Some("#"),
+            Lang::Plain => None,
+        }
+    }
+
+    fn quotes(&self) -> &'static [char] {
+        match self {
+            // Rust's `'` also starts lifetimes, so only `"` is a string there
+            Lang::Rust => &['"'],
+            Lang::Javascript => &['"', '\'', '`'],
+            Lang::Python | Lang::Shell => &['"', '\''],
+            Lang::Plain => &[],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Snippet {
+    pub title: String,
+    pub code: String,
+}
+
+/// A named set of snippets in one language, as stored in
+/// `src/content/snippets` or imported from a local file.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct SnippetPack {
+    pub name: String,
+    pub lang: Lang,
+    pub snippets: Vec<Snippet>,
+}
+
+impl SnippetPack {
+    /// Parse and check a pack file before it is imported.
+    pub fn parse(text: &str) -> Result<SnippetPack, String> {
+        let pack: SnippetPack = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        pack.check()
+    }
+
+    /// Normalise the pack, failing if it has no name or nothing to type.
+    pub fn check(self) -> Result<SnippetPack, String> {
+        let mut pack = self;
+        pack.name = pack.name.trim().to_string();
+        pack.snippets.retain(|s| !s.code.trim().is_empty());
+        for snippet in pack.snippets.iter_mut() {
+            snippet.code = snippet.code.replace("\r\n", "\n").replace('\t', "    ");
+        }
+        if pack.name.is_empty() {
+            return Err(String::from("missing pack name"));
+        }
+        if pack.snippets.is_empty() {
+            return Err(String::from("no snippets"));
+        }
+        Ok(pack)
+    }
+
+    /// Snippets as dictionary-shaped entries for the keyboard.
+    pub fn entries(&self) -> serde_json::Value {
+        self.snippets
+            .iter()
+            .map(|s| serde_json::json!({ "name": s.code, "trans": [s.title] }))
+            .collect()
+    }
+}
+
+pub fn builtin() -> &'static [SnippetPack] {
+    &BUILTIN_PACKS
+}
+
+/// Whether `name` is taken by a built-in pack.
+pub fn is_builtin(name: &str) -> bool {
+    builtin().iter().any(|p| p.name == name)
+}
+
+/// Packs imported by the user, kept in local storage.
+pub fn imported() -> Vec<SnippetPack> {
+    storage::load(KEY_SNIPPETS)
+}
+
+/// Add `pack`, replacing an imported pack of the same name.
+pub fn import(pack: SnippetPack) {
+    let mut packs = imported();
+    packs.retain(|p| p.name != pack.name);
+    packs.push(pack);
+    storage::save(KEY_SNIPPETS, &packs);
+}
+
+pub fn remove(name: &str) {
+    let mut packs = imported();
+    packs.retain(|p| p.name != name);
+    storage::save(KEY_SNIPPETS, &packs);
+}
+
+pub fn names() -> Vec<String> {
+    builtin()
+        .iter()
+        .cloned()
+        .chain(imported())
+        .map(|p| p.name)
+        .collect()
+}
+
+pub fn find(name: &str) -> Option<SnippetPack> {
+    builtin()
+        .iter()
+        .find(|p| p.name == name)
+        .cloned()
+        .or_else(|| imported().into_iter().find(|p| p.name == name))
+}
+
+/// Bytes of `target` after `typed` that are filled in without typing:
+/// indentation following a newline and, with `auto_close`, closing brackets.
+pub fn auto_fill(target: &str, typed: &str, auto_close: bool) -> usize {
+    let rest = match target.strip_prefix(typed) {
+        Some(rest) => rest,
+        None => return 0,
+    };
+    let mut at_line_start = typed.is_empty() || typed.ends_with('\n');
+    let mut fill = 0;
+    for c in rest.chars() {
+        let skip = (at_line_start && (c == ' ' || c == '\t'))
+            || (auto_close && matches!(c, ')' | ']' | '}'));
+        if !skip {
+            break;
+        }
+        at_line_start = at_line_start && (c == ' ' || c == '\t');
+        fill += c.len_utf8();
+    }
+    fill
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Token {
+    Plain,
+    Keyword,
+    Str,
+    Comment,
+    Number,
+    Punct,
+}
+
+impl Token {
+    pub fn class(&self) -> &'static str {
+        match self {
+            Token::Plain => "tok-plain",
+            Token::Keyword => "tok-kw",
+            Token::Str => "tok-str",
+            Token::Comment => "tok-comment",
+            Token::Number => "tok-num",
+            Token::Punct => "tok-punct",
+        }
+    }
+}
+
+/// A token class for every char of `code`; a small lexer that is good
+/// enough for colouring short snippets.
+pub fn highlight(lang: Lang, code: &str) -> Vec<Token> {
+    let chars: Vec<char> = code.chars().collect();
+    let mut tokens = vec![Token::Plain; chars.len()];
+    let comment: Vec<char> = lang.line_comment().unwrap_or("").chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        let token = if !comment.is_empty() && chars[i..].starts_with(&comment) {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            Token::Comment
+        } else if lang.quotes().contains(&c) {
+            i += 1;
+            while i < chars.len() && chars[i] != c && chars[i] != '\n' {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(chars.len());
+            Token::Str
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            Token::Number
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            if lang.keywords().contains(&word.as_str()) {
+                Token::Keyword
+            } else {
+                Token::Plain
+            }
+        } else {
+            i += 1;
+            if c.is_ascii_punctuation() {
+                Token::Punct
+            } else {
+                Token::Plain
+            }
+        };
+        let end = i.min(chars.len());
+        tokens[start..end].iter_mut().for_each(|t| *t = token);
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_packs_parse() {
+        let names: Vec<&str> = builtin().iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Rust", "Python", "JavaScript", "Shell"]);
+        assert!(builtin().iter().all(|p| p.lang != Lang::Plain));
+        assert!(builtin().iter().all(|p| !p.snippets.is_empty()));
+        assert!(is_builtin("Rust"));
+        assert!(!is_builtin("rust"));
+    }
+
+    #[test]
+    fn parse_normalises_and_validates() {
+        let pack = SnippetPack::parse(
+            r#"{ "name": " Go ", "lang": "go", "snippets": [
+                { "title": "loop", "code": "for {\r\n\tbreak\r\n}" },
+                { "title": "empty", "code": "  " }
+            ] }"#,
+        )
+        .unwrap();
+        assert_eq!(pack.name, "Go");
+        assert_eq!(pack.lang, Lang::Plain);
+        assert_eq!(pack.snippets.len(), 1);
+        assert_eq!(pack.snippets[0].code, "for {\n    break\n}");
+
+        assert!(SnippetPack::parse("[]").is_err());
+        assert!(SnippetPack::parse(r#"{ "name": "x", "snippets": [] }"#).is_err());
+        assert!(SnippetPack::parse(r#"{ "snippets": [{ "code": "x" }] }"#).is_err());
+    }
+
+    #[test]
+    fn fills_indentation_and_closers() {
+        let code = "fn f() {\n    g(1);\n}\n";
+        assert_eq!(auto_fill(code, "fn f() {\n", false), 4);
+        assert_eq!(auto_fill(code, "fn f() {\n    g(1", false), 0);
+        assert_eq!(auto_fill(code, "fn f() {\n    g(1", true), 1);
+        assert_eq!(auto_fill(code, "fn f() {\n    g(1);\n", false), 0);
+        assert_eq!(auto_fill(code, "fn f() {\n    g(1);\n", true), 1);
+        assert_eq!(auto_fill(code, "fn x", true), 0);
+        assert_eq!(auto_fill("    x", "", false), 4);
+    }
+
+    #[test]
+    fn highlights_tokens() {
+        let code = "let s = \"a\\\"b\"; // 42";
+        let tokens = highlight(Lang::Rust, code);
+        assert_eq!(tokens.len(), code.chars().count());
+        assert_eq!(&tokens[0..3], &[Token::Keyword; 3]);
+        assert_eq!(tokens[3], Token::Plain);
+        assert_eq!(tokens[4], Token::Plain);
+        assert_eq!(tokens[6], Token::Punct);
+        assert_eq!(&tokens[8..14], &[Token::Str; 6]);
+        assert_eq!(tokens[14], Token::Punct);
+        assert!(tokens[16..].iter().all(|t| *t == Token::Comment));
+
+        let tokens = highlight(Lang::Python, "x = 10  # n");
+        assert_eq!(&tokens[4..6], &[Token::Number; 2]);
+        assert_eq!(tokens[8], Token::Comment);
+        assert!(highlight(Lang::Plain, "if x")
+            .iter()
+            .all(|t| *t == Token::Plain));
+    }
+}
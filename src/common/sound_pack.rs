@@ -0,0 +1,388 @@
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Cache, File, Response};
+
+use crate::common::i18n::{t, tf};
+use crate::common::storage::{self, KEY_SOUND_PACKS};
+
+// Uploaded samples live in Cache Storage, the pack list in local storage
+const CACHE_NAME: &str = "sound-packs";
+pub const MAX_SAMPLES: usize = 16;
+const MAX_SAMPLE_BYTES: f64 = 1024.0 * 1024.0;
+/// Samples synthesised for each built-in pack.
+pub const VARIANTS: usize = 4;
+
+/// The sound of a key press.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub enum SoundPack {
+    #[default]
+    Classic, // the embedded click.wav
+    Blue, // clicky mechanical switches
+    Brown,
+    Typewriter,
+    Soft,
+    Custom(String), // uploaded by the user, by name
+}
+
+impl SoundPack {
+    pub fn built_in() -> [SoundPack; 5] {
+        [
+            SoundPack::Classic,
+            SoundPack::Blue,
+            SoundPack::Brown,
+            SoundPack::Typewriter,
+            SoundPack::Soft,
+        ]
+    }
+
+    pub fn code(&self) -> String {
+        match self {
+            SoundPack::Classic => String::from("classic"),
+            SoundPack::Blue => String::from("blue"),
+            SoundPack::Brown => String::from("brown"),
+            SoundPack::Typewriter => String::from("typewriter"),
+            SoundPack::Soft => String::from("soft"),
+            SoundPack::Custom(name) => format!("custom:{}", name),
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<SoundPack> {
+        match code.strip_prefix("custom:") {
+            Some(name) => Some(SoundPack::Custom(name.to_string())),
+            None => SoundPack::built_in().into_iter().find(|p| p.code() == code),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            SoundPack::Classic => String::from(t("pack.classic")),
+            SoundPack::Blue => String::from(t("pack.blue")),
+            SoundPack::Brown => String::from(t("pack.brown")),
+            SoundPack::Typewriter => String::from(t("pack.typewriter")),
+            SoundPack::Soft => String::from(t("pack.soft")),
+            SoundPack::Custom(name) => name.clone(),
+        }
+    }
+}
+
+/// Which sample of a pack a key press plays.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub enum Variation {
+    #[default]
+    Same,
+    PerKey, // each key always sounds the same
+    Random, // any sample, slightly detuned
+}
+
+impl Variation {
+    pub fn all() -> [Variation; 3] {
+        [Variation::Same, Variation::PerKey, Variation::Random]
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Variation::Same => "same",
+            Variation::PerKey => "per-key",
+            Variation::Random => "random",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Variation> {
+        Variation::all().into_iter().find(|v| v.code() == code)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Variation::Same => t("variation.same"),
+            Variation::PerKey => t("variation.per_key"),
+            Variation::Random => t("variation.random"),
+        }
+    }
+}
+
+/// Index of the sample to play for `key` out of `count`, `random` in [0, 1).
+pub fn pick(variation: Variation, key: &str, count: usize, random: f64) -> usize {
+    if count == 0 {
+        return 0;
+    }
+    match variation {
+        Variation::Same => 0,
+        Variation::PerKey => {
+            let hash = key
+                .bytes()
+                .fold(7usize, |h, b| h.wrapping_mul(31).wrapping_add(b as usize));
+            hash % count
+        }
+        Variation::Random => ((random * count as f64) as usize).min(count - 1),
+    }
+}
+
+/// Shape of a synthesised key press.
+struct Voice {
+    length: f32,              // seconds
+    tone: f32,                // Hz of the body of the key
+    tone_decay: f32,          // seconds
+    noise: f32,               // level of the click
+    noise_decay: f32,         // seconds
+    smooth: f32,              // 0 leaves the noise bright, towards 1 muffles it
+    second: Option<f32>,      // seconds until the bottom-out click
+    ping: Option<(f32, f32)>, // Hz and decay of a ringing bell
+}
+
+fn voice(pack: &SoundPack) -> Option<Voice> {
+    let voice = match pack {
+        SoundPack::Blue => Voice {
+            length: 0.07,
+            tone: 2400.0,
+            tone_decay: 0.004,
+            noise: 1.0,
+            noise_decay: 0.003,
+            smooth: 0.1,
+            second: Some(0.025),
+            ping: None,
+        },
+        SoundPack::Brown => Voice {
+            length: 0.08,
+            tone: 420.0,
+            tone_decay: 0.012,
+            noise: 0.6,
+            noise_decay: 0.005,
+            smooth: 0.6,
+            second: Some(0.02),
+            ping: None,
+        },
+        SoundPack::Typewriter => Voice {
+            length: 0.14,
+            tone: 160.0,
+            tone_decay: 0.02,
+            noise: 1.0,
+            noise_decay: 0.006,
+            smooth: 0.2,
+            second: None,
+            ping: Some((2900.0, 0.05)),
+        },
+        SoundPack::Soft => Voice {
+            length: 0.06,
+            tone: 300.0,
+            tone_decay: 0.01,
+            noise: 0.4,
+            noise_decay: 0.008,
+            smooth: 0.85,
+            second: None,
+            ping: None,
+        },
+        SoundPack::Classic | SoundPack::Custom(_) => return None,
+    };
+    Some(voice)
+}
+
+/// Mono samples of variant `variant` of a built-in pack, normalised to a
+/// peak of 0.8; empty for packs that are not synthesised.
+pub fn synthesize(pack: &SoundPack, variant: usize, sample_rate: f32) -> Vec<f32> {
+    let voice = match voice(pack) {
+        Some(voice) => voice,
+        None => return Vec::new(),
+    };
+    // Each variant is a little higher or lower, with its own noise
+    let pitch = 1.0 + 0.06 * (variant as f32 - (VARIANTS as f32 - 1.0) / 2.0);
+    let mut seed = 0x9E37_79B9u32 ^ (variant as u32 + 1).wrapping_mul(0x85EB_CA6B);
+    let mut noise = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        seed as f32 / u32::MAX as f32 * 2.0 - 1.0
+    };
+
+    let len = (voice.length * sample_rate) as usize;
+    let mut filtered = 0.0;
+    let mut samples: Vec<f32> = (0..len)
+        .map(|i| {
+            let time = i as f32 / sample_rate;
+            filtered = filtered * voice.smooth + noise() * (1.0 - voice.smooth);
+            let mut clicks = (-time / voice.noise_decay).exp();
+            if let Some(second) = voice.second {
+                if time >= second {
+                    clicks += 0.7 * (-(time - second) / voice.noise_decay).exp();
+                }
+            }
+            let tone = (TAU * voice.tone * pitch * time).sin() * (-time / voice.tone_decay).exp();
+            let ping = voice.ping.map_or(0.0, |(hz, decay)| {
+                0.3 * (TAU * hz * pitch * time).sin() * (-time / decay).exp()
+            });
+            voice.noise * filtered * clicks + tone + ping
+        })
+        .collect();
+
+    let peak = samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    if peak > 0.0 {
+        samples.iter_mut().for_each(|s| *s *= 0.8 / peak);
+    }
+    samples
+}
+
+/// A pack of uploaded samples.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct CustomPack {
+    pub name: String,
+    pub samples: usize,
+}
+
+pub fn custom_packs() -> Vec<CustomPack> {
+    storage::load(KEY_SOUND_PACKS)
+}
+
+fn save_custom_packs(packs: &[CustomPack]) {
+    storage::save(KEY_SOUND_PACKS, &packs);
+}
+
+fn sample_url(name: &str, index: usize) -> String {
+    format!(
+        "/sound-packs/{}/{}",
+        js_sys::encode_uri_component(name),
+        index
+    )
+}
+
+async fn open() -> Result<Cache, JsValue> {
+    let caches = web_sys::window().unwrap().caches()?;
+    JsFuture::from(caches.open(CACHE_NAME)).await?.dyn_into()
+}
+
+/// Check an upload before storing anything.
+pub fn validate(name: &str, files: &[File]) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err(String::from(t("pack.error_name")));
+    }
+    if files.is_empty() || files.len() > MAX_SAMPLES {
+        return Err(tf("pack.error_count", &[&MAX_SAMPLES]));
+    }
+    match files
+        .iter()
+        .find(|f| !f.type_().starts_with("audio/") || f.size() > MAX_SAMPLE_BYTES)
+    {
+        Some(file) => Err(tf("pack.error_file", &[&file.name()])),
+        None => Ok(()),
+    }
+}
+
+/// Store `files` as the samples of the pack `name`, replacing any pack of
+/// that name.
+pub async fn import(name: String, files: Vec<File>) -> Result<CustomPack, String> {
+    validate(&name, &files)?;
+    let name = name.trim().to_string();
+    remove(&name).await;
+    let cache = open().await.map_err(|e| format!("{:?}", e))?;
+    for (i, file) in files.iter().enumerate() {
+        let response = Response::new_with_opt_blob(Some(file)).map_err(|e| format!("{:?}", e))?;
+        JsFuture::from(cache.put_with_str(&sample_url(&name, i), &response))
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+    }
+
+    let pack = CustomPack {
+        name,
+        samples: files.len(),
+    };
+    let mut packs = custom_packs();
+    packs.push(pack.clone());
+    save_custom_packs(&packs);
+    Ok(pack)
+}
+
+/// Delete the pack `name` and its samples.
+pub async fn remove(name: &str) {
+    let mut packs = custom_packs();
+    let removed: Vec<CustomPack> = packs.iter().filter(|p| p.name == name).cloned().collect();
+    packs.retain(|p| p.name != name);
+    save_custom_packs(&packs);
+    if let Ok(cache) = open().await {
+        for pack in removed {
+            for i in 0..pack.samples {
+                let _ = JsFuture::from(cache.delete_with_str(&sample_url(name, i))).await;
+            }
+        }
+    }
+}
+
+/// The raw samples of the pack `name`, ready to be decoded.
+pub async fn load(name: &str) -> Vec<js_sys::ArrayBuffer> {
+    let samples = match custom_packs().into_iter().find(|p| p.name == name) {
+        Some(pack) => pack.samples,
+        None => return Vec::new(),
+    };
+    let cache = match open().await {
+        Ok(cache) => cache,
+        Err(_) => return Vec::new(),
+    };
+    let mut buffers = Vec::new();
+    for i in 0..samples {
+        let response = JsFuture::from(cache.match_with_str(&sample_url(name, i))).await;
+        let response = match response.ok().and_then(|r| r.dyn_into::<Response>().ok()) {
+            Some(response) => response,
+            None => continue,
+        };
+        if let Ok(buffer) = response.array_buffer() {
+            if let Ok(buffer) = JsFuture::from(buffer).await {
+                buffers.push(buffer.unchecked_into());
+            }
+        }
+    }
+    buffers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_packs_are_synthesised() {
+        for pack in SoundPack::built_in() {
+            let variants: Vec<Vec<f32>> = (0..VARIANTS)
+                .map(|v| synthesize(&pack, v, 44100.0))
+                .collect();
+            if pack == SoundPack::Classic {
+                assert!(variants.iter().all(Vec::is_empty));
+                continue;
+            }
+            for samples in &variants {
+                let peak = samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+                assert!((peak - 0.8).abs() < 1e-4, "{:?} peaks at {}", pack, peak);
+                // Dies away instead of stopping with a click
+                assert!(samples.last().unwrap().abs() < 0.1, "{:?}", pack);
+            }
+            assert_eq!(variants[0], synthesize(&pack, 0, 44100.0));
+            assert_ne!(variants[0], variants[1]);
+        }
+        assert!(synthesize(&SoundPack::Custom(String::from("mine")), 0, 44100.0).is_empty());
+    }
+
+    #[test]
+    fn picks_samples_by_variation() {
+        assert_eq!(pick(Variation::Same, "a", 4, 0.9), 0);
+        assert_eq!(
+            pick(Variation::PerKey, "a", 4, 0.1),
+            pick(Variation::PerKey, "a", 4, 0.9)
+        );
+        let keys: Vec<usize> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|k| pick(Variation::PerKey, k, 4, 0.0))
+            .collect();
+        assert!(keys.iter().any(|i| *i != keys[0]));
+        assert_eq!(pick(Variation::Random, "a", 4, 0.0), 0);
+        assert_eq!(pick(Variation::Random, "a", 4, 0.99), 3);
+        assert_eq!(pick(Variation::Random, "a", 0, 0.5), 0);
+    }
+
+    #[test]
+    fn pack_codes_round_trip() {
+        for pack in SoundPack::built_in() {
+            assert_eq!(SoundPack::from_code(&pack.code()), Some(pack));
+        }
+        let mine = SoundPack::Custom(String::from("my: board"));
+        assert_eq!(SoundPack::from_code(&mine.code()), Some(mine));
+        assert_eq!(SoundPack::from_code("nope"), None);
+    }
+}
@@ -0,0 +1,89 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::cell::RefCell;
+use yew::format::{Json, Text};
+use yew::services::storage::{Area, StorageService};
+
+use crate::common::profiles::{scoped_key, Profiles};
+
+pub const KEY_SETTINGS: &str = "keypress.settings";
+pub const KEY_DAILY: &str = "keypress.daily";
+pub const KEY_HISTORY: &str = "keypress.history";
+pub const KEY_SNIPPETS: &str = "keypress.snippets";
+pub const KEY_RUNS: &str = "keypress.runs";
+pub const KEY_SYNC: &str = "keypress.sync";
+pub const KEY_AUDIO: &str = "keypress.audio";
+pub const KEY_SOUND_PACKS: &str = "keypress.sound_packs";
+pub const KEY_REWARDS: &str = "keypress.rewards";
+pub const KEY_PROFILES: &str = "keypress.profiles";
+pub const KEY_MASTERY: &str = "keypress.mastery";
+pub const KEY_USER_DICTS: &str = "keypress.user_dicts";
+
+// The same for every profile: the profile list itself, and indexes of the
+// caches the browser shares between them
+const SHARED: [&str; 3] = [KEY_PROFILES, KEY_AUDIO, KEY_SOUND_PACKS];
+
+/// Everything kept apart for each profile.
+pub const PROFILE_KEYS: [&str; 9] = [
+    KEY_SETTINGS,
+    KEY_DAILY,
+    KEY_HISTORY,
+    KEY_SNIPPETS,
+    KEY_RUNS,
+    KEY_SYNC,
+    KEY_REWARDS,
+    KEY_MASTERY,
+    KEY_USER_DICTS,
+];
+
+thread_local! {
+    static ACTIVE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Id of the profile the page was opened with.
+pub fn active_profile() -> String {
+    ACTIVE.with(|active| {
+        active
+            .borrow_mut()
+            .get_or_insert_with(|| load_raw::<Profiles>(KEY_PROFILES).active)
+            .clone()
+    })
+}
+
+fn key_for(key: &str) -> String {
+    if SHARED.contains(&key) {
+        key.to_string()
+    } else {
+        scoped_key(key, &active_profile())
+    }
+}
+
+/// Remove everything stored for the profile `id`.
+pub fn clear_profile(id: &str) {
+    if let Ok(mut storage) = StorageService::new(Area::Local) {
+        for key in PROFILE_KEYS.iter() {
+            storage.remove(&scoped_key(key, id));
+        }
+    }
+}
+
+/// The value under `key` for the active profile.
+pub fn load<T: DeserializeOwned + Default>(key: &str) -> T {
+    load_raw(&key_for(key))
+}
+
+fn load_raw<T: DeserializeOwned + Default>(key: &str) -> T {
+    StorageService::new(Area::Local)
+        .ok()
+        .and_then(|storage| {
+            let text: Text = storage.restore(key);
+            text.ok()
+        })
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    if let Ok(mut storage) = StorageService::new(Area::Local) {
+        storage.store(&key_for(key), Json(value));
+    }
+}
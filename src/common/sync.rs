@@ -0,0 +1,164 @@
+use keypress_protocol::sync::{Entry, Replica, SYNC_PATH};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+
+use crate::common::backup::Backup;
+use crate::common::history::{DailyProgress, History, SessionRecord};
+use crate::common::storage::{self, KEY_DAILY, KEY_HISTORY, KEY_SYNC};
+
+/// Where this device syncs to, and what it synced last.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct SyncConfig {
+    pub server: String, // e.g. http://localhost:9002
+    pub token: String,
+    pub replica: Replica,
+    pub last: Option<f64>, // ms since the epoch of the last successful sync
+}
+
+impl SyncConfig {
+    pub fn load() -> Self {
+        storage::load(KEY_SYNC)
+    }
+
+    pub fn save(&self) {
+        storage::save(KEY_SYNC, self);
+    }
+
+    pub fn enabled(&self) -> bool {
+        !self.server.trim().is_empty() && !self.token.trim().is_empty()
+    }
+
+    pub fn url(&self) -> String {
+        format!("{}{}", self.server.trim().trim_end_matches('/'), SYNC_PATH)
+    }
+}
+
+fn session_key(rec: &SessionRecord) -> String {
+    format!("{}/{}", rec.started, rec.dict)
+}
+
+/// The part of `backup` synced last-writer-wins: everything except the
+/// history, merged separately, and today's progress, which belongs to this
+/// device and changes every second of practice.
+fn state(backup: Backup) -> Value {
+    let backup = Backup {
+        history: History::default(),
+        daily: DailyProgress::default(),
+        ..backup
+    };
+    serde_json::to_value(backup).unwrap()
+}
+
+pub fn local_state() -> Value {
+    state(Backup::collect(String::new()))
+}
+
+/// The history as append-only entries.
+pub fn local_entries() -> Vec<Entry> {
+    History::load()
+        .sessions
+        .iter()
+        .map(|rec| Entry {
+            key: session_key(rec),
+            data: serde_json::to_value(rec).unwrap(),
+        })
+        .collect()
+}
+
+/// Add the sessions in `entries` that `history` doesn't have yet.
+pub fn merge(history: &mut History, entries: Vec<Entry>) {
+    let mut seen: HashSet<String> = history.sessions.iter().map(session_key).collect();
+    for entry in entries {
+        if let Ok(rec) = serde_json::from_value::<SessionRecord>(entry.data) {
+            if seen.insert(session_key(&rec)) {
+                history.sessions.push(rec);
+            }
+        }
+    }
+    history
+        .sessions
+        .sort_by(|a, b| a.started.total_cmp(&b.started));
+}
+
+/// Store what a sync brought in: a newer state from another device, if
+/// any, and sessions practised elsewhere. Today's progress stays local.
+pub fn apply(state: Option<Value>, entries: Vec<Entry>) {
+    let mut history = History::load();
+    merge(&mut history, entries);
+    match state.and_then(|s| serde_json::from_value::<Backup>(s).ok()) {
+        Some(mut backup) => {
+            backup.drop_invalid();
+            let daily = storage::load(KEY_DAILY);
+            Backup {
+                history,
+                daily,
+                ..backup
+            }
+            .apply()
+        }
+        None => storage::save(KEY_HISTORY, &history),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(started: f64, dict: &str) -> SessionRecord {
+        SessionRecord {
+            started,
+            dict: String::from(dict),
+            words: 10,
+            ..Default::default()
+        }
+    }
+
+    fn entry(rec: &SessionRecord) -> Entry {
+        Entry {
+            key: session_key(rec),
+            data: serde_json::to_value(rec).unwrap(),
+        }
+    }
+
+    #[test]
+    fn state_leaves_out_history_and_todays_progress() {
+        let backup = Backup::new(
+            String::from("2026-10-19"),
+            Default::default(),
+            DailyProgress {
+                date: String::from("2026-10-19"),
+                seconds: 600,
+                words: 42,
+            },
+            History {
+                sessions: vec![session(100.0, "CET4")],
+            },
+        );
+        let synced: Backup = serde_json::from_value(state(backup.clone())).unwrap();
+        assert_eq!(synced.daily, DailyProgress::default());
+        assert!(synced.history.sessions.is_empty());
+        assert_eq!(synced.settings, backup.settings);
+    }
+
+    #[test]
+    fn merges_sessions_from_other_devices() {
+        let mut history = History {
+            sessions: vec![session(100.0, "CET4"), session(300.0, "CET4")],
+        };
+        let entries = vec![
+            entry(&session(200.0, "CET6")),
+            entry(&session(300.0, "CET4")),
+            Entry {
+                key: String::from("broken"),
+                data: Value::from("not a session"),
+            },
+        ];
+        merge(&mut history, entries);
+
+        let started: Vec<f64> = history.sessions.iter().map(|s| s.started).collect();
+        assert_eq!(started, vec![100.0, 200.0, 300.0]);
+        assert_eq!(history.sessions[1].dict, "CET6");
+    }
+}
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter, Result};
+use std::slice::Iter;
+use wasm_bindgen::{prelude::Closure, JsCast};
+
+use crate::common::i18n::t;
+use crate::common::settings::Settings;
+
+const DARK_QUERY: &str = "(prefers-color-scheme: dark)";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Theme {
+    Light,
+    Dark,
+    HighContrast,
+}
+
+impl Theme {
+    pub fn iterator() -> Iter<'static, Theme> {
+        static THEME_S: [Theme; 3] = [Theme::Light, Theme::Dark, Theme::HighContrast];
+        THEME_S.iter()
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::HighContrast => "high-contrast",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Theme> {
+        Theme::iterator().find(|th| th.code() == code).copied()
+    }
+
+    /// Follow the operating system's light or dark preference.
+    pub fn detect() -> Theme {
+        let dark = web_sys::window()
+            .and_then(|w| w.match_media(DARK_QUERY).ok().flatten())
+            .map(|q| q.matches())
+            .unwrap_or(false);
+        if dark {
+            Theme::Dark
+        } else {
+            Theme::Light
+        }
+    }
+
+    /// Switch the CSS variables defined in `scss/theme.scss`.
+    pub fn apply(&self) {
+        let root = web_sys::window()
+            .and_then(|w| w.document())
+            .and_then(|d| d.document_element());
+        if let Some(root) = root {
+            let _ = root.set_attribute("data-theme", self.code());
+        }
+    }
+}
+
+impl Display for Theme {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Theme::Light => write!(f, "{}", t("theme.light")),
+            Theme::Dark => write!(f, "{}", t("theme.dark")),
+            Theme::HighContrast => write!(f, "{}", t("theme.high_contrast")),
+        }
+    }
+}
+
+/// Apply the configured theme and keep following the system preference
+/// for as long as no theme is chosen explicitly.
+pub fn init() {
+    Settings::load().theme.unwrap_or_else(Theme::detect).apply();
+
+    let query = web_sys::window().and_then(|w| w.match_media(DARK_QUERY).ok().flatten());
+    if let Some(query) = query {
+        let handler = move |_: web_sys::Event| {
+            if Settings::load().theme.is_none() {
+                Theme::detect().apply();
+            }
+        };
+        let cb = Closure::wrap(Box::new(handler) as Box<dyn FnMut(_)>);
+        let _ = query.add_event_listener_with_callback("change", cb.as_ref().unchecked_ref());
+        cb.forget();
+    }
+}
@@ -0,0 +1,22 @@
+//! Labels for the typing rules, which live in `keypress-engine` so the
+//! terminal frontend applies exactly the same ones.
+
+pub use keypress_engine::typing::{Attempt, CasePolicy, Policy, Rules, Separators};
+
+use crate::common::i18n::t;
+
+pub fn policy_name(policy: Policy) -> &'static str {
+    match policy {
+        Policy::Strict => t("policy.strict"),
+        Policy::Optional => t("policy.optional"),
+        Policy::AutoSkip => t("policy.auto_skip"),
+    }
+}
+
+pub fn case_name(case: CasePolicy) -> &'static str {
+    match case {
+        CasePolicy::Exact => t("case.exact"),
+        CasePolicy::Insensitive => t("case.insensitive"),
+        CasePolicy::AutoCapitalise => t("case.auto_capitalise"),
+    }
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+use crate::common::dict::Word;
+use crate::common::storage::{self, KEY_USER_DICTS};
+
+/// A word list imported by the user, practised like the built-in
+/// dictionaries.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct UserDict {
+    pub name: String,
+    pub words: Vec<Word>,
+}
+
+impl UserDict {
+    /// Normalise the dictionary as an Anki import would: words need a name
+    /// and a translation to show, and the dictionary needs a name and words.
+    pub fn check(self) -> Option<UserDict> {
+        let name = self.name.trim().to_string();
+        let words: Vec<Word> = self
+            .words
+            .into_iter()
+            .filter(|w| !w.name.trim().is_empty())
+            .map(|mut w| {
+                if w.trans.is_empty() {
+                    w.trans.push(String::new());
+                }
+                w
+            })
+            .collect();
+        if name.is_empty() || words.is_empty() {
+            return None;
+        }
+        Some(UserDict { name, words })
+    }
+}
+
+pub fn imported() -> Vec<UserDict> {
+    storage::load(KEY_USER_DICTS)
+}
+
+/// Add `dict`, replacing an imported dictionary of the same name.
+pub fn import(dict: UserDict) {
+    let mut dicts = imported();
+    dicts.retain(|d| d.name != dict.name);
+    dicts.push(dict);
+    storage::save(KEY_USER_DICTS, &dicts);
+}
+
+pub fn remove(name: &str) {
+    let mut dicts = imported();
+    dicts.retain(|d| d.name != name);
+    storage::save(KEY_USER_DICTS, &dicts);
+}
+
+pub fn names() -> Vec<String> {
+    imported().into_iter().map(|d| d.name).collect()
+}
+
+pub fn find(name: &str) -> Option<UserDict> {
+    imported().into_iter().find(|d| d.name == name)
+}
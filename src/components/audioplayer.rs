@@ -1,12 +1,18 @@
 use std::{
+    cell::{Cell, RefCell},
     fmt::{Display, Formatter, Result},
+    rc::Rc,
     slice::Iter,
 };
 use wasm_bindgen::{prelude::Closure, JsCast};
-use web_sys::{
-    AudioBuffer, AudioContext, AudioDestinationNode, HtmlAudioElement,
-};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{AudioBuffer, AudioContext, Blob, GainNode, HtmlAudioElement, Url};
+use yew::Callback;
 
+use crate::common::audio_cache;
+use crate::common::i18n::t;
+use crate::common::settings::AudioSettings;
+use crate::common::sound_pack::{self, SoundPack, Variation, VARIANTS};
 
 const SOUND_CLICK: &[u8] = include_bytes!("../content/sound/click.wav");
 const SOUND_CORRECT: &[u8] = include_bytes!("../content/sound/correct.mp3");
@@ -14,7 +20,7 @@ const SOUND_WRONG: &[u8] = include_bytes!("../content/sound/wrong.mp3");
 
 macro_rules! audio_url{
     ($($arg:tt)*) => {
-        format!("http://dict.youdao.com/dictvoice?type={prounc}&audio={word}", $($arg)*)
+        format!("https://dict.youdao.com/dictvoice?type={prounc}&audio={word}", $($arg)*)
     };
 }
 
@@ -34,8 +40,8 @@ impl Pronunc {
 impl Display for Pronunc {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         match self {
-            Pronunc::AmE => write!(f, "Amercan pronunciations"),
-            Pronunc::BrE => write!(f, "British pronunciations"),
+            Pronunc::AmE => write!(f, "{}", t("pronunc.ame")),
+            Pronunc::BrE => write!(f, "{}", t("pronunc.bre")),
         }
     }
 }
@@ -49,16 +55,98 @@ impl From<u8> for Pronunc {
     }
 }
 
+#[derive(Clone)]
 pub struct AudioPlayer {
     prounc: Pronunc, // Switch Amercan English and British English
     audio_ctx: AudioContext,
+    gain: GainNode, // master volume, in front of the destination
+    clicks: Rc<RefCell<Vec<AudioBuffer>>>, // decoded once per sound pack
+    settings: AudioSettings,
+}
+
+async fn decode(ctx: &AudioContext, data: js_sys::ArrayBuffer) -> Option<AudioBuffer> {
+    let decoded = JsFuture::from(ctx.decode_audio_data(&data).ok()?).await;
+    decoded.ok()?.dyn_into().ok()
+}
+
+/// The samples of `pack`, falling back to the classic click for a custom
+/// pack that is missing, e.g. after syncing from another device.
+async fn click_buffers(ctx: &AudioContext, pack: &SoundPack) -> Vec<AudioBuffer> {
+    let mut buffers = Vec::new();
+    if let SoundPack::Custom(name) = pack {
+        for data in sound_pack::load(name).await {
+            buffers.extend(decode(ctx, data).await);
+        }
+    }
+    for variant in 0..VARIANTS {
+        let samples = sound_pack::synthesize(pack, variant, ctx.sample_rate());
+        if samples.is_empty() {
+            break;
+        }
+        if let Ok(buffer) = ctx.create_buffer(1, samples.len() as u32, ctx.sample_rate()) {
+            if buffer.copy_to_channel(&samples, 0).is_ok() {
+                buffers.push(buffer);
+            }
+        }
+    }
+    if buffers.is_empty() {
+        let data = js_sys::Uint8Array::from(SOUND_CLICK).buffer();
+        buffers.extend(decode(ctx, data).await);
+    }
+    buffers
+}
+
+/// Play `audio` `settings.repeat` times, pausing `settings.interval` ms in
+/// between, then call `done`.
+fn play_repeated(audio: HtmlAudioElement, settings: &AudioSettings, done: impl FnOnce() + 'static) {
+    audio.set_playback_rate(settings.rate as f64 / 100.0);
+    let left = Rc::new(Cell::new(settings.repeat.max(1) - 1));
+    let mut done = Some(done);
+    let element = audio.clone();
+    let on_ended = Closure::wrap(Box::new(move || {
+        if left.get() == 0 {
+            element.set_onended(None);
+            if let Some(done) = done.take() {
+                done();
+            }
+            return;
+        }
+        left.set(left.get() - 1);
+        let again = element.clone();
+        let replay = Closure::once_into_js(move || {
+            let _ = again.play();
+        });
+        let _ = web_sys::window()
+            .unwrap()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(
+                replay.unchecked_ref(),
+                settings.interval as i32,
+            );
+    }) as Box<dyn FnMut()>);
+    audio.set_onended(Some(on_ended.as_ref().unchecked_ref()));
+    on_ended.forget();
+    let _ = audio.play().unwrap();
 }
 
 impl AudioPlayer {
     fn play_audio_from_url(&self, url: &str) {
         // https://docs.rs/web-sys/0.3.19/web_sys/struct.HtmlAudioElement.html
         let audio = HtmlAudioElement::new_with_src(url).unwrap();
-        let _ = audio.play().unwrap();
+        // Cross-origin audio routed through the context would be silent, so
+        // the element gets the master volume itself
+        audio.set_volume(self.settings.volume as f64 / 100.0);
+        play_repeated(audio, &self.settings, || ());
+    }
+
+    fn play_audio_from_blob(&self, blob: &Blob) {
+        let url = Url::create_object_url_with_blob(blob).unwrap();
+        let audio = HtmlAudioElement::new_with_src(&url).unwrap();
+        if let Ok(source) = self.audio_ctx.create_media_element_source(&audio) {
+            let _ = source.connect_with_audio_node(&self.gain);
+        }
+        play_repeated(audio, &self.settings, move || {
+            let _ = Url::revoke_object_url(&url);
+        });
     }
 
     fn play_audio_from_array(&self, array: &'static [u8]) {
@@ -67,14 +155,14 @@ impl AudioPlayer {
 
         // https://docs.rs/web-sys/0.3.19/web_sys/struct.AudioContext.html
 
-        let destination: AudioDestinationNode = self.audio_ctx.destination();
+        let destination = self.gain.clone();
         let song = self.audio_ctx.create_buffer_source().unwrap();
 
         let handler = move |buf: AudioBuffer| {
             let buffer: Option<&AudioBuffer> = Some(&buf);
 
             song.set_buffer(buffer);
-            song.connect_with_audio_node(destination.as_ref()).unwrap();
+            song.connect_with_audio_node(&destination).unwrap();
             song.start().unwrap();
         };
 
@@ -94,12 +182,43 @@ impl AudioPlayer {
 }
 
 impl AudioPlayer {
-    pub fn new(prounc: Pronunc) -> Self {
-        let audio_ctx = {
-            let audio_ctx = AudioContext::new().unwrap();
-            audio_ctx
+    pub fn new(prounc: Pronunc, settings: AudioSettings) -> Self {
+        let audio_ctx = AudioContext::new().unwrap();
+        let gain = audio_ctx.create_gain().unwrap();
+        gain.connect_with_audio_node(&audio_ctx.destination())
+            .unwrap();
+        let mut player = AudioPlayer {
+            prounc,
+            audio_ctx,
+            gain,
+            clicks: Rc::default(),
+            settings: AudioSettings::default(),
         };
-        AudioPlayer { prounc, audio_ctx }
+        player.load_clicks(&settings.pack);
+        player.set_settings(settings);
+        player
+    }
+
+    fn load_clicks(&self, pack: &SoundPack) {
+        let ctx = self.audio_ctx.clone();
+        let clicks = self.clicks.clone();
+        let pack = pack.clone();
+        spawn_local(async move {
+            *clicks.borrow_mut() = click_buffers(&ctx, &pack).await;
+        });
+    }
+
+    /// Decode the sound pack again, e.g. after its samples were replaced.
+    pub fn reload_clicks(&self) {
+        self.load_clicks(&self.settings.pack);
+    }
+
+    pub fn set_settings(&mut self, settings: AudioSettings) {
+        if settings.pack != self.settings.pack {
+            self.load_clicks(&settings.pack);
+        }
+        self.gain.gain().set_value(settings.volume as f32 / 100.0);
+        self.settings = settings;
     }
 
     pub fn prounc(&self) -> &Pronunc {
@@ -111,19 +230,62 @@ impl AudioPlayer {
     }
 
     pub fn play_correct(&self) {
-        self.play_audio_from_array(SOUND_CORRECT);
+        if self.settings.feedback {
+            self.play_audio_from_array(SOUND_CORRECT);
+        }
     }
 
     pub fn play_wrong(&self) {
-        self.play_audio_from_array(SOUND_WRONG);
+        if self.settings.feedback {
+            self.play_audio_from_array(SOUND_WRONG);
+        }
+    }
+
+    fn word_url(&self, word: &str) -> String {
+        audio_url!(prounc = self.prounc as u8, word = word)
     }
 
     pub fn play_word(&self, word: &str) {
-        let word_url = audio_url!(prounc = self.prounc as u8, word = word);
-        self.play_audio_from_url(&word_url);
+        if !self.settings.pronunciation {
+            return;
+        }
+        let word_url = self.word_url(word);
+        let player = self.clone();
+        spawn_local(async move {
+            match audio_cache::fetch(&word_url).await {
+                Ok(blob) => player.play_audio_from_blob(&blob),
+                // Without Cache Storage or CORS just stream it as before
+                Err(_) => player.play_audio_from_url(&word_url),
+            }
+        });
     }
 
-    pub fn play_click(&self) {
-        self.play_audio_from_array(SOUND_CLICK);
+    /// Download the pronunciations of `words` into the cache one by one,
+    /// telling `on_each` whether each succeeded.
+    pub fn prefetch(&self, words: &[String], on_each: Callback<bool>) {
+        let urls: Vec<String> = words.iter().map(|w| self.word_url(w)).collect();
+        spawn_local(async move {
+            for url in urls {
+                on_each.emit(audio_cache::fetch(&url).await.is_ok());
+            }
+        });
+    }
+
+    /// Play the sound pack's sample for `key`, already decoded.
+    pub fn play_click(&self, key: &str) {
+        let clicks = self.clicks.borrow();
+        if !self.settings.clicks || clicks.is_empty() {
+            return;
+        }
+        let variation = self.settings.variation;
+        let index = sound_pack::pick(variation, key, clicks.len(), js_sys::Math::random());
+        let source = self.audio_ctx.create_buffer_source().unwrap();
+        source.set_buffer(Some(&clicks[index]));
+        if variation == Variation::Random {
+            let detune = 0.96 + 0.08 * js_sys::Math::random() as f32;
+            source.playback_rate().set_value(detune);
+        }
+        source.connect_with_audio_node(&self.gain).unwrap();
+        source.start().unwrap();
     }
 }
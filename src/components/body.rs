@@ -1,19 +1,168 @@
 use std::time::Duration;
+use yew::agent::Bridged;
 use yew::services::interval::{IntervalService, IntervalTask};
-use yew::services::ConsoleService;
-use yew::{html, Callback, Component, ComponentLink, Html, ShouldRender};
+use yew::{html, Bridge, Component, ComponentLink, Html, ShouldRender};
 
+use crate::common::clock::{self, format_duration};
+use crate::common::event_bus::{EventBus, Request};
+use crate::common::history::DailyProgress;
+use crate::common::i18n::{t, tf};
 use crate::common::msg::Msg;
+use crate::common::settings::{DailyGoal, Settings};
+use crate::common::storage::{self, KEY_DAILY};
 
 pub struct Body {
-    time: String,
-    _standalone: (IntervalTask, IntervalTask),
+    link: ComponentLink<Self>,
+    bus: Box<dyn Bridge<EventBus>>,
+    settings: Settings,
+    daily: DailyProgress,
+    running: bool,
+    elapsed: u64,
+    on_break: bool,
+    phase_left: u64, // seconds left in the current pomodoro phase
+    notice: Option<String>,
+    _clock: IntervalTask,
 }
 
 impl Body {
-    fn get_current_time() -> String {
-        let date = js_sys::Date::new_0();
-        String::from("Time: ") + &String::from(date.to_locale_time_string("en-US"))
+    fn load_daily() -> DailyProgress {
+        let daily: DailyProgress = storage::load(KEY_DAILY);
+        let today = clock::today();
+        if daily.date == today {
+            daily
+        } else {
+            DailyProgress {
+                date: today,
+                ..Default::default()
+            }
+        }
+    }
+
+    fn start_work(&mut self) {
+        self.on_break = false;
+        self.phase_left = self.settings.pomodoro_work as u64 * 60;
+    }
+
+    fn tick(&mut self) {
+        if self.daily.date != clock::today() {
+            self.daily = Body::load_daily();
+        }
+
+        if self.running {
+            self.elapsed += 1;
+            self.daily.seconds += 1;
+            storage::save(KEY_DAILY, &self.daily);
+        }
+
+        if !self.settings.pomodoro || !(self.running || self.on_break) {
+            return;
+        }
+
+        self.phase_left = self.phase_left.saturating_sub(1);
+        if self.phase_left > 0 {
+            return;
+        }
+
+        if self.on_break {
+            self.start_work();
+            self.notice = Some(String::from(t("body.break_over")));
+        } else {
+            self.on_break = true;
+            self.phase_left = self.settings.pomodoro_break as u64 * 60;
+            self.notice = Some(tf("body.break_start", &[&self.settings.pomodoro_break]));
+            self.bus.send(Request::PauseSession);
+        }
+    }
+
+    fn goal_progress(&self) -> (u64, u64, &'static str) {
+        match self.settings.daily_goal {
+            DailyGoal::Off => (0, 0, ""),
+            DailyGoal::Minutes(n) => (self.daily.seconds / 60, n as u64, t("body.unit_min")),
+            DailyGoal::Words(n) => (self.daily.words as u64, n as u64, t("body.unit_words")),
+        }
+    }
+
+    fn view_pomodoro(&self) -> Html {
+        let phase = if !self.settings.pomodoro {
+            String::new()
+        } else if self.on_break {
+            tf("body.break", &[&format_duration(self.phase_left)])
+        } else {
+            tf("body.focus", &[&format_duration(self.phase_left)])
+        };
+
+        html! {
+            <div class="form-check form-check-inline">
+                <input class="form-check-input" type="checkbox" id="pomodoro"
+                    checked=self.settings.pomodoro
+                    onclick=self.link.callback(|_| Msg::TogglePomodoro)/>
+                <label class="form-check-label" for="pomodoro">{ t("body.pomodoro") }</label>
+                <span class="timer-phase">{ phase }</span>
+            </div>
+        }
+    }
+
+    fn view_goal(&self) -> Html {
+        let cur_goal = self.settings.daily_goal;
+        let (done, target, unit) = self.goal_progress();
+        let percent = (done * 100).checked_div(target).unwrap_or(0).min(100);
+
+        html! {
+            <div class="row">
+                <div class="col-5">
+                    <select class="form-control form-control-sm"
+                        onchange=self.link.callback(|v: html::ChangeData| match v {
+                            html::ChangeData::Select(ele) => Msg::SelectGoal(ele.value()),
+                            _ => Msg::SelectGoal(DailyGoal::Off.key()),
+                        })>
+                        {
+                            for DailyGoal::presets().iter().map(|g| html! {
+                                <option value=g.key() selected=*g == cur_goal>{ g }</option>
+                            })
+                        }
+                    </select>
+                </div>
+                <div class="col-7">
+                    {
+                        if target > 0 {
+                            html! {
+                                <div class="progress" id="goal">
+                                    <div class="progress-bar" role="progressbar"
+                                        style=format!("width: {}%", percent)>
+                                        { format!("{}/{} {}", done.min(target), target, unit) }
+                                    </div>
+                                </div>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
+                </div>
+            </div>
+        }
+    }
+
+    fn view_notice(&self) -> Html {
+        match &self.notice {
+            Some(notice) => html! {
+                <div class="row justify-content-center" id="timer-notice">
+                    <p>{ notice }</p>
+                    {
+                        if self.on_break {
+                            html! {
+                                <button type="button" class="btn btn-outline-info btn-sm"
+                                    onclick=self.link.callback(|_| Msg::SkipBreak)>
+                                    { t("body.skip_break") }
+                                </button>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
+                </div>
+            },
+            None => html! {},
+        }
     }
 }
 
@@ -22,33 +171,67 @@ impl Component for Body {
     type Properties = ();
 
     fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
-        let standalone_handle = IntervalService::spawn(
-            Duration::from_secs(60),
-            // This callback doesn't send any message to a scope
-            Callback::from(|_| {
-                ConsoleService::debug("Standalone timer callback.");
-            }),
-        );
-
         let clock_handle = IntervalService::spawn(
             Duration::from_secs(1),
             // Timer callback
             link.callback(|_| Msg::UpdateTime),
         );
 
+        let bus = EventBus::bridge(link.batch_callback(|req| match req {
+            Request::SessionStarted => Some(Msg::SessionStarted),
+            Request::SessionPaused => Some(Msg::SessionPaused),
+            Request::WordTyped => Some(Msg::WordTyped),
+            Request::Synced => Some(Msg::Reload),
+            _ => None,
+        }));
+
+        let settings = Settings::load();
+        let phase_left = settings.pomodoro_work as u64 * 60;
+
         Self {
-            time: Body::get_current_time(),
-            _standalone: (standalone_handle, clock_handle),
+            link,
+            bus,
+            settings,
+            daily: Body::load_daily(),
+            running: false,
+            elapsed: 0,
+            on_break: false,
+            phase_left,
+            notice: None,
+            _clock: clock_handle,
         }
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
-            Msg::UpdateTime => {
-                self.time = Body::get_current_time();
-                true
+            Msg::UpdateTime => self.tick(),
+            Msg::SessionStarted => {
+                self.running = true;
+                if self.on_break {
+                    self.start_work();
+                }
+                self.notice = None;
+            }
+            Msg::SessionPaused => self.running = false,
+            Msg::WordTyped => {
+                self.daily.words += 1;
+                storage::save(KEY_DAILY, &self.daily);
+            }
+            Msg::TogglePomodoro => {
+                self.settings = Settings::modify(|s| s.pomodoro = !s.pomodoro);
+                self.start_work();
+                self.notice = None;
+            }
+            Msg::SelectGoal(key) => {
+                self.settings = Settings::modify(|s| s.daily_goal = DailyGoal::from_key(&key));
+            }
+            Msg::Reload => self.settings = Settings::load(),
+            Msg::SkipBreak => {
+                self.start_work();
+                self.notice = None;
             }
         }
+        true
     }
 
     fn change(&mut self, _props: Self::Properties) -> ShouldRender {
@@ -58,13 +241,19 @@ impl Component for Body {
     fn view(&self) -> Html {
         html! {
             <>
-                <div class="container-fluid">
+                <div class="container-fluid" id="timer">
                     <div class="row justify-content-end">
-                        <div class="col-10"></div>
                         <div class="col-2">
-                            <p> <font color="black"> { &self.time } </font></p>
+                            <p>{ tf("body.practice", &[&format_duration(self.elapsed)]) }</p>
+                        </div>
+                        <div class="col-3">
+                            { self.view_pomodoro() }
+                        </div>
+                        <div class="col-5">
+                            { self.view_goal() }
                         </div>
                     </div>
+                    { self.view_notice() }
                 </div>
             </>
         }
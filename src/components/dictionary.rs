@@ -0,0 +1,375 @@
+use web_sys::Element;
+use yew::{html, Component, ComponentLink, Html, NodeRef, Properties, ShouldRender};
+
+use crate::common::anki;
+use crate::common::dict::{self, Mode, Word, CHAPTER_SIZE};
+use crate::common::file;
+use crate::common::i18n::{t, tf};
+use crate::common::mastery::{Mastery, WordState};
+use crate::common::msg::DictMsg;
+use crate::common::route::Route;
+
+// Rows are a fixed height so only the visible slice of large
+// dictionaries like TOEFL has to be rendered.
+const ROW_HEIGHT: i32 = 36;
+const VIEW_ROWS: i32 = 15;
+const OVERSCAN: i32 = 5;
+
+const LENGTH_FILTERS: [(&str, &str, usize, usize); 5] = [
+    ("any", "", 0, usize::MAX),
+    ("1-4", "1-4", 1, 4),
+    ("5-8", "5-8", 5, 8),
+    ("9-12", "9-12", 9, 12),
+    ("13-", "13+", 13, usize::MAX),
+];
+
+const STATE_FILTERS: [(&str, &str, Option<WordState>); 4] = [
+    ("any", "dict.any_state", None),
+    ("new", "dict.state_new", Some(WordState::New)),
+    ("learning", "dict.state_learning", Some(WordState::Learning)),
+    ("mastered", "dict.state_mastered", Some(WordState::Mastered)),
+];
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct DictionaryProps {
+    #[prop_or_default]
+    pub dict: Option<String>,
+}
+
+pub struct Dictionary {
+    props: DictionaryProps,
+    cur_level: String,
+    words: Vec<Word>,
+    query: String,
+    length: usize, // index into LENGTH_FILTERS
+    state: usize,  // index into STATE_FILTERS
+    mastery: Mastery,
+    filtered: Vec<usize>,
+    export: String, // "all", "mistakes" or a chapter number
+    export_status: Option<String>,
+    scroll_top: i32,
+    scroller: NodeRef,
+    link: ComponentLink<Self>,
+}
+
+impl Dictionary {
+    fn select(&mut self) {
+        let levels = Mode::Words.levels();
+        self.cur_level = self
+            .props
+            .dict
+            .clone()
+            .filter(|d| levels.contains(d))
+            .unwrap_or_else(|| levels[0].clone());
+        self.words = dict::word_list(&self.cur_level);
+        self.mastery = Mastery::load();
+        self.export = String::from("all");
+        self.export_status = None;
+        self.filter();
+    }
+
+    /// The words to export and the file name for them.
+    fn export_words(&self) -> (Vec<Word>, String) {
+        let level = self.cur_level.replace(' ', "-");
+        match self.export.as_str() {
+            "mistakes" => {
+                let words = self
+                    .words
+                    .iter()
+                    .filter(|w| self.mastery.mistaken(&self.cur_level, &w.name))
+                    .cloned()
+                    .collect();
+                (words, format!("keypress-{}-mistakes.txt", level))
+            }
+            chapter => match chapter.parse::<usize>() {
+                Ok(chapter) => {
+                    let first = ((chapter.max(1) - 1) * CHAPTER_SIZE).min(self.words.len());
+                    let last = (first + CHAPTER_SIZE).min(self.words.len());
+                    let name = format!("keypress-{}-chapter-{}.txt", level, chapter);
+                    (self.words[first..last].to_vec(), name)
+                }
+                Err(_) => (self.words.clone(), format!("keypress-{}.txt", level)),
+            },
+        }
+    }
+
+    fn filter(&mut self) {
+        let (_, _, min, max) = LENGTH_FILTERS[self.length];
+        let (_, _, state) = STATE_FILTERS[self.state];
+        self.filtered = self
+            .words
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| (min..=max).contains(&w.name.chars().count()))
+            .filter(|(_, w)| {
+                state.is_none_or(|state| self.mastery.state(&self.cur_level, &w.name) == state)
+            })
+            .filter(|(_, w)| w.matches(&self.query))
+            .map(|(i, _)| i)
+            .collect();
+
+        self.scroll_top = 0;
+        if let Some(scroller) = self.scroller.cast::<Element>() {
+            scroller.set_scroll_top(0);
+        }
+    }
+
+    fn view_levels(&self) -> Html {
+        html! {
+            <div class="row justify-content-center" id="dict-levels">
+                {
+                    for Mode::Words.levels().into_iter().map(|level| {
+                        let route = Route::Dictionary { dict: Some(level.clone()) };
+                        let class = if *level == self.cur_level {
+                            "btn btn-info btn-sm"
+                        } else {
+                            "btn btn-outline-info btn-sm"
+                        };
+                        html! { <a class=class href=route.to_hash()>{ level }</a> }
+                    })
+                }
+            </div>
+        }
+    }
+
+    fn view_filters(&self) -> Html {
+        html! {
+            <div class="row justify-content-center" id="dict-filters">
+                <div class="col-4">
+                    <input type="search" class="form-control form-control-sm"
+                        placeholder=t("dict.search")
+                        value=self.query.clone()
+                        oninput=self.link.callback(|e: html::InputData| DictMsg::Search(e.value))/>
+                </div>
+                <div class="col-2">
+                    <select class="form-control form-control-sm"
+                        onchange=self.link.callback(|v: html::ChangeData| match v {
+                            html::ChangeData::Select(ele) => DictMsg::FilterLength(ele.value()),
+                            _ => DictMsg::FilterLength(String::from("any")),
+                        })>
+                        {
+                            for LENGTH_FILTERS.iter().enumerate().map(|(i, (key, range, _, _))| {
+                                let label = if range.is_empty() {
+                                    String::from(t("dict.any_length"))
+                                } else {
+                                    tf("dict.letters", &[range])
+                                };
+                                html! { <option value=*key selected=i == self.length>{ label }</option> }
+                            })
+                        }
+                    </select>
+                </div>
+                <div class="col-2">
+                    <select class="form-control form-control-sm"
+                        onchange=self.link.callback(|v: html::ChangeData| match v {
+                            html::ChangeData::Select(ele) => DictMsg::FilterState(ele.value()),
+                            _ => DictMsg::FilterState(String::from("any")),
+                        })>
+                        {
+                            for STATE_FILTERS.iter().enumerate().map(|(i, (key, label, _))| {
+                                html! { <option value=*key selected=i == self.state>{ t(label) }</option> }
+                            })
+                        }
+                    </select>
+                </div>
+                <div class="col-2">
+                    <p>{ tf("dict.count", &[&self.filtered.len(), &self.words.len()]) }</p>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_row(&self, index: usize) -> Html {
+        let word = &self.words[index];
+        let route = Route::Practice {
+            dict: Some(self.cur_level.clone()),
+            chapter: None,
+            word: Some(index),
+        };
+
+        html! {
+            <div class="dict-row">
+                <span class="dict-index">{ index + 1 }</span>
+                <span class="dict-name">{ &word.name }</span>
+                <span class="dict-phone">{ &word.usphone }</span>
+                <span class="dict-trans" title=word.trans.join("; ")>{ word.trans.join("; ") }</span>
+                <a class="btn btn-outline-info btn-sm" href=route.to_hash()>{ t("dict.practise_here") }</a>
+            </div>
+        }
+    }
+
+    fn view_words(&self) -> Html {
+        let first = (self.scroll_top / ROW_HEIGHT - OVERSCAN).max(0) as usize;
+        let last = (first + (VIEW_ROWS + 2 * OVERSCAN) as usize).min(self.filtered.len());
+        let height = self.filtered.len() as i32 * ROW_HEIGHT;
+
+        html! {
+            <div id="dict-words" ref=self.scroller.clone()
+                style=format!("height: {}px", VIEW_ROWS * ROW_HEIGHT)
+                onscroll=self.link.callback(|_| DictMsg::Scroll)>
+                <div style=format!("height: {}px", height)>
+                    <div style=format!("transform: translateY({}px)", first as i32 * ROW_HEIGHT)>
+                        { for self.filtered[first..last].iter().map(|i| self.view_row(*i)) }
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_export(&self) -> Html {
+        let nr_chapter = dict::nr_chapter(self.words.len());
+
+        html! {
+            <div class="row justify-content-center" id="dict-export">
+                <div class="col-3">
+                    <select class="form-control form-control-sm"
+                        onchange=self.link.callback(|v: html::ChangeData| match v {
+                            html::ChangeData::Select(ele) => DictMsg::SetExport(ele.value()),
+                            _ => DictMsg::SetExport(String::from("all")),
+                        })>
+                        <option value="all" selected=self.export == "all">{ t("anki.whole") }</option>
+                        <option value="mistakes" selected=self.export == "mistakes">{ t("anki.mistakes") }</option>
+                        {
+                            for (1..=nr_chapter).map(|chapter| {
+                                let value = chapter.to_string();
+                                html! {
+                                    <option value=value.clone() selected=self.export == value>
+                                        { tf("keyboard.chapter", &[&chapter]) }
+                                    </option>
+                                }
+                            })
+                        }
+                    </select>
+                </div>
+                <div class="col-2">
+                    <button class="btn btn-outline-info btn-sm" onclick=self.link.callback(|_| DictMsg::Export)>
+                        { t("anki.export") }
+                    </button>
+                </div>
+                <div class="col-3">
+                    <p>{ self.export_status.clone().unwrap_or_default() }</p>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_chapters(&self) -> Html {
+        html! {
+            <div id="dict-chapters">
+                <p>{ tf("dict.chapters", &[&dict::nr_chapter(self.words.len())]) }</p>
+                {
+                    for (1..(dict::nr_chapter(self.words.len()) + 1)).map(|chapter| {
+                        let route = Route::Practice {
+                            dict: Some(self.cur_level.clone()),
+                            chapter: Some(chapter),
+                            word: None,
+                        };
+                        html! {
+                            <a class="btn btn-outline-secondary btn-sm" href=route.to_hash()>
+                                { chapter }
+                            </a>
+                        }
+                    })
+                }
+            </div>
+        }
+    }
+}
+
+impl Component for Dictionary {
+    type Message = DictMsg;
+    type Properties = DictionaryProps;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut dictionary = Self {
+            props,
+            cur_level: String::new(),
+            words: Vec::new(),
+            query: String::new(),
+            length: 0,
+            state: 0,
+            mastery: Mastery::default(),
+            filtered: Vec::new(),
+            export: String::from("all"),
+            export_status: None,
+            scroll_top: 0,
+            scroller: NodeRef::default(),
+            link,
+        };
+        dictionary.select();
+        dictionary
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            DictMsg::Search(query) => {
+                self.query = query;
+                self.filter();
+            }
+            DictMsg::FilterLength(key) => {
+                self.length = LENGTH_FILTERS
+                    .iter()
+                    .position(|(k, _, _, _)| *k == key)
+                    .unwrap_or(0);
+                self.filter();
+            }
+            DictMsg::FilterState(key) => {
+                self.state = STATE_FILTERS
+                    .iter()
+                    .position(|(k, _, _)| *k == key)
+                    .unwrap_or(0);
+                self.filter();
+            }
+            DictMsg::SetExport(export) => {
+                self.export = export;
+                self.export_status = None;
+            }
+            DictMsg::Export => {
+                let (words, name) = self.export_words();
+                if words.is_empty() {
+                    self.export_status = Some(String::from(t("anki.nothing")));
+                    return true;
+                }
+                let tag = format!("keypress::{}", self.cur_level);
+                file::download(&name, anki::MIME, &anki::export(&words, &tag));
+                self.export_status = Some(tf("anki.exported", &[&words.len()]));
+            }
+            DictMsg::Scroll => {
+                let scroll_top = match self.scroller.cast::<Element>() {
+                    Some(scroller) => scroller.scroll_top(),
+                    None => return false,
+                };
+                // Only re-render once a whole row has scrolled past
+                if scroll_top / ROW_HEIGHT == self.scroll_top / ROW_HEIGHT {
+                    self.scroll_top = scroll_top;
+                    return false;
+                }
+                self.scroll_top = scroll_top;
+            }
+        }
+        true
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props == props {
+            return false;
+        }
+        self.props = props;
+        self.select();
+        true
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <>
+                <div class="container-fluid" id="dictionary">
+                    { self.view_levels() }
+                    { self.view_filters() }
+                    { self.view_words() }
+                    { self.view_chapters() }
+                    { self.view_export() }
+                </div>
+            </>
+        }
+    }
+}
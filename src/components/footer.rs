@@ -1,5 +1,7 @@
 use yew::{html, Component, ComponentLink, Html, ShouldRender};
 
+use crate::common::i18n::t;
+
 pub struct Footer;
 
 impl Component for Footer {
@@ -22,12 +24,13 @@ impl Component for Footer {
         html! {
             <>
                 <div id="footer">
-            <font color="#946F7C">{ "Powered by " }</font>
+            <span class="footer-text">{ t("footer.powered_by") }</span>
             <a href="https://yew.rs">{ "Yew" }</a>
-            <font color="#946F7C">{ " using " }</font>
+            <span class="footer-text">{ t("footer.using") }</span>
             <a href="https://www.rust-lang.org">{ "Rust" }</a>
-            <font color="#946F7C">{ " and crates from " } </font>
+            <span class="footer-text">{ t("footer.crates_from") } </span>
             <a href="https://crates.io">{ "Crates" }</a>
+            <span class="footer-text">{ t("footer.end") }</span>
                 </div>
             </>
         }
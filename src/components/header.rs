@@ -1,21 +1,69 @@
-use yew::{html, Component, ComponentLink, Html, ShouldRender};
+use std::mem::discriminant;
+use yew::{html, Callback, Component, ComponentLink, Html, Properties, ShouldRender};
 
-pub struct Header;
+use crate::common::i18n::{t, Locale};
+use crate::common::route::Route;
+use crate::components::profile_switcher::ProfileSwitcher;
+
+#[derive(Properties, Clone)]
+pub struct HeaderProps {
+    pub route: Route,
+    pub locale: Option<Locale>,
+    pub on_locale: Callback<Option<Locale>>,
+}
+
+pub struct Header {
+    props: HeaderProps,
+}
+
+impl Header {
+    fn view_nav(&self, route: Route, label: &str) -> Html {
+        let class = if discriminant(&self.props.route) == discriminant(&route) {
+            "btn btn-info btn-sm"
+        } else {
+            "btn btn-outline-info btn-sm"
+        };
+
+        html! {
+            <a class=class href=route.to_hash()>{ label }</a>
+        }
+    }
+
+    fn view_locale(&self) -> Html {
+        let locale = self.props.locale;
+
+        html! {
+            <select class="form-control form-control-sm" id="locale"
+                onchange=self.props.on_locale.reform(|v: html::ChangeData| match v {
+                    html::ChangeData::Select(ele) => Locale::from_code(&ele.value()),
+                    _ => None,
+                })>
+                <option value="auto" selected=locale.is_none()>{ t("nav.auto_locale") }</option>
+                {
+                    for Locale::iterator().map(|l| html! {
+                        <option value=l.code() selected=locale == Some(*l)>{ l }</option>
+                    })
+                }
+            </select>
+        }
+    }
+}
 
 impl Component for Header {
     type Message = ();
-    type Properties = ();
+    type Properties = HeaderProps;
 
-    fn create(_props: Self::Properties, _link: ComponentLink<Self>) -> Self {
-        Self
+    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+        Self { props }
     }
 
     fn update(&mut self, _msg: Self::Message) -> ShouldRender {
         true
     }
 
-    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
-        false
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        true
     }
 
     fn view(&self) -> Html {
@@ -34,7 +82,19 @@ impl Component for Header {
                                 <div class="col-10"></div>
                             </div>
                         </div>
-                        <div class="col-8"></div>
+                        <div class="col-2">
+                            <ProfileSwitcher/>
+                        </div>
+                        <div class="col-4" id="nav">
+                            { self.view_nav(Route::last_practice(), t("nav.practice")) }
+                            { self.view_nav(Route::Dictionary { dict: None }, t("nav.dictionary")) }
+                            { self.view_nav(Route::Stats, t("nav.stats")) }
+                            { self.view_nav(Route::Profile, t("nav.profile")) }
+                            { self.view_nav(Route::Settings, t("nav.settings")) }
+                        </div>
+                        <div class="col-2">
+                            { self.view_locale() }
+                        </div>
                     </div>
                 </div>
             </>
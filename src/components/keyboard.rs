@@ -1,54 +1,328 @@
-use std::collections::HashMap;
-
+use std::cmp::Ordering;
+use std::time::Duration;
 use yew::agent::Bridged;
+use yew::services::interval::{IntervalService, IntervalTask};
 use yew::services::ConsoleService;
-use yew::{html, Bridge, Component, ComponentLink, Html, ShouldRender};
+use yew::{classes, html, Bridge, Component, ComponentLink, Html, Properties, ShouldRender};
 
-use crate::common::event_bus::EventBus;
+use crate::common::clock;
+use crate::common::dict::{self, Mode, CHAPTER_SIZE, DICT_INDEX};
+use crate::common::event_bus::{EventBus, Request};
+use crate::common::ghost::{Race, Run, Runs};
+use crate::common::history::{History, SessionRecord};
+use crate::common::i18n::{t, tf};
+use crate::common::mastery::{Mastery, Tally, WordState};
 use crate::common::msg::Key;
+use crate::common::rewards::{Rewards, Typed};
+use crate::common::route::Route;
+use crate::common::settings::Settings;
+use crate::common::snippets::{self, Lang};
+use crate::common::typing::{Attempt, Rules};
 use crate::components::audioplayer::{AudioPlayer, Pronunc};
 
-const DICT_PROGRAMMER: &str = include_str!("../content/dicts/it-words.json");
-const DICT_CET4: &str = include_str!("../content/dicts/CET4_T.json");
-const DICT_CET6: &str = include_str!("../content/dicts/CET6_T.json");
-const DICT_TOEFL: &str = include_str!("../content/dicts/TOEFL_T.json");
-
-lazy_static::lazy_static! {
-    static ref DICT_INDEX: Vec<&'static str> = vec![
-        "Programmer",
-        "CET4",
-        "CET6",
-        "TOEFL",
-    ];
-
-    static ref DICT_MAP: HashMap<String, &'static str> =
-    {
-        let mut map = HashMap::new();
-        map.insert(DICT_INDEX[0].to_string(), DICT_PROGRAMMER);
-        map.insert(DICT_INDEX[1].to_string(), DICT_CET4);
-        map.insert(DICT_INDEX[2].to_string(), DICT_CET6);
-        map.insert(DICT_INDEX[3].to_string(), DICT_TOEFL);
-        map
-    };
+/// Id of the Start button, pressed while a session runs.
+pub const START_BUTTON: &str = "start-session";
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct KeyboardProps {
+    #[prop_or_default]
+    pub dict: Option<String>,
+    #[prop_or_default]
+    pub chapter: Option<usize>,
+    #[prop_or_default]
+    pub word: Option<usize>,
+}
+
+/// A finished race and the best run it was compared with.
+struct RaceResult {
+    run: Run,
+    previous: Option<Run>,
+    new_best: bool,
+    first: usize, // index of the chapter's first word
+}
+
+/// Progress of downloading the chapter's pronunciations.
+struct Prefetch {
+    done: usize,
+    saved: usize,
+    total: usize,
 }
 
 pub struct Keyboard {
-    start_status: String,
+    props: KeyboardProps,
+    running: bool,
     start_class: String,
     dict: serde_json::Value,
     nr_word: usize,
     cur_index: usize,
     cur_level: String,
+    mode: Mode,
+    lang: Lang, // for highlighting snippets
+    auto_close: bool,
+    rules: Rules,
+    caps_lock: bool,
+    composing: bool,
     cur_chaper: usize,
-    inputs: String,
-    _producer: Box<dyn Bridge<EventBus>>,
+    attempt: Attempt,
+    last_item: Option<(f64, f64)>, // wpm and accuracy of the last sentence
+    session: Option<SessionRecord>,
+    race_mode: bool,
+    mastery: Mastery,
+    skip_mastered: bool,
+    race: Option<Race>,
+    race_result: Option<RaceResult>,
+    prefetch: Option<Prefetch>,
+    _race_clock: Option<IntervalTask>,
+    bus: Box<dyn Bridge<EventBus>>,
     link: ComponentLink<Self>,
     audio: AudioPlayer,
 }
 
 impl Keyboard {
+    fn current(&self) -> &str {
+        self.dict[self.cur_index]["name"]
+            .as_str()
+            .unwrap_or_default()
+    }
+
+    fn speak(&self) {
+        if self.mode != Mode::Code {
+            self.audio.play_word(self.current());
+        }
+    }
+
+    /// Skip the indentation and, if enabled, closing brackets ahead.
+    fn auto_fill(&mut self) {
+        if self.mode != Mode::Code {
+            return;
+        }
+        let word = self.current();
+        let typed = &self.attempt.typed;
+        let fill = snippets::auto_fill(word, typed, self.auto_close);
+        let filled = word[typed.len()..typed.len() + fill].to_string();
+        self.attempt.typed.push_str(&filled);
+    }
+
+    fn set_level(&mut self, level: String) {
+        // A level with nothing to type would leave no current word
+        let dict = match dict::load(&level).filter(|d| d.as_array().is_some_and(|a| !a.is_empty()))
+        {
+            Some(dict) => dict,
+            None => return,
+        };
+        self.mode = dict::mode(&level);
+        self.lang = snippets::find(&level).map(|p| p.lang).unwrap_or_default();
+        self.cur_level = level;
+        self.nr_word = dict.as_array().unwrap().len();
+        self.dict = dict;
+        self.abort_race();
+        self.race_result = None;
+        self.prefetch = None;
+        self.attempt = Attempt::default();
+        self.last_item = None;
+        self.cur_index = 0;
+        self.cur_chaper = self.cur_index / CHAPTER_SIZE + 1;
+    }
+
+    fn set_chapter(&mut self, chaper: usize) {
+        let chaper = chaper.clamp(1, dict::nr_chapter(self.nr_word).max(1));
+        self.abort_race();
+        self.race_result = None;
+        self.prefetch = None;
+        self.attempt = Attempt::default();
+        self.cur_index = (chaper - 1) * CHAPTER_SIZE;
+        self.cur_chaper = chaper;
+    }
+
+    /// Restore the dictionary and chapter or word named in the URL.
+    fn apply_props(&mut self) {
+        if let Some(level) = self.props.dict.clone() {
+            if level != self.cur_level {
+                self.set_level(level);
+            }
+        }
+        if let Some(chaper) = self.props.chapter {
+            self.set_chapter(chaper);
+        }
+        if let Some(index) = self.props.word.filter(|i| *i < self.nr_word) {
+            self.abort_race();
+            self.attempt = Attempt::default();
+            self.cur_index = index;
+            self.cur_chaper = self.cur_index / CHAPTER_SIZE + 1;
+        }
+    }
+
+    /// Keep the address on the current word, so reloading or sharing it
+    /// comes back here.
+    fn sync_route(&self) {
+        Route::Practice {
+            dict: Some(self.cur_level.clone()),
+            chapter: Some(self.cur_chaper),
+            word: Some(self.cur_index),
+        }
+        .replace();
+    }
+
+    fn start_session(&mut self) {
+        self.session = Some(SessionRecord {
+            date: clock::today(),
+            started: clock::now_ms(),
+            dict: self.cur_level.clone(),
+            chapter: self.cur_chaper,
+            ..Default::default()
+        });
+        self.bus.send(Request::SessionStarted);
+    }
+
+    /// Race the chapter from its first word against its best run.
+    fn start_race(&mut self) {
+        let first = (self.cur_chaper - 1) * CHAPTER_SIZE;
+        let words = CHAPTER_SIZE.min(self.nr_word - first);
+        let ghost = Runs::load().best(&self.cur_level, self.cur_chaper).cloned();
+        self.cur_index = first;
+        self.attempt = Attempt::default();
+        self.race = Some(Race::new(&self.cur_level, self.cur_chaper, words, ghost));
+        self.race_result = None;
+        self._race_clock = Some(IntervalService::spawn(
+            Duration::from_millis(200),
+            self.link.callback(|_| Key::RaceTick),
+        ));
+    }
+
+    fn finish_race(&mut self) {
+        if let Some(race) = self.race.take() {
+            let first = (race.run.chapter - 1) * CHAPTER_SIZE;
+            let mut run = race.run;
+            run.date = clock::today();
+            self.bus.send(Request::RaceFinished { ms: run.total() });
+            let (previous, new_best) = Runs::record(run.clone());
+            self.race_result = Some(RaceResult {
+                run,
+                previous,
+                new_best,
+                first,
+            });
+        }
+        self._race_clock = None;
+    }
+
+    fn abort_race(&mut self) {
+        self.race = None;
+        self._race_clock = None;
+    }
+
+    fn finish_session(&mut self) {
+        self.abort_race();
+        if let Some(mut session) = self.session.take() {
+            session.seconds = ((clock::now_ms() - session.started) / 1000.0) as u64;
+            if session.keystrokes > 0 {
+                History::append(session);
+            }
+        }
+        let days = History::load().days();
+        let today = clock::parse_day(&clock::today()).unwrap_or_default();
+        for reward in Rewards::modify(|r| r.settle(&days, today)) {
+            self.bus.send(Request::Rewarded(reward));
+        }
+        self.bus.send(Request::SessionPaused);
+    }
+
+    /// XP and achievements for a dictionary word typed through.
+    fn reward(&mut self, word: &str) {
+        if self.mode != Mode::Words || !DICT_INDEX.contains(&self.cur_level.as_str()) {
+            return;
+        }
+        let now = clock::now_ms();
+        let typed = Typed {
+            dict: &self.cur_level,
+            nr_word: self.nr_word,
+            index: self.cur_index,
+            word,
+            mistakes: self.attempt.mistakes,
+            started: self.attempt.started.unwrap_or(now),
+            now,
+        };
+        for reward in Rewards::modify(|r| r.word(&typed, &clock::today())) {
+            self.bus.send(Request::Rewarded(reward));
+        }
+    }
+
+    fn state(&self, index: usize) -> WordState {
+        let word = self.dict[index]["name"].as_str().unwrap_or_default();
+        self.mastery.state(&self.cur_level, word)
+    }
+
+    /// The word after the current one, passing over mastered words if asked
+    /// to, unless every word is mastered.
+    fn next_index(&self) -> usize {
+        let skip = self.skip_mastered && self.mode == Mode::Words;
+        (1..=self.nr_word)
+            .map(|step| (self.cur_index + step) % self.nr_word)
+            .find(|i| !skip || self.state(*i) != WordState::Mastered)
+            .unwrap_or((self.cur_index + 1) % self.nr_word)
+    }
+
+    fn tally(&self, first: usize, last: usize) -> Tally {
+        let words = (first..last).filter_map(|i| self.dict[i]["name"].as_str());
+        self.mastery.tally(&self.cur_level, words)
+    }
+
+    fn view_bar(label: String, tally: Tally) -> Html {
+        html! {
+            <div class="mastery-bar" title=tf("keyboard.mastery_tally", &[&tally.new, &tally.learning, &tally.mastered])>
+                <span>{ label }</span>
+                <div class="mastery-track">
+                    <div class="mastery-mastered" style=format!("width: {:.1}%", tally.percent(tally.mastered))></div>
+                    <div class="mastery-learning" style=format!("width: {:.1}%", tally.percent(tally.learning))></div>
+                </div>
+                <small>{ format!("{:.0}%", tally.percent(tally.mastered)) }</small>
+            </div>
+        }
+    }
+
+    fn level_label(&self, level: &str) -> String {
+        match self.mastery.mastered(level) {
+            0 => level.to_string(),
+            count => tf("keyboard.level_mastered", &[&level, &count]),
+        }
+    }
+
+    fn chapter_label(&self, chapter: usize) -> String {
+        let name = tf("keyboard.chapter", &[&chapter]);
+        if self.mode != Mode::Words {
+            return name;
+        }
+        let first = (chapter - 1) * CHAPTER_SIZE;
+        let tally = self.tally(first, (first + CHAPTER_SIZE).min(self.nr_word));
+        match tally.mastered {
+            0 => name,
+            mastered => tf(
+                "keyboard.chapter_mastered",
+                &[&name, &mastered, &tally.total()],
+            ),
+        }
+    }
+
+    /// Mastered and learning words of the dictionary and the chapter.
+    fn view_mastery(&self) -> Html {
+        if self.mode != Mode::Words {
+            return html! {};
+        }
+        let first = (self.cur_chaper - 1) * CHAPTER_SIZE;
+        let last = (first + CHAPTER_SIZE).min(self.nr_word);
+
+        html! {
+            <div class="row justify-content-end" id="mastery">
+                <div class="col-3">{ Self::view_bar(self.cur_level.clone(), self.tally(0, self.nr_word)) }</div>
+                <div class="col-3">{ Self::view_bar(tf("keyboard.chapter", &[&self.cur_chaper]), self.tally(first, last)) }</div>
+                <div class="col-1"></div>
+            </div>
+        }
+    }
+
     fn view_select_button(&self) -> Html {
-        let chapers: Vec<usize> = (1..(self.nr_word / 20 + 1)).collect();
+        let chapers: Vec<usize> = (1..(dict::nr_chapter(self.nr_word) + 1)).collect();
+        let levels = self.mode.levels();
 
         html! {
             <>
@@ -66,7 +340,22 @@ impl Keyboard {
                         {for Pronunc::iterator().map(|o| html!{<option value=(*o as u8).to_string()>{o}</option>} )}
                         </select>
                    </div>
-                   <div class="col-6"></div>
+                   <div class="col-4"></div>
+                   <div class="col-2">
+                   <select class="form-control form-control-sm" id="mode"
+                       onchange=self.link.callback(| v:html::ChangeData | {
+                           match v {
+                               html::ChangeData::Select(ele) => Key::SelectMode(ele.value()),
+                               _ => Key::SelectMode(String::from("words")),
+                           }
+                       } )>
+                       {
+                           for Mode::iterator().map(|m| html! {
+                               <option value=m.code() selected=*m == self.mode>{ m }</option>
+                           })
+                       }
+                   </select>
+                   </div>
                    <div class="col-2">
                    <select class="form-control form-control-sm" id="exampleFormControlSelect2"
                        onchange=self.link.callback(| v:html::ChangeData | {
@@ -79,7 +368,16 @@ impl Keyboard {
                                }
                            }
                        } )>
-                       { for DICT_INDEX.iter().map(|b| html! { <option value=*b>{ b }</option> }) }
+                       {
+                           for levels.iter().map(|b| {
+                               let label = self.level_label(b);
+                               if *b == self.cur_level {
+                                   html! { <option value=b.clone() selected=true>{ label }</option> }
+                               } else {
+                                   html! { <option value=b.clone()>{ label }</option> }
+                               }
+                           })
+                       }
                    </select>
                    </div>
                    <div class="col-2">
@@ -96,18 +394,20 @@ impl Keyboard {
                        } )>
                        {
                            for chapers.iter().map(|b| {
+                               let label = self.chapter_label(*b);
                                if *b == self.cur_chaper {
-                                   html! { <option value=b.to_string() selected=true>{ format!("Charper {}", b) }</option> }
+                                   html! { <option value=b.to_string() selected=true>{ label }</option> }
                                } else {
-                                   html! { <option value=b.to_string()>{ format!("Charper {}", b) }</option> }
+                                   html! { <option value=b.to_string()>{ label }</option> }
                                }
                            })
                        }
                    </select>
                    </div>
                    <div class="col-1">
-                   <button onclick=self.link.callback(|_| Key::Submit) type="button" class=&self.start_class>
-                       { &self.start_status }
+                   <button id=START_BUTTON aria-pressed=self.running.to_string()
+                       onclick=self.link.callback(|_| Key::Submit) type="button" class=&self.start_class>
+                       { if self.running { t("keyboard.pause") } else { t("keyboard.start") } }
                    </button>
                    </div>
                 </div>
@@ -118,24 +418,213 @@ impl Keyboard {
     fn view_word(&self) -> Html {
         let word = self.dict.get(self.cur_index).unwrap();
         let word_name: &str = word["name"].as_str().unwrap();
-        let word_trans: &str = word["trans"][0].as_str().unwrap();
-        let name_byte = word_name.as_bytes();
-        let inputs_byte = self.inputs.as_bytes();
-        let name_byte_last = &name_byte[inputs_byte.len()..name_byte.len()];
+        let word_trans: &str = word["trans"][0].as_str().unwrap_or_default();
+        let typed = self.attempt.typed.chars().count();
+
+        if self.mode == Mode::Code {
+            return self.view_code(word_name, word_trans, typed);
+        }
+
+        let mut rest = word_name.chars().skip(typed);
+        let next = if self.mode == Mode::Sentences {
+            rest.next()
+        } else {
+            None
+        };
 
         html! {
             <>
-                <div id="word">
-                   { for inputs_byte.iter().map(|b| html! { <font color="#059669">{ *b as char }</font> }) }
-                   { for name_byte_last.iter().map(|b| html! { <font color="#4B5563">{ *b as char }</font> }) }
+                <div id="word" class=if self.mode == Mode::Sentences { "sentence" } else { "" }>
+                   { for self.attempt.typed.chars().map(|c| self.view_char(c, "typed")) }
+                   { for next.map(|c| self.view_char(c, "untyped cursor")) }
+                   { for rest.map(|c| self.view_char(c, "untyped")) }
                 </div>
                 <div id="trans">
                    <p> { &word_trans } </p>
+                   { self.view_result() }
                 </div>
             </>
         }
     }
 
+    /// Spaces inside phrases are drawn as a visible gap marker.
+    fn view_char(&self, c: char, class: &'static str) -> Html {
+        if c == ' ' && self.mode == Mode::Words {
+            html! { <span class=classes!(class, "space")>{ '\u{2423}' }</span> }
+        } else {
+            html! { <span class=class>{ c }</span> }
+        }
+    }
+
+    fn view_code(&self, code: &str, title: &str, typed: usize) -> Html {
+        let tokens = snippets::highlight(self.lang, code);
+
+        html! {
+            <>
+                <div id="trans">
+                   <p> { title } </p>
+                </div>
+                <pre id="code">
+                    {
+                        for code.chars().zip(tokens.iter()).enumerate().map(|(i, (c, tok))| {
+                            let state = match i.cmp(&typed) {
+                                Ordering::Less => "typed",
+                                Ordering::Equal => "untyped cursor",
+                                Ordering::Greater => "untyped",
+                            };
+                            let text = if c == '\n' && i == typed { String::from("\u{21b5}\n") } else { c.to_string() };
+                            html! { <span class=classes!(state, tok.class())>{ text }</span> }
+                        })
+                    }
+                </pre>
+                <div id="trans">
+                   { self.view_result() }
+                </div>
+            </>
+        }
+    }
+
+    fn view_result(&self) -> Html {
+        match self.last_item {
+            Some((wpm, accuracy)) if self.mode != Mode::Words => html! {
+                <p id="sentence-result">
+                    { tf("keyboard.sentence_result", &[&format!("{:.0}", wpm), &format!("{:.0}", accuracy)]) }
+                </p>
+            },
+            _ => html! {},
+        }
+    }
+
+    fn view_race(&self) -> Html {
+        let race = match &self.race {
+            Some(race) => race,
+            None => return html! {},
+        };
+        let elapsed = race.elapsed(clock::now_ms());
+        let done = race.run.splits.len();
+        let percent = |n: usize, of: usize| (n * 100).checked_div(of).unwrap_or(0);
+
+        let ghost = match &race.ghost {
+            Some(ghost) => {
+                let chars = ghost.chars_at(elapsed);
+                html! {
+                    <div class="race-lane">
+                        <span>{ tf("race.ghost", &[&ghost.words_at(elapsed), &race.words]) }</span>
+                        <div class="progress">
+                            <div class="progress-bar bg-secondary" role="progressbar"
+                                style=format!("width: {}%", percent(chars, ghost.keys.len()))/>
+                        </div>
+                    </div>
+                }
+            }
+            None => html! { <p>{ t("race.no_ghost") }</p> },
+        };
+        let split = match race.run.splits.last() {
+            Some(ms) => {
+                let delta = race.delta().map(|d| {
+                    let key = if d <= 0 { "race.ahead" } else { "race.behind" };
+                    tf(key, &[&seconds(d.unsigned_abs() as u32)])
+                });
+                html! {
+                    <p class="race-split">
+                        { tf("race.split", &[&done, &seconds(*ms)]) }
+                        { delta.map(|d| format!(" ({})", d)).unwrap_or_default() }
+                    </p>
+                }
+            }
+            None => html! {},
+        };
+
+        html! {
+            <div id="race-board">
+                <div class="race-lane">
+                    <span>{ tf("race.you", &[&done, &race.words]) }</span>
+                    <div class="progress">
+                        <div class="progress-bar" role="progressbar"
+                            style=format!("width: {}%", percent(done, race.words))/>
+                    </div>
+                </div>
+                { ghost }
+                { split }
+            </div>
+        }
+    }
+
+    fn view_race_result(&self) -> Html {
+        let result = match &self.race_result {
+            Some(result) => result,
+            None => return html! {},
+        };
+        let total = result.run.total();
+        let verdict = match (&result.previous, result.new_best) {
+            (None, _) => String::from(t("race.first_run")),
+            (Some(best), true) => tf("race.new_best", &[&seconds(best.total() - total)]),
+            (Some(best), false) => tf("race.best", &[&seconds(best.total())]),
+        };
+        let ghost = result.previous.as_ref();
+
+        html! {
+            <div id="race-result">
+                <p>{ tf("race.finished", &[&seconds(total)]) }{ " " }{ verdict }</p>
+                <table class="table table-sm">
+                    <thead>
+                        <tr>
+                            <th>{ "#" }</th>
+                            <th>{ t("race.col_word") }</th>
+                            <th>{ t("race.col_you") }</th>
+                            <th>{ t("race.col_ghost") }</th>
+                            <th>{ t("race.col_delta") }</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {
+                            for result.run.splits.iter().enumerate().map(|(i, ms)| {
+                                let name = self.dict[result.first + i]["name"].as_str().unwrap_or_default();
+                                let theirs = ghost.and_then(|g| g.splits.get(i));
+                                html! {
+                                    <tr>
+                                        <td>{ i + 1 }</td>
+                                        <td>{ name }</td>
+                                        <td>{ seconds(*ms) }</td>
+                                        <td>{ theirs.map(|g| seconds(*g)).unwrap_or_default() }</td>
+                                        <td>
+                                            {
+                                                theirs
+                                                    .map(|g| format!("{:+.1}", (*ms as f64 - *g as f64) / 1000.0))
+                                                    .unwrap_or_default()
+                                            }
+                                        </td>
+                                    </tr>
+                                }
+                            })
+                        }
+                    </tbody>
+                </table>
+            </div>
+        }
+    }
+
+    fn view_prefetch(&self) -> Html {
+        if self.mode == Mode::Code {
+            return html! {};
+        }
+        let status = match &self.prefetch {
+            None => String::new(),
+            Some(p) if p.done < p.total => tf("keyboard.prefetching", &[&p.done, &p.total]),
+            Some(p) => tf("keyboard.prefetched", &[&p.saved, &p.total]),
+        };
+        html! {
+            <div id="prefetch">
+                <button type="button" class="btn btn-link btn-sm"
+                    disabled=self.prefetch.as_ref().is_some_and(|p| p.done < p.total)
+                    onclick=self.link.callback(|_| Key::Prefetch)>
+                    { t("keyboard.prefetch") }
+                </button>
+                <span>{ status }</span>
+            </div>
+        }
+    }
+
     fn view_bottom_button(&self) -> Html {
         html! {
             <>
@@ -144,14 +633,31 @@ impl Keyboard {
                    <div class="col-2">
                    <button type="button" class="btn btn-outline-info"
                         onclick=self.link.callback(|_| Key::WordNextPre(String::from("prev")))>
-                        { "Prev" }
+                        { t("keyboard.prev") }
                    </button>
                    </div>
-                   <div class="col-6"></div>
+                   <div class="col-6">
+                       <div class="form-check" id="race-toggle">
+                           <input class="form-check-input" type="checkbox" id="race"
+                               checked=self.race_mode
+                               onclick=self.link.callback(|_| Key::ToggleRace)/>
+                           <label class="form-check-label" for="race">{ t("keyboard.race") }</label>
+                       </div>
+                       <div class="form-check" id="skip-mastered">
+                           <input class="form-check-input" type="checkbox" id="skip"
+                               checked=self.skip_mastered
+                               onclick=self.link.callback(|_| Key::ToggleSkipMastered)/>
+                           <label class="form-check-label" for="skip">{ t("keyboard.skip_mastered") }</label>
+                       </div>
+                       { self.view_prefetch() }
+                       <a class="btn btn-link btn-sm" id="placement-link" href=Route::Placement.to_hash()>
+                           { t("keyboard.placement") }
+                       </a>
+                   </div>
                    <div class="col-2">
                    <button type="button" class="btn btn-outline-info"
                         onclick=self.link.callback(|_| Key::WordNextPre(String::from("next")))>
-                        { "Next" }
+                        { t("keyboard.next") }
                    </button>
                    </div>
                 </div>
@@ -160,128 +666,313 @@ impl Keyboard {
     }
 }
 
+fn seconds(ms: u32) -> String {
+    format!("{:.1}", ms as f64 / 1000.0)
+}
+
 impl Component for Keyboard {
     type Message = Key;
-    type Properties = ();
+    type Properties = KeyboardProps;
 
-    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let settings = Settings::load();
         let cur_level = DICT_INDEX[0].to_string();
-        let dict: serde_json::Value = serde_json::from_str(DICT_MAP[&cur_level]).unwrap();
+        let dict: serde_json::Value = dict::load(&cur_level).unwrap();
 
         let nr_word: usize = dict.as_array().unwrap().len();
         let cur_index = 0;
-        let cur_chaper = cur_index / 20 + 1;
-
-        Self {
-            dict: dict,
-            nr_word: nr_word,
-            cur_index: cur_index,
-            cur_level: cur_level,
-            cur_chaper: cur_chaper,
-            inputs: String::with_capacity(100),
-            start_status: String::from("Start"),
+        let cur_chaper = cur_index / CHAPTER_SIZE + 1;
+
+        let mut keyboard = Self {
+            props,
+            dict,
+            nr_word,
+            cur_index,
+            cur_level,
+            mode: Mode::Words,
+            lang: Lang::Plain,
+            auto_close: settings.auto_close,
+            rules: Rules {
+                case: settings.case,
+                separators: settings.separators,
+            },
+            caps_lock: false,
+            composing: false,
+            cur_chaper,
+            attempt: Attempt::default(),
+            last_item: None,
+            session: None,
+            race_mode: settings.race_ghost,
+            mastery: Mastery::load(),
+            skip_mastered: settings.skip_mastered,
+            race: None,
+            race_result: None,
+            prefetch: None,
+            _race_clock: None,
+            running: false,
             start_class: String::from("btn btn-primary btn-sm"),
-            _producer: EventBus::bridge(link.callback(Key::SetText)),
+            bus: EventBus::bridge(link.batch_callback(|req| match req {
+                Request::EventBusMsg(text) => Some(Key::SetText(text)),
+                Request::PauseSession => Some(Key::Pause),
+                Request::CapsLock(on) => Some(Key::CapsLock(on)),
+                Request::Composing(on) => Some(Key::Composing(on)),
+                Request::RaceStart { dict, chapter } => Some(Key::StartRace(dict, chapter)),
+                Request::Synced => Some(Key::Reload),
+                _ => None,
+            })),
             link,
-            audio: AudioPlayer::new(Pronunc::AmE),
-        }
+            audio: AudioPlayer::new(Pronunc::AmE, settings.audio),
+        };
+        keyboard.apply_props();
+        keyboard
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
             Key::SetText(text) => {
-                if self.start_status != String::from("Pause") || text.len() != 1 {
-                    return true;
-                }
-
-                self.audio.play_click();
+                let mut chars = text.chars();
+                let chr = match (chars.next(), chars.next()) {
+                    _ if !self.running => return true,
+                    (Some(chr), None) => chr,
+                    _ if text == "Enter" && self.mode == Mode::Code => '\n',
+                    _ => return true,
+                };
 
-                let chr: char = text.as_bytes()[0] as char;
-                self.inputs.push(chr);
+                self.audio.play_click(&text);
 
-                let word = self.dict[self.cur_index]["name"].as_str().unwrap();
+                if let Some(session) = self.session.as_mut() {
+                    session.keystrokes += 1;
+                }
 
-                if word.starts_with(&self.inputs) {
-                    if word.len() == self.inputs.len() {
-                        self.inputs.clear();
-                        self.cur_index = self.cur_index + 1;
+                let word = self.current().to_string();
+                // Separator policies only apply to dictionary entries and
+                // code is always matched exactly
+                let rules = match self.mode {
+                    Mode::Words => self.rules,
+                    Mode::Sentences => Rules {
+                        case: self.rules.case,
+                        ..Default::default()
+                    },
+                    Mode::Code => Rules::default(),
+                };
+                if self.attempt.key(&rules, &word, chr, clock::now_ms()) {
+                    if let Some(race) = self.race.as_mut() {
+                        race.key(clock::now_ms());
+                    }
+                    if let Some(session) = self.session.as_mut() {
+                        session.chars += 1;
+                    }
+                    self.auto_fill();
+                    if self.attempt.is_complete(&word) {
+                        if let Some(session) = self.session.as_mut() {
+                            session.words += 1;
+                        }
+                        self.last_item = Some(self.attempt.score(clock::now_ms()));
+                        if self.mode == Mode::Words {
+                            self.mastery
+                                .record(&self.cur_level, &word, self.attempt.mistakes);
+                            self.mastery.save();
+                        }
+                        self.reward(&word);
+                        self.attempt = Attempt::default();
+                        let chapter_done = self
+                            .race
+                            .as_mut()
+                            .is_some_and(|race| race.split(clock::now_ms()));
+                        self.cur_index += 1;
                         if self.cur_index >= self.nr_word {
                             self.cur_index = 0;
                         }
-                        self.cur_chaper = self.cur_index / 20 + 1;
+                        self.cur_chaper = self.cur_index / CHAPTER_SIZE + 1;
+                        self.sync_route();
+                        self.bus.send(Request::WordTyped);
+                        if let Some(race) = self.race.as_ref() {
+                            self.bus.send(Request::RaceProgress {
+                                words: race.run.splits.len(),
+                                chars: race.run.keys.len(),
+                            });
+                        }
                         self.audio.play_correct();
-                        self.audio
-                            .play_word(self.dict[self.cur_index]["name"].as_str().unwrap());
+                        if chapter_done {
+                            self.finish_race();
+                            self.update(Key::Submit);
+                        } else {
+                            self.speak();
+                        }
                     }
                 } else {
+                    if let Some(session) = self.session.as_mut() {
+                        session.mistakes += 1;
+                    }
                     self.audio.play_wrong();
-                    self.inputs.clear();
+                    // Retyping a whole sentence after one slip is too harsh
+                    if self.mode == Mode::Words {
+                        self.attempt.typed.clear();
+                    }
                 }
             }
             Key::SelectProunc(prounc) => {
                 self.audio.set_prounc(prounc.into());
                 let msg = format!("> select audio type: {}.", self.audio.prounc());
                 ConsoleService::debug(&msg);
+                self.speak();
+            }
+            Key::Prefetch => {
+                let first = (self.cur_chaper - 1) * CHAPTER_SIZE;
+                let last = (first + CHAPTER_SIZE).min(self.nr_word);
+                let words: Vec<String> = (first..last)
+                    .filter_map(|i| self.dict[i]["name"].as_str().map(String::from))
+                    .collect();
+                self.prefetch = Some(Prefetch {
+                    done: 0,
+                    saved: 0,
+                    total: words.len(),
+                });
                 self.audio
-                    .play_word(self.dict[self.cur_index]["name"].as_str().unwrap());
+                    .prefetch(&words, self.link.callback(Key::Prefetched));
+            }
+            Key::Prefetched(saved) => {
+                if let Some(p) = self.prefetch.as_mut() {
+                    p.done += 1;
+                    p.saved += saved as usize;
+                }
+            }
+            Key::SelectMode(mode) => {
+                let mode = Mode::from_code(&mode).unwrap_or(Mode::Words);
+                if mode == self.mode {
+                    return false;
+                }
+                if let Some(level) = mode.levels().into_iter().next() {
+                    self.set_level(level);
+                }
+                self.sync_route();
             }
             Key::SelectLevel(level) => {
-                self.cur_level = level;
-                let msg = format!("> select level: {}.", self.cur_level);
+                let msg = format!("> select level: {}.", level);
                 ConsoleService::debug(&msg);
 
-                self.dict = serde_json::from_str(DICT_MAP[&self.cur_level]).unwrap();
-                self.nr_word = self.dict.as_array().unwrap().len();
-                self.inputs.clear();
-                self.cur_index = 0;
-                self.cur_chaper = self.cur_index / 20 + 1;
+                self.set_level(level);
+                self.sync_route();
             }
             Key::SelectChapter(chaper) => {
-                self.cur_index = (chaper - 1) * 20;
+                self.set_chapter(chaper);
                 let msg = format!("> select chaper: {}.", chaper);
                 ConsoleService::debug(&msg);
+                self.sync_route();
             }
             Key::WordNextPre(text) => {
-                if self.start_status == String::from("Start") {
+                if !self.running {
                     return true;
                 }
-                self.inputs.clear();
-                if text == String::from("next") {
-                    self.cur_index = self.cur_index + 1;
-                    if self.cur_index >= self.nr_word {
-                        self.cur_index = 0;
-                    }
+                self.abort_race();
+                self.attempt = Attempt::default();
+                if text == "next" {
+                    self.cur_index = self.next_index();
+                } else if self.cur_index == 0 {
+                    self.cur_index = self.nr_word - 1;
                 } else {
-                    if self.cur_index <= 0 {
-                        self.cur_index = self.nr_word - 1;
-                    } else {
-                        self.cur_index = self.cur_index - 1;
-                    }
+                    self.cur_index -= 1;
                 }
-                self.cur_chaper = self.cur_index / 20 + 1;
+                self.cur_chaper = self.cur_index / CHAPTER_SIZE + 1;
+                self.sync_route();
 
-                self.audio
-                    .play_word(self.dict[self.cur_index]["name"].as_str().unwrap());
+                self.speak();
             }
             Key::Submit => {
-                if self.start_status == String::from("Start") {
-                    self.start_status = String::from("Pause");
+                if !self.running {
+                    self.running = true;
                     self.start_class = String::from("btn btn-secondary btn-sm");
+                    self.start_session();
+                    if self.race_mode {
+                        self.start_race();
+                    }
 
-                    self.audio
-                        .play_word(self.dict[self.cur_index]["name"].as_str().unwrap());
+                    self.speak();
                 } else {
-                    self.start_status = String::from("Start");
+                    self.running = false;
                     self.start_class = String::from("btn btn-primary btn-sm");
+                    self.finish_session();
                 }
             }
+            Key::ToggleSkipMastered => {
+                self.skip_mastered =
+                    Settings::modify(|s| s.skip_mastered = !s.skip_mastered).skip_mastered;
+            }
+            Key::ToggleRace => {
+                self.race_mode = Settings::modify(|s| s.race_ghost = !s.race_ghost).race_ghost;
+                self.abort_race();
+                self.race_result = None;
+            }
+            Key::StartRace(level, chaper) => {
+                if self.running {
+                    self.running = false;
+                    self.finish_session();
+                }
+                if level != self.cur_level {
+                    self.set_level(level.clone());
+                }
+                // A dictionary this browser doesn't have, e.g. an imported pack
+                if level != self.cur_level {
+                    self.start_class = String::from("btn btn-primary btn-sm");
+                    return true;
+                }
+                self.set_chapter(chaper);
+                self.sync_route();
+                self.running = true;
+                self.start_class = String::from("btn btn-secondary btn-sm");
+                self.start_session();
+                self.start_race();
+                self.speak();
+            }
+            Key::RaceTick => {
+                if self.race.as_ref().is_none_or(|r| r.started.is_none()) {
+                    return false;
+                }
+            }
+            Key::CapsLock(on) => {
+                if on == self.caps_lock {
+                    return false;
+                }
+                self.caps_lock = on;
+            }
+            Key::Composing(on) => {
+                if on == self.composing {
+                    return false;
+                }
+                self.composing = on;
+            }
+            Key::Reload => {
+                // Saving what is held would undo what the sync brought in
+                let settings = Settings::load();
+                self.mastery = Mastery::load();
+                self.auto_close = settings.auto_close;
+                self.rules = Rules {
+                    case: settings.case,
+                    separators: settings.separators,
+                };
+                self.race_mode = settings.race_ghost;
+                self.skip_mastered = settings.skip_mastered;
+            }
+            Key::Pause => {
+                if !self.running {
+                    return false;
+                }
+                self.running = false;
+                self.start_class = String::from("btn btn-primary btn-sm");
+                self.attempt = Attempt::default();
+                self.finish_session();
+            }
         }
 
         true
     }
 
-    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
-        false
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        // Only a navigation re-renders the app, and it may lead back to the
+        // props we had before `sync_route` moved on, so always follow them
+        self.props = props;
+        self.apply_props();
+        true
     }
 
     fn view(&self) -> Html {
@@ -289,12 +980,37 @@ impl Component for Keyboard {
             <>
                 <div class="container-fluid">
                     { self.view_select_button() }
+                    { self.view_mastery() }
                 </div>
+                {
+                    if self.composing {
+                        html! { <div id="ime-notice" role="alert">{ t("keyboard.ime") }</div> }
+                    } else {
+                        html! {}
+                    }
+                }
+                {
+                    if self.caps_lock {
+                        html! { <div id="caps-lock" role="alert">{ t("keyboard.caps_lock") }</div> }
+                    } else {
+                        html! {}
+                    }
+                }
                 { self.view_word() }
                 <div class="container-fluid">
+                    { self.view_race() }
+                    { self.view_race_result() }
                     { self.view_bottom_button() }
                 </div>
             </>
         }
     }
+
+    /// Leaving the page mid-session records it, as pausing would.
+    fn destroy(&mut self) {
+        if self.running {
+            self.running = false;
+            self.finish_session();
+        }
+    }
 }
@@ -1,5 +1,15 @@
 pub mod audioplayer;
 pub mod body;
+pub mod dictionary;
 pub mod footer;
 pub mod header;
 pub mod keyboard;
+pub mod multiplayer;
+pub mod placement;
+pub mod profile;
+pub mod profile_switcher;
+pub mod settings;
+pub mod stats;
+pub mod sync_status;
+pub mod toasts;
+pub mod update_banner;
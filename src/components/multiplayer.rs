@@ -0,0 +1,321 @@
+use std::collections::BTreeMap;
+use yew::agent::Bridged;
+use yew::services::websocket::{WebSocketService, WebSocketStatus, WebSocketTask};
+use yew::{html, Bridge, Component, ComponentLink, Html, ShouldRender};
+
+use keypress_protocol::race::{ClientMsg, Player, PlayerId, ServerMsg};
+
+use crate::common::dict::{self, CHAPTER_SIZE, DICT_INDEX};
+use crate::common::event_bus::{EventBus, Request};
+use crate::common::i18n::{t, tf};
+use crate::common::msg::MultiMsg;
+use crate::common::route::Route;
+use crate::common::settings::Settings;
+
+/// The chapter a room is racing through.
+struct RoomRace {
+    dict: String,
+    chapter: usize,
+    words: usize,
+}
+
+/// Rooms on a `keypress-race-server`, where everyone types the same
+/// chapter at once and sees each other's progress.
+pub struct Multiplayer {
+    link: ComponentLink<Self>,
+    server: String,
+    name: String,
+    room: String,
+    ws: Option<WebSocketTask>,
+    me: Option<PlayerId>, // set once the server welcomed us
+    players: Vec<Player>,
+    places: BTreeMap<PlayerId, usize>,
+    race: Option<RoomRace>,
+    error: Option<String>,
+    _bus: Box<dyn Bridge<EventBus>>,
+}
+
+impl Multiplayer {
+    fn send(&mut self, msg: ClientMsg) {
+        if let Some(ws) = self.ws.as_mut() {
+            ws.send(Ok(msg.to_json()));
+        }
+    }
+
+    fn disconnect(&mut self) {
+        self.ws = None;
+        self.me = None;
+        self.players.clear();
+        self.places.clear();
+        self.race = None;
+    }
+
+    fn player(&mut self, id: PlayerId) -> Option<&mut Player> {
+        self.players.iter_mut().find(|p| p.id == id)
+    }
+
+    fn receive(&mut self, msg: ServerMsg) {
+        match msg {
+            ServerMsg::Welcome { id, players, .. } => {
+                self.me = Some(id);
+                self.players = players;
+            }
+            ServerMsg::Joined { player } => self.players.push(player),
+            ServerMsg::Left { id } => self.players.retain(|p| p.id != id),
+            ServerMsg::Started { dict, chapter } => {
+                let nr_word = dict::load(&dict).map_or(0, |d| d.as_array().unwrap().len());
+                let first = (chapter.max(1) - 1) * CHAPTER_SIZE;
+                for p in self.players.iter_mut() {
+                    p.words = 0;
+                    p.chars = 0;
+                    p.finished = None;
+                }
+                self.places.clear();
+                self.race = Some(RoomRace {
+                    dict: dict.clone(),
+                    chapter,
+                    words: CHAPTER_SIZE.min(nr_word.saturating_sub(first)),
+                });
+                self._bus.send(Request::RaceStart { dict, chapter });
+            }
+            ServerMsg::Progress { id, words, chars } => {
+                if let Some(p) = self.player(id) {
+                    p.words = words;
+                    p.chars = chars;
+                }
+            }
+            ServerMsg::Finished { id, ms, place } => {
+                let words = self.race.as_ref().map_or(0, |r| r.words);
+                if let Some(p) = self.player(id) {
+                    p.words = p.words.max(words);
+                    p.finished = Some(ms);
+                }
+                self.places.insert(id, place);
+            }
+            ServerMsg::Error { message } => self.error = Some(message),
+        }
+    }
+
+    fn view_join(&self) -> Html {
+        let joined = self.ws.is_some();
+
+        html! {
+            <div class="form-row">
+                <div class="col-4">
+                    <input type="text" class="form-control form-control-sm"
+                        placeholder=t("multi.server") value=self.server.clone() disabled=joined
+                        oninput=self.link.callback(|e: html::InputData| MultiMsg::SetServer(e.value))/>
+                </div>
+                <div class="col-3">
+                    <input type="text" class="form-control form-control-sm"
+                        placeholder=t("multi.room") value=self.room.clone() disabled=joined
+                        oninput=self.link.callback(|e: html::InputData| MultiMsg::SetRoom(e.value))/>
+                </div>
+                <div class="col-3">
+                    <input type="text" class="form-control form-control-sm"
+                        placeholder=t("multi.name") value=self.name.clone() disabled=joined
+                        oninput=self.link.callback(|e: html::InputData| MultiMsg::SetName(e.value))/>
+                </div>
+                <div class="col-2">
+                {
+                    if joined {
+                        html! {
+                            <button type="button" class="btn btn-outline-secondary btn-sm"
+                                onclick=self.link.callback(|_| MultiMsg::Leave)>
+                                { t("multi.leave") }
+                            </button>
+                        }
+                    } else {
+                        html! {
+                            <button type="button" class="btn btn-outline-info btn-sm"
+                                disabled=self.room.trim().is_empty() || self.name.trim().is_empty()
+                                onclick=self.link.callback(|_| MultiMsg::Join)>
+                                { t("multi.join") }
+                            </button>
+                        }
+                    }
+                }
+                </div>
+            </div>
+        }
+    }
+
+    fn view_player(&self, player: &Player) -> Html {
+        let total = self.race.as_ref().map_or(0, |r| r.words);
+        let percent = (player.words * 100)
+            .checked_div(total)
+            .unwrap_or(0)
+            .min(100);
+        let name = if Some(player.id) == self.me {
+            tf("multi.you", &[&player.name])
+        } else {
+            player.name.clone()
+        };
+        let result = match (self.places.get(&player.id), player.finished) {
+            (Some(place), Some(ms)) => tf(
+                "multi.place",
+                &[place, &format!("{:.1}", ms as f64 / 1000.0)],
+            ),
+            _ => format!("{} / {}", player.words, total),
+        };
+
+        html! {
+            <div class="race-lane">
+                <span>{ name }</span>
+                <div class="progress">
+                    <div class="progress-bar" role="progressbar"
+                        style=format!("width: {}%", percent)/>
+                </div>
+                <span class="race-place">{ result }</span>
+            </div>
+        }
+    }
+
+    fn view_room(&self) -> Html {
+        if self.me.is_none() {
+            return html! {};
+        }
+        let status = match &self.race {
+            Some(race) => tf("multi.racing", &[&race.dict, &race.chapter]),
+            None => String::from(t("multi.waiting")),
+        };
+
+        html! {
+            <>
+                <p class="multi-status">
+                    { status }{ " " }
+                    <button type="button" class="btn btn-outline-info btn-sm"
+                        onclick=self.link.callback(|_| MultiMsg::Start)>
+                        { t("multi.start") }
+                    </button>
+                </p>
+                { for self.players.iter().map(|p| self.view_player(p)) }
+            </>
+        }
+    }
+}
+
+impl Component for Multiplayer {
+    type Message = MultiMsg;
+    type Properties = ();
+
+    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let settings = Settings::load();
+        Self {
+            server: settings.race_server,
+            name: settings.race_name,
+            room: String::new(),
+            ws: None,
+            me: None,
+            players: Vec::new(),
+            places: BTreeMap::new(),
+            race: None,
+            error: None,
+            _bus: EventBus::bridge(link.batch_callback(|req| match req {
+                Request::RaceProgress { .. } | Request::RaceFinished { .. } => {
+                    Some(MultiMsg::Bus(req))
+                }
+                _ => None,
+            })),
+            link,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            MultiMsg::SetServer(server) => self.server = server,
+            MultiMsg::SetRoom(room) => self.room = room,
+            MultiMsg::SetName(name) => self.name = name,
+            MultiMsg::Join => {
+                let (server, name) = (self.server.trim(), self.name.trim());
+                if name.is_empty() {
+                    self.error = Some(String::from(t("multi.no_name")));
+                    return true;
+                }
+                self.error = None;
+                match WebSocketService::connect_text(
+                    server,
+                    self.link.callback(MultiMsg::Received),
+                    self.link.callback(MultiMsg::Status),
+                ) {
+                    Ok(task) => self.ws = Some(task),
+                    Err(e) => self.error = Some(e.to_string()),
+                }
+            }
+            MultiMsg::Leave => {
+                self.send(ClientMsg::Leave);
+                self.disconnect();
+            }
+            MultiMsg::Start => {
+                let (dict, chapter) = Route::current()
+                    .level()
+                    .unwrap_or_else(|| (DICT_INDEX[0].to_string(), 1));
+                self.send(ClientMsg::Start { dict, chapter });
+            }
+            MultiMsg::Status(WebSocketStatus::Opened) => {
+                // Only a server that could be reached is worth keeping; the
+                // fields can't change while connecting
+                let (server, name) = (self.server.trim(), self.name.trim());
+                Settings::modify(|s| {
+                    s.race_server = String::from(server);
+                    s.race_name = String::from(name);
+                });
+                let join = ClientMsg::Join {
+                    room: self.room.trim().to_string(),
+                    name: self.name.trim().to_string(),
+                };
+                self.send(join);
+            }
+            MultiMsg::Status(_) => {
+                if self.ws.is_some() {
+                    self.error = Some(String::from(t("multi.lost")));
+                }
+                self.disconnect();
+            }
+            MultiMsg::Received(Ok(text)) => match ServerMsg::parse(&text) {
+                Ok(msg) => self.receive(msg),
+                Err(e) => self.error = Some(e),
+            },
+            MultiMsg::Received(Err(_)) => return false,
+            MultiMsg::Bus(req) => {
+                let (me, racing) = (self.me, self.race.is_some());
+                let me = match me.filter(|_| racing) {
+                    Some(me) => me,
+                    None => return false,
+                };
+                match req {
+                    Request::RaceProgress { words, chars } => {
+                        if let Some(p) = self.player(me) {
+                            p.words = words;
+                            p.chars = chars;
+                        }
+                        self.send(ClientMsg::Progress { words, chars });
+                    }
+                    Request::RaceFinished { ms } => self.send(ClientMsg::Finish { ms }),
+                    _ => return false,
+                }
+            }
+        }
+        true
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <div id="multiplayer" class="container-fluid">
+                <h6>{ t("multi.title") }</h6>
+                { self.view_join() }
+                {
+                    match &self.error {
+                        Some(error) => html! { <p class="multi-error">{ error }</p> },
+                        None => html! {},
+                    }
+                }
+                { self.view_room() }
+            </div>
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use web_sys::KeyboardEvent;
+use yew::{html, Component, ComponentLink, Html, ShouldRender};
+
+use crate::common::dict::{self, Word};
+use crate::common::i18n::{t, tf};
+use crate::common::msg::PlacementMsg;
+use crate::common::placement::{recalls, Placement, QUESTIONS};
+use crate::common::route::Route;
+
+/// A short adaptive test recommending where to start: each word is shown
+/// by its translation and has to be typed from memory.
+pub struct PlacementTest {
+    link: ComponentLink<Self>,
+    words: HashMap<&'static str, Vec<Word>>,
+    placement: Option<Placement>,
+    word: Option<Word>,
+    answer: String,
+    last: Option<(bool, String)>, // whether the previous word was recalled
+}
+
+impl PlacementTest {
+    fn next_word(&mut self) {
+        let placement = match self.placement.as_mut() {
+            Some(p) if !p.done() => p,
+            _ => {
+                self.word = None;
+                return;
+            }
+        };
+        let dict = placement.rung().dict;
+        let words = self.words.entry(dict).or_insert_with(|| dict::words(dict));
+        let index = placement.pick(words.len(), js_sys::Math::random());
+        self.word = words.get(index).cloned();
+        self.answer.clear();
+    }
+
+    /// The first letter, then a blank for each letter left.
+    fn hint(word: &str) -> String {
+        word.chars()
+            .enumerate()
+            .map(|(i, c)| match c {
+                _ if i == 0 => c.to_string(),
+                ' ' => String::from("  "),
+                _ => String::from(" _"),
+            })
+            .collect()
+    }
+
+    fn view_intro(&self) -> Html {
+        html! {
+            <div id="placement-intro">
+                <p>{ tf("placement.intro", &[&QUESTIONS]) }</p>
+                <button class="btn btn-primary btn-sm" onclick=self.link.callback(|_| PlacementMsg::Start)>
+                    { t("placement.start") }
+                </button>
+            </div>
+        }
+    }
+
+    fn view_question(&self, placement: &Placement, word: &Word) -> Html {
+        html! {
+            <div id="placement-question">
+                <p class="placement-count">{ tf("placement.progress", &[&(placement.answered() + 1), &QUESTIONS]) }</p>
+                { for word.trans.iter().map(|trans| html! { <p class="placement-trans">{ trans }</p> }) }
+                <p class="placement-hint">{ Self::hint(&word.name) }</p>
+                <input class="form-control" type="text" value=self.answer.clone()
+                    placeholder=t("placement.type")
+                    oninput=self.link.callback(|e: html::InputData| PlacementMsg::SetAnswer(e.value))
+                    onkeypress=self.link.batch_callback(|e: KeyboardEvent| {
+                        if e.key() == "Enter" { Some(PlacementMsg::Submit) } else { None }
+                    })/>
+                <button class="btn btn-primary btn-sm" onclick=self.link.callback(|_| PlacementMsg::Submit)>
+                    { t("placement.check") }
+                </button>
+                <button class="btn btn-outline-secondary btn-sm" onclick=self.link.callback(|_| PlacementMsg::Skip)>
+                    { t("placement.dont_know") }
+                </button>
+                {
+                    match &self.last {
+                        Some((true, name)) => html! { <p class="placement-right">{ tf("placement.right", &[name]) }</p> },
+                        Some((false, name)) => html! { <p class="placement-wrong">{ tf("placement.wrong", &[name]) }</p> },
+                        None => html! {},
+                    }
+                }
+            </div>
+        }
+    }
+
+    fn view_result(&self, placement: &Placement) -> Html {
+        let rung = placement.recommend();
+        let nr_word = self.words.get(rung.dict).map_or(0, |words| words.len());
+        let chapter = rung.chapter(nr_word);
+        let practice = Route::Practice {
+            dict: Some(rung.dict.to_string()),
+            chapter: Some(chapter),
+            word: None,
+        };
+
+        html! {
+            <div id="placement-result">
+                <p>{ tf("placement.result", &[&rung.dict, &chapter]) }</p>
+                <a class="btn btn-primary btn-sm" href=practice.to_hash()>{ t("placement.practise") }</a>
+                <button class="btn btn-outline-secondary btn-sm" onclick=self.link.callback(|_| PlacementMsg::Start)>
+                    { t("placement.retake") }
+                </button>
+            </div>
+        }
+    }
+}
+
+impl Component for PlacementTest {
+    type Message = PlacementMsg;
+    type Properties = ();
+
+    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            link,
+            words: HashMap::new(),
+            placement: None,
+            word: None,
+            answer: String::new(),
+            last: None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            PlacementMsg::Start => {
+                self.placement = Some(Placement::default());
+                self.last = None;
+                self.next_word();
+            }
+            PlacementMsg::SetAnswer(answer) => {
+                self.answer = answer;
+                return false;
+            }
+            PlacementMsg::Submit | PlacementMsg::Skip => {
+                let (placement, word) = match (self.placement.as_mut(), self.word.as_ref()) {
+                    (Some(p), Some(w)) => (p, w),
+                    _ => return false,
+                };
+                let recalled =
+                    matches!(msg, PlacementMsg::Submit) && recalls(&self.answer, &word.name);
+                placement.answer(recalled);
+                self.last = Some((recalled, word.name.clone()));
+                self.next_word();
+            }
+        }
+        true
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        let body = match (&self.placement, &self.word) {
+            (None, _) => self.view_intro(),
+            (Some(placement), Some(word)) => self.view_question(placement, word),
+            (Some(placement), None) => self.view_result(placement),
+        };
+
+        html! {
+            <div class="container" id="placement">
+                <h4>{ t("placement.title") }</h4>
+                { body }
+            </div>
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use yew::{html, Component, ComponentLink, Html, ShouldRender};
+
+use crate::common::clock;
+use crate::common::history::History;
+use crate::common::i18n::{t, tf};
+use crate::common::rewards::{xp_for_level, Achievement, Rewards, MAX_FREEZES};
+
+/// Level, streak and achievements earned while practising.
+pub struct Profile {
+    rewards: Rewards,
+    streak: u32,
+}
+
+impl Profile {
+    fn view_level(&self) -> Html {
+        let level = self.rewards.level();
+        let floor = xp_for_level(level);
+        let next = xp_for_level(level + 1);
+        let percent = (self.rewards.xp - floor) as f64 * 100.0 / (next - floor) as f64;
+
+        html! {
+            <div id="profile-level">
+                <h4>{ tf("profile.level", &[&level]) }</h4>
+                <div class="xp-bar">
+                    <div class="xp-fill" style=format!("width: {:.1}%", percent)></div>
+                </div>
+                <p>{ tf("profile.xp", &[&self.rewards.xp, &next]) }</p>
+            </div>
+        }
+    }
+
+    fn view_stats(&self) -> Html {
+        let rewards = &self.rewards;
+
+        html! {
+            <div class="row justify-content-center" id="profile-stats">
+                <div class="col-2">{ tf("profile.streak", &[&self.streak]) }</div>
+                <div class="col-2" title=t("profile.freezes_hint")>
+                    { tf("profile.freezes", &[&rewards.freezes, &MAX_FREEZES]) }
+                </div>
+                <div class="col-2">{ tf("profile.best_run", &[&rewards.best_clean_run]) }</div>
+                <div class="col-2">{ tf("profile.best_wpm", &[&format!("{:.0}", rewards.best_chapter_wpm)]) }</div>
+                <div class="col-2">{ tf("profile.chapters", &[&rewards.chapters.len()]) }</div>
+            </div>
+        }
+    }
+
+    fn view_achievement(&self, achievement: Achievement) -> Html {
+        let (title, desc) = achievement.keys();
+        let (class, status) = match self.rewards.unlocked.get(&achievement) {
+            Some(date) => ("achievement unlocked", tf("profile.unlocked_on", &[date])),
+            None => ("achievement", t("profile.locked").to_string()),
+        };
+
+        html! {
+            <li class=class>
+                <strong>{ t(title) }</strong>
+                <span>{ t(desc) }</span>
+                <small>{ status }</small>
+            </li>
+        }
+    }
+}
+
+impl Component for Profile {
+    type Message = ();
+    type Properties = ();
+
+    fn create(_props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+        let rewards = Rewards::load();
+        let today = clock::parse_day(&clock::today()).unwrap_or_default();
+        let streak = rewards.streak(&History::load().days(), today);
+        Self { rewards, streak }
+    }
+
+    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <div class="container" id="profile">
+                { self.view_level() }
+                { self.view_stats() }
+                <h5>{ t("profile.achievements") }</h5>
+                <ul id="achievements">
+                    { for Achievement::all().iter().map(|a| self.view_achievement(*a)) }
+                </ul>
+            </div>
+        }
+    }
+}
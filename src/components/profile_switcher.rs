@@ -0,0 +1,159 @@
+use yew::{html, Component, ComponentLink, Html, ShouldRender};
+
+use crate::common::i18n::{t, tf};
+use crate::common::msg::ProfilesMsg;
+use crate::common::profiles::{ProfileError, Profiles};
+use crate::common::storage;
+
+/// Picks who is practising; every profile has its own settings, progress
+/// and history.
+pub struct ProfileSwitcher {
+    link: ComponentLink<Self>,
+    profiles: Profiles,
+    managing: bool,
+    name: String,
+    error: Option<ProfileError>,
+}
+
+impl ProfileSwitcher {
+    /// Start over with the data of the newly active profile.
+    fn reload() {
+        let _ = web_sys::window().unwrap().location().reload();
+    }
+
+    fn confirm(message: &str) -> bool {
+        web_sys::window()
+            .unwrap()
+            .confirm_with_message(message)
+            .unwrap_or(false)
+    }
+
+    fn view_manage(&self) -> Html {
+        if !self.managing {
+            return html! {};
+        }
+        html! {
+            <div id="profiles-manage">
+                <input class="form-control form-control-sm" type="text" value=self.name.clone()
+                    placeholder=t("profiles.name")
+                    oninput=self.link.callback(|e: html::InputData| ProfilesMsg::SetName(e.value))/>
+                <button class="btn btn-outline-info btn-sm"
+                    onclick=self.link.callback(|_| ProfilesMsg::Create)>
+                    { t("profiles.create") }
+                </button>
+                {
+                    match &self.error {
+                        Some(error) => html! { <p class="profiles-error">{ error.to_string() }</p> },
+                        None => html! {},
+                    }
+                }
+                <ul>
+                    {
+                        for self.profiles.list.iter().map(|p| {
+                            let rename = p.id.clone();
+                            let delete = p.id.clone();
+                            html! {
+                                <li>
+                                    <span>{ p.display_name() }</span>
+                                    <button class="btn btn-outline-secondary btn-sm"
+                                        onclick=self.link.callback(move |_| ProfilesMsg::Rename(rename.clone()))>
+                                        { t("profiles.rename") }
+                                    </button>
+                                    <button class="btn btn-outline-danger btn-sm"
+                                        onclick=self.link.callback(move |_| ProfilesMsg::Delete(delete.clone()))>
+                                        { t("settings.remove") }
+                                    </button>
+                                </li>
+                            }
+                        })
+                    }
+                </ul>
+            </div>
+        }
+    }
+}
+
+impl Component for ProfileSwitcher {
+    type Message = ProfilesMsg;
+    type Properties = ();
+
+    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            link,
+            profiles: Profiles::load(),
+            managing: false,
+            name: String::new(),
+            error: None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        self.error = None;
+        let active = self.profiles.active.clone();
+        let result = match msg {
+            ProfilesMsg::Switch(id) => self.profiles.switch(&id),
+            ProfilesMsg::ToggleManage => {
+                self.managing = !self.managing;
+                return true;
+            }
+            ProfilesMsg::SetName(name) => {
+                self.name = name;
+                return false;
+            }
+            ProfilesMsg::Create => self.profiles.create(&self.name).map(|_| ()),
+            ProfilesMsg::Rename(id) => self.profiles.rename(&id, &self.name),
+            ProfilesMsg::Delete(id) => {
+                let name = match self.profiles.get(&id) {
+                    Some(p) => p.display_name().to_string(),
+                    None => return false,
+                };
+                if !Self::confirm(&tf("profiles.confirm_delete", &[&name])) {
+                    return false;
+                }
+                self.profiles
+                    .remove(&id)
+                    .map(|_| storage::clear_profile(&id))
+            }
+        };
+        match result {
+            Ok(()) => {
+                self.profiles.save();
+                self.name.clear();
+                if self.profiles.active != active {
+                    Self::reload();
+                }
+            }
+            Err(error) => self.error = Some(error),
+        }
+        true
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        let active = self.profiles.active.clone();
+
+        html! {
+            <div id="profiles">
+                <select class="form-control form-control-sm" title=t("profiles.title")
+                    onchange=self.link.callback(move |v: html::ChangeData| match v {
+                        html::ChangeData::Select(ele) => ProfilesMsg::Switch(ele.value()),
+                        _ => ProfilesMsg::Switch(active.clone()),
+                    })>
+                    {
+                        for self.profiles.list.iter().map(|p| html! {
+                            <option value=p.id.clone() selected=p.id == self.profiles.active>{ p.display_name() }</option>
+                        })
+                    }
+                </select>
+                <button class="btn btn-outline-info btn-sm" title=t("profiles.manage")
+                    onclick=self.link.callback(|_| ProfilesMsg::ToggleManage)>
+                    { "⚙" }
+                </button>
+                { self.view_manage() }
+            </div>
+        }
+    }
+}
@@ -0,0 +1,766 @@
+use wasm_bindgen_futures::spawn_local;
+use yew::agent::{Dispatched, Dispatcher};
+use yew::services::reader::{File, FileData, ReaderService, ReaderTask};
+use yew::services::ConsoleService;
+use yew::{html, Component, ComponentLink, Html, ShouldRender};
+
+use crate::common::anki;
+use crate::common::backup::{Backup, BackupDiff};
+use crate::common::clock;
+use crate::common::dict::{self, Mode};
+use crate::common::event_bus::{EventBus, Request};
+use crate::common::file;
+use crate::common::i18n::{t, tf};
+use crate::common::msg::SettingsMsg;
+use crate::common::settings::{AudioSettings, Settings};
+use crate::common::snippets::{self, SnippetPack};
+use crate::common::sound_pack::{self, CustomPack, SoundPack, Variation};
+use crate::common::sync::SyncConfig;
+use crate::common::theme::Theme;
+use crate::common::typing::{case_name, policy_name, CasePolicy, Policy};
+use crate::common::user_dicts::{self, UserDict};
+use crate::components::audioplayer::{AudioPlayer, Pronunc};
+
+pub struct SettingsView {
+    settings: Settings,
+    import: Option<(Backup, BackupDiff)>,
+    status: Option<String>,
+    snippet_status: Option<String>,
+    anki_status: Option<String>,
+    sync: SyncConfig,
+    reader: Option<ReaderTask>,
+    packs: Vec<CustomPack>,
+    pack_name: String,
+    pack_status: Option<String>,
+    player: AudioPlayer, // previews key sounds
+    previews: usize,
+    bus: Dispatcher<EventBus>,
+    link: ComponentLink<Self>,
+}
+
+impl SettingsView {
+    fn read(&mut self, file: File, msg: fn(FileData) -> SettingsMsg) {
+        match ReaderService::read_file(file, self.link.callback(msg)) {
+            Ok(task) => self.reader = Some(task),
+            Err(e) => ConsoleService::error(&e.to_string()),
+        }
+    }
+
+    fn number_input(&self, id: &str, value: u32, msg: fn(u32) -> SettingsMsg) -> Html {
+        html! {
+            <input type="number" class="form-control form-control-sm" id=id.to_string()
+                min="1" max="120" value=value.to_string()
+                onchange=self.link.batch_callback(move |v: html::ChangeData| match v {
+                    html::ChangeData::Value(v) => v.parse().ok().filter(|n| *n > 0).map(msg),
+                    _ => None,
+                })/>
+        }
+    }
+
+    fn view_pomodoro(&self) -> Html {
+        html! {
+            <div class="form-group row">
+                <label class="col-3 col-form-label" for="pomodoro-work">{ t("settings.pomodoro_work") }</label>
+                <div class="col-2">
+                    { self.number_input("pomodoro-work", self.settings.pomodoro_work, SettingsMsg::SetPomodoroWork) }
+                </div>
+                <label class="col-3 col-form-label" for="pomodoro-break">{ t("settings.pomodoro_break") }</label>
+                <div class="col-2">
+                    { self.number_input("pomodoro-break", self.settings.pomodoro_break, SettingsMsg::SetPomodoroBreak) }
+                </div>
+            </div>
+        }
+    }
+
+    fn view_separator(&self, sep: char, id: &'static str, label: &'static str) -> Html {
+        let separators = self.settings.separators;
+        let current = match sep {
+            ' ' => separators.space,
+            '-' => separators.hyphen,
+            _ => separators.apostrophe,
+        };
+
+        html! {
+            <>
+                <label class="col-2 col-form-label" for=id>{ t(label) }</label>
+                <div class="col-2">
+                    <select class="form-control form-control-sm" id=id
+                        onchange=self.link.batch_callback(move |v: html::ChangeData| match v {
+                            html::ChangeData::Select(ele) => {
+                                Policy::from_code(&ele.value()).map(|p| SettingsMsg::SetSeparator(sep, p))
+                            }
+                            _ => None,
+                        })>
+                        {
+                            for Policy::iterator().map(|p| html! {
+                                <option value=p.code() selected=*p == current>{ policy_name(*p) }</option>
+                            })
+                        }
+                    </select>
+                </div>
+            </>
+        }
+    }
+
+    fn view_case(&self) -> Html {
+        let case = self.settings.case;
+
+        html! {
+            <div class="form-group row">
+                <label class="col-3 col-form-label" for="case">{ t("settings.case") }</label>
+                <div class="col-3">
+                    <select class="form-control form-control-sm" id="case"
+                        onchange=self.link.batch_callback(|v: html::ChangeData| match v {
+                            html::ChangeData::Select(ele) => CasePolicy::from_code(&ele.value()).map(SettingsMsg::SelectCase),
+                            _ => None,
+                        })>
+                        {
+                            for CasePolicy::iterator().map(|c| html! {
+                                <option value=c.code() selected=*c == case>{ case_name(*c) }</option>
+                            })
+                        }
+                    </select>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_separators(&self) -> Html {
+        html! {
+            <div class="form-group row">
+                { self.view_separator(' ', "sep-space", "settings.space") }
+                { self.view_separator('-', "sep-hyphen", "settings.hyphen") }
+                { self.view_separator('\'', "sep-apostrophe", "settings.apostrophe") }
+            </div>
+        }
+    }
+
+    fn view_theme(&self) -> Html {
+        let theme = self.settings.theme;
+
+        html! {
+            <div class="form-group row">
+                <label class="col-3 col-form-label" for="theme">{ t("settings.theme") }</label>
+                <div class="col-2">
+                    <select class="form-control form-control-sm" id="theme"
+                        onchange=self.link.callback(|v: html::ChangeData| match v {
+                            html::ChangeData::Select(ele) => SettingsMsg::SelectTheme(Theme::from_code(&ele.value())),
+                            _ => SettingsMsg::SelectTheme(None),
+                        })>
+                        <option value="auto" selected=theme.is_none()>{ t("theme.auto") }</option>
+                        {
+                            for Theme::iterator().map(|th| html! {
+                                <option value=th.code() selected=theme == Some(*th)>{ th }</option>
+                            })
+                        }
+                    </select>
+                </div>
+            </div>
+        }
+    }
+
+    fn audio_toggle(
+        &self,
+        id: &'static str,
+        label: &'static str,
+        checked: bool,
+        toggle: fn(&mut AudioSettings),
+    ) -> Html {
+        html! {
+            <div class="form-check">
+                <input type="checkbox" class="form-check-input" id=id checked=checked
+                    onclick=self.link.callback(move |_| SettingsMsg::SetAudio(Box::new(toggle)))/>
+                <label class="form-check-label" for=id>{ t(label) }</label>
+            </div>
+        }
+    }
+
+    fn audio_select(
+        &self,
+        id: &'static str,
+        label: &'static str,
+        options: &[(u32, String)],
+        current: u32,
+        set: fn(&mut AudioSettings, u32),
+    ) -> Html {
+        html! {
+            <>
+                <label class="col-3 col-form-label" for=id>{ t(label) }</label>
+                <div class="col-2">
+                    <select class="form-control form-control-sm" id=id
+                        onchange=self.link.batch_callback(move |v: html::ChangeData| match v {
+                            html::ChangeData::Select(ele) => ele.value().parse::<u32>().ok().map(|n| {
+                                SettingsMsg::SetAudio(Box::new(move |a: &mut AudioSettings| set(a, n)))
+                            }),
+                            _ => None,
+                        })>
+                        {
+                            for options.iter().map(|(n, name)| html! {
+                                <option value=n.to_string() selected=*n == current>{ name }</option>
+                            })
+                        }
+                    </select>
+                </div>
+            </>
+        }
+    }
+
+    fn view_key_sounds(&self) -> Html {
+        let audio = &self.settings.audio;
+        let packs = SoundPack::built_in()
+            .into_iter()
+            .chain(self.packs.iter().map(|p| SoundPack::Custom(p.name.clone())));
+
+        html! {
+            <>
+                <div class="form-group row">
+                    <label class="col-3 col-form-label" for="sound-pack">{ t("settings.key_sounds") }</label>
+                    <div class="col-2">
+                        <select class="form-control form-control-sm" id="sound-pack"
+                            onchange=self.link.batch_callback(|v: html::ChangeData| match v {
+                                html::ChangeData::Select(ele) => SoundPack::from_code(&ele.value()).map(|pack| {
+                                    SettingsMsg::SetAudio(Box::new(move |a: &mut AudioSettings| a.pack = pack))
+                                }),
+                                _ => None,
+                            })>
+                            {
+                                for packs.map(|p| html! {
+                                    <option value=p.code() selected=p == audio.pack>{ p.name() }</option>
+                                })
+                            }
+                        </select>
+                    </div>
+                    <div class="col-3">
+                        <select class="form-control form-control-sm" id="variation"
+                            onchange=self.link.batch_callback(|v: html::ChangeData| match v {
+                                html::ChangeData::Select(ele) => Variation::from_code(&ele.value()).map(|variation| {
+                                    SettingsMsg::SetAudio(Box::new(move |a: &mut AudioSettings| a.variation = variation))
+                                }),
+                                _ => None,
+                            })>
+                            {
+                                for Variation::all().iter().map(|v| html! {
+                                    <option value=v.code() selected=*v == audio.variation>{ v.name() }</option>
+                                })
+                            }
+                        </select>
+                    </div>
+                    <div class="col-2">
+                        <button type="button" class="btn btn-outline-info btn-sm"
+                            onclick=self.link.callback(|_| SettingsMsg::PreviewClick)>
+                            { t("settings.preview") }
+                        </button>
+                    </div>
+                </div>
+                <div class="form-group row">
+                    <label class="col-3 col-form-label" for="pack-name">{ t("settings.import_pack") }</label>
+                    <div class="col-2">
+                        <input type="text" class="form-control form-control-sm" id="pack-name"
+                            placeholder=t("settings.pack_name") value=self.pack_name.clone()
+                            oninput=self.link.callback(|e: html::InputData| SettingsMsg::SetPackName(e.value))/>
+                    </div>
+                    <div class="col-4">
+                        <input type="file" class="form-control-file" accept="audio/*" multiple=true
+                            onchange=self.link.batch_callback(|v: html::ChangeData| match v {
+                                html::ChangeData::Files(files) => {
+                                    Some(SettingsMsg::ImportPack((0..files.length()).filter_map(|i| files.get(i)).collect()))
+                                }
+                                _ => None,
+                            })/>
+                    </div>
+                </div>
+                {
+                    for self.packs.iter().map(|pack| {
+                        let name = pack.name.clone();
+                        html! {
+                            <div class="sound-pack">
+                                { tf("settings.pack_samples", &[&pack.name, &pack.samples]) }
+                                <button type="button" class="btn btn-link btn-sm"
+                                    onclick=self.link.callback(move |_| SettingsMsg::RemovePack(name.clone()))>
+                                    { t("settings.remove") }
+                                </button>
+                            </div>
+                        }
+                    })
+                }
+                {
+                    match &self.pack_status {
+                        Some(status) => html! { <p>{ status }</p> },
+                        None => html! {},
+                    }
+                }
+            </>
+        }
+    }
+
+    fn view_audio(&self) -> Html {
+        let audio = &self.settings.audio;
+        let repeats: Vec<(u32, String)> = AudioSettings::REPEATS
+            .iter()
+            .map(|n| (*n, tf("settings.times", &[n])))
+            .collect();
+        let intervals: Vec<(u32, String)> = AudioSettings::INTERVALS
+            .iter()
+            .map(|ms| (*ms, tf("settings.seconds", &[&(*ms as f64 / 1000.0)])))
+            .collect();
+        let rates: Vec<(u32, String)> = AudioSettings::RATES
+            .iter()
+            .map(|r| (*r, tf("settings.percent", &[r])))
+            .collect();
+
+        html! {
+            <div id="audio-settings">
+                <div class="form-group row">
+                    <label class="col-3 col-form-label" for="volume">{ t("settings.volume") }</label>
+                    <div class="col-3">
+                        <input type="range" class="form-control-range" id="volume" min="0" max="100"
+                            value=audio.volume.to_string()
+                            oninput=self.link.batch_callback(|e: html::InputData| {
+                                e.value.parse::<u32>().ok().map(|volume| {
+                                    SettingsMsg::SetAudio(Box::new(move |a: &mut AudioSettings| a.volume = volume))
+                                })
+                            })/>
+                    </div>
+                    <div class="col-1">{ tf("settings.percent", &[&audio.volume]) }</div>
+                </div>
+                { self.audio_toggle("sound-clicks", "settings.clicks", audio.clicks, |a| a.clicks = !a.clicks) }
+                { self.view_key_sounds() }
+                { self.audio_toggle("sound-feedback", "settings.feedback", audio.feedback, |a| a.feedback = !a.feedback) }
+                { self.audio_toggle("sound-pronunciation", "settings.pronunciation", audio.pronunciation, |a| a.pronunciation = !a.pronunciation) }
+                <div class="form-group row">
+                    { self.audio_select("repeat", "settings.repeat", &repeats, audio.repeat, |a, repeat| a.repeat = repeat) }
+                    { self.audio_select("interval", "settings.interval", &intervals, audio.interval, |a, interval| a.interval = interval) }
+                </div>
+                <div class="form-group row">
+                    { self.audio_select("rate", "settings.rate", &rates, audio.rate, |a, rate| a.rate = rate) }
+                </div>
+            </div>
+        }
+    }
+
+    fn view_snippets(&self) -> Html {
+        html! {
+            <div id="snippets">
+                <div class="form-check">
+                    <input type="checkbox" class="form-check-input" id="auto-close"
+                        checked=self.settings.auto_close
+                        onclick=self.link.callback(|_| SettingsMsg::ToggleAutoClose)/>
+                    <label class="form-check-label" for="auto-close">{ t("settings.auto_close") }</label>
+                </div>
+                <div class="form-group row">
+                    <label class="col-3 col-form-label">{ t("settings.import_snippets") }</label>
+                    <div class="col-4">
+                        <input type="file" accept=".json,application/json" class="form-control-file"
+                            onchange=self.link.callback(|v: html::ChangeData| match v {
+                                html::ChangeData::Files(files) => SettingsMsg::ImportSnippets(files.get(0)),
+                                _ => SettingsMsg::ImportSnippets(None),
+                            })/>
+                    </div>
+                    <div class="col-5">
+                        <p>{ self.snippet_status.clone().unwrap_or_default() }</p>
+                    </div>
+                </div>
+                <ul>
+                    {
+                        for snippets::imported().into_iter().map(|pack| {
+                            let name = pack.name.clone();
+                            html! {
+                                <li>
+                                    { tf("settings.snippet_pack", &[&pack.name, &pack.snippets.len()]) }
+                                    <button type="button" class="btn btn-outline-danger btn-sm"
+                                        onclick=self.link.callback(move |_| SettingsMsg::RemoveSnippets(name.clone()))>
+                                        { t("settings.remove") }
+                                    </button>
+                                </li>
+                            }
+                        })
+                    }
+                </ul>
+            </div>
+        }
+    }
+
+    fn view_user_dicts(&self) -> Html {
+        html! {
+            <div id="user-dicts">
+                <div class="form-group row">
+                    <label class="col-3 col-form-label">{ t("anki.import") }</label>
+                    <div class="col-4">
+                        <input type="file" accept=".txt,.tsv,.csv,text/plain" class="form-control-file"
+                            onchange=self.link.callback(|v: html::ChangeData| match v {
+                                html::ChangeData::Files(files) => SettingsMsg::ImportAnki(files.get(0)),
+                                _ => SettingsMsg::ImportAnki(None),
+                            })/>
+                    </div>
+                    <div class="col-5">
+                        <p>{ self.anki_status.clone().unwrap_or_default() }</p>
+                    </div>
+                </div>
+                <small>{ t("anki.hint") }</small>
+                <ul>
+                    {
+                        for user_dicts::imported().into_iter().map(|dict| {
+                            let name = dict.name.clone();
+                            html! {
+                                <li>
+                                    { tf("anki.dict", &[&dict.name, &dict.words.len()]) }
+                                    <button type="button" class="btn btn-outline-danger btn-sm"
+                                        onclick=self.link.callback(move |_| SettingsMsg::RemoveUserDict(name.clone()))>
+                                        { t("settings.remove") }
+                                    </button>
+                                </li>
+                            }
+                        })
+                    }
+                </ul>
+            </div>
+        }
+    }
+
+    fn view_preview(&self) -> Html {
+        let (backup, diff) = match &self.import {
+            Some(import) => import,
+            None => return html! {},
+        };
+
+        html! {
+            <div id="import-preview">
+                <p>{ tf("settings.exported_on", &[&backup.exported]) }</p>
+                <ul>
+                    <li>{ tf("settings.new_sessions", &[&diff.new_sessions]) }</li>
+                    <li>{ tf("settings.local_sessions", &[&diff.local_only_sessions]) }</li>
+                    <li>{ tf("settings.new_packs", &[&diff.new_packs]) }</li>
+                    <li>{ tf("settings.new_dicts", &[&diff.new_dicts]) }</li>
+                    <li>{ tf("settings.new_mastery", &[&diff.new_mastery]) }</li>
+                    {
+                        if diff.dropped > 0 {
+                            html! { <li>{ tf("settings.dropped", &[&diff.dropped]) }</li> }
+                        } else {
+                            html! {}
+                        }
+                    }
+                    <li>
+                        {
+                            if diff.settings_changed {
+                                t("settings.settings_changed")
+                            } else {
+                                t("settings.settings_unchanged")
+                            }
+                        }
+                    </li>
+                    <li>
+                        {
+                            if diff.daily_changed {
+                                t("settings.daily_changed")
+                            } else {
+                                t("settings.daily_unchanged")
+                            }
+                        }
+                    </li>
+                </ul>
+                <button type="button" class="btn btn-primary btn-sm"
+                    onclick=self.link.callback(|_| SettingsMsg::ApplyImport(true))>
+                    { t("settings.merge") }
+                </button>
+                <button type="button" class="btn btn-outline-danger btn-sm"
+                    onclick=self.link.callback(|_| SettingsMsg::ApplyImport(false))>
+                    { t("settings.replace") }
+                </button>
+                <button type="button" class="btn btn-outline-info btn-sm"
+                    onclick=self.link.callback(|_| SettingsMsg::CancelImport)>
+                    { t("settings.cancel") }
+                </button>
+            </div>
+        }
+    }
+
+    fn view_sync(&self) -> Html {
+        let last = match self.sync.last {
+            Some(ms) => tf("sync.last", &[&clock::format_datetime(ms)]),
+            None => String::from(t("sync.never")),
+        };
+
+        html! {
+            <div class="form-group row" id="sync">
+                <div class="col-4">
+                    <input type="url" class="form-control form-control-sm"
+                        placeholder=t("sync.server") value=self.sync.server.clone()
+                        oninput=self.link.callback(|e: html::InputData| SettingsMsg::SetSyncServer(e.value))/>
+                </div>
+                <div class="col-3">
+                    <input type="password" class="form-control form-control-sm"
+                        placeholder=t("sync.token") value=self.sync.token.clone()
+                        oninput=self.link.callback(|e: html::InputData| SettingsMsg::SetSyncToken(e.value))/>
+                </div>
+                <div class="col-2">
+                    <button type="button" class="btn btn-primary btn-sm"
+                        onclick=self.link.callback(|_| SettingsMsg::SaveSync)>
+                        { t("sync.save") }
+                    </button>
+                </div>
+                <div class="col-3">
+                    <p>{ last }</p>
+                </div>
+                <div class="col-12">
+                    <small>{ t("sync.hint") }</small>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_backup(&self) -> Html {
+        html! {
+            <div class="form-group row" id="backup">
+                <div class="col-2">
+                    <button type="button" class="btn btn-primary btn-sm"
+                        onclick=self.link.callback(|_| SettingsMsg::Export)>
+                        { t("settings.export") }
+                    </button>
+                </div>
+                <div class="col-4">
+                    <input type="file" accept=".json,application/json" class="form-control-file"
+                        onchange=self.link.callback(|v: html::ChangeData| match v {
+                            html::ChangeData::Files(files) => SettingsMsg::ImportFile(files.get(0)),
+                            _ => SettingsMsg::ImportFile(None),
+                        })/>
+                </div>
+                <div class="col-6">
+                    <p>{ self.status.clone().unwrap_or_default() }</p>
+                </div>
+            </div>
+        }
+    }
+}
+
+impl Component for SettingsView {
+    type Message = SettingsMsg;
+    type Properties = ();
+
+    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let settings = Settings::load();
+        Self {
+            player: AudioPlayer::new(Pronunc::AmE, settings.audio.clone()),
+            settings,
+            import: None,
+            status: None,
+            snippet_status: None,
+            anki_status: None,
+            sync: SyncConfig::load(),
+            reader: None,
+            packs: sound_pack::custom_packs(),
+            pack_name: String::new(),
+            pack_status: None,
+            previews: 0,
+            bus: EventBus::dispatcher(),
+            link,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            SettingsMsg::SetPomodoroWork(n) => {
+                self.settings = Settings::modify(|s| s.pomodoro_work = n);
+            }
+            SettingsMsg::SetPomodoroBreak(n) => {
+                self.settings = Settings::modify(|s| s.pomodoro_break = n);
+            }
+            SettingsMsg::SelectTheme(theme) => {
+                self.settings = Settings::modify(|s| s.theme = theme);
+                theme.unwrap_or_else(Theme::detect).apply();
+            }
+            SettingsMsg::Export => {
+                let today = clock::today();
+                let backup = Backup::collect(today.clone());
+                let name = format!("keypress-backup-{}.json", today);
+                file::download(&name, "application/json", &backup.to_json());
+            }
+            SettingsMsg::ImportFile(Some(file)) => {
+                self.read(file, SettingsMsg::Imported);
+            }
+            SettingsMsg::ImportFile(None) => return false,
+            SettingsMsg::Imported(FileData { content, .. }) => {
+                self.reader = None;
+                let text = String::from_utf8_lossy(&content);
+                match Backup::parse(&text) {
+                    Ok(backup) => {
+                        let diff = backup.diff(&Backup::collect(clock::today()));
+                        self.import = Some((backup, diff));
+                        self.status = None;
+                    }
+                    Err(e) => {
+                        self.import = None;
+                        self.status = Some(e.to_string());
+                    }
+                }
+            }
+            SettingsMsg::ApplyImport(merge) => {
+                if let Some((backup, _)) = self.import.take() {
+                    if merge {
+                        backup.merge_into(&Backup::collect(clock::today())).apply();
+                        self.status = Some(String::from(t("settings.merged")));
+                    } else {
+                        backup.apply();
+                        self.status = Some(String::from(t("settings.restored")));
+                    }
+                    self.settings = Settings::load();
+                    self.settings.theme.unwrap_or_else(Theme::detect).apply();
+                }
+            }
+            SettingsMsg::CancelImport => self.import = None,
+            SettingsMsg::SetSeparator(sep, policy) => {
+                self.settings = Settings::modify(|s| match sep {
+                    ' ' => s.separators.space = policy,
+                    '-' => s.separators.hyphen = policy,
+                    _ => s.separators.apostrophe = policy,
+                });
+            }
+            SettingsMsg::SelectCase(case) => {
+                self.settings = Settings::modify(|s| s.case = case);
+            }
+            SettingsMsg::ToggleAutoClose => {
+                self.settings = Settings::modify(|s| s.auto_close = !s.auto_close);
+            }
+            SettingsMsg::ImportSnippets(Some(file)) => {
+                self.read(file, SettingsMsg::SnippetsRead);
+            }
+            SettingsMsg::ImportSnippets(None) => return false,
+            SettingsMsg::SnippetsRead(FileData { content, .. }) => {
+                self.reader = None;
+                match SnippetPack::parse(&String::from_utf8_lossy(&content)) {
+                    Ok(mut pack) => {
+                        // Built-in packs and other levels keep their name
+                        if snippets::is_builtin(&pack.name) || dict::mode(&pack.name) != Mode::Code
+                        {
+                            pack.name = format!("{} (imported)", pack.name);
+                        }
+                        let status = tf(
+                            "settings.snippets_imported",
+                            &[&pack.snippets.len(), &pack.name],
+                        );
+                        self.snippet_status = Some(status);
+                        snippets::import(pack);
+                    }
+                    Err(e) => {
+                        self.snippet_status = Some(tf("settings.snippets_invalid", &[&e]));
+                    }
+                }
+            }
+            SettingsMsg::RemoveSnippets(name) => {
+                snippets::remove(&name);
+                self.snippet_status = None;
+            }
+            SettingsMsg::ImportAnki(Some(file)) => {
+                self.read(file, SettingsMsg::AnkiRead);
+            }
+            SettingsMsg::ImportAnki(None) => return false,
+            SettingsMsg::AnkiRead(FileData { name, content }) => {
+                self.reader = None;
+                match anki::parse(&String::from_utf8_lossy(&content)) {
+                    Ok(words) => {
+                        let mut name = anki::dict_name(&name);
+                        // Built-in levels keep their name
+                        if dict::is_builtin(&name) {
+                            name = format!("{} (Anki)", name);
+                        }
+                        self.anki_status = Some(tf("anki.imported", &[&words.len(), &name]));
+                        user_dicts::import(UserDict { name, words });
+                    }
+                    Err(e) => self.anki_status = Some(tf("anki.invalid", &[&e])),
+                }
+            }
+            SettingsMsg::RemoveUserDict(name) => {
+                user_dicts::remove(&name);
+                self.anki_status = None;
+            }
+            SettingsMsg::SetAudio(set) => {
+                self.settings = Settings::modify(|s| set(&mut s.audio));
+                self.player.set_settings(self.settings.audio.clone());
+            }
+            SettingsMsg::PreviewClick => {
+                // Cycle through a few keys so per-key variation is audible
+                let key = ["a", "s", "d", "f"][self.previews % 4];
+                self.previews += 1;
+                self.player.play_click(key);
+                return false;
+            }
+            SettingsMsg::SetPackName(name) => self.pack_name = name,
+            SettingsMsg::ImportPack(files) => {
+                if let Err(e) = sound_pack::validate(&self.pack_name, &files) {
+                    self.pack_status = Some(tf("settings.pack_invalid", &[&e]));
+                    return true;
+                }
+                let name = self.pack_name.clone();
+                let done = self.link.callback(SettingsMsg::PackImported);
+                spawn_local(async move { done.emit(sound_pack::import(name, files).await) });
+            }
+            SettingsMsg::PackImported(Ok(pack)) => {
+                self.pack_status = Some(tf("settings.pack_imported", &[&pack.name, &pack.samples]));
+                self.pack_name.clear();
+                self.packs = sound_pack::custom_packs();
+                self.settings = Settings::modify(|s| s.audio.pack = SoundPack::Custom(pack.name));
+                self.player.set_settings(self.settings.audio.clone());
+                self.player.reload_clicks();
+            }
+            SettingsMsg::PackImported(Err(e)) => {
+                self.pack_status = Some(tf("settings.pack_invalid", &[&e]));
+            }
+            SettingsMsg::RemovePack(name) => {
+                self.packs.retain(|p| p.name != name);
+                if self.settings.audio.pack == SoundPack::Custom(name.clone()) {
+                    self.settings = Settings::modify(|s| s.audio.pack = SoundPack::Classic);
+                    self.player.set_settings(self.settings.audio.clone());
+                }
+                spawn_local(async move { sound_pack::remove(&name).await });
+            }
+            SettingsMsg::SetSyncServer(server) => self.sync.server = server,
+            SettingsMsg::SetSyncToken(token) => self.sync.token = token,
+            SettingsMsg::SaveSync => {
+                let saved = SyncConfig::load();
+                // Another server or account starts over from nothing
+                if saved.server.trim() != self.sync.server.trim()
+                    || saved.token.trim() != self.sync.token.trim()
+                {
+                    self.sync = SyncConfig {
+                        server: self.sync.server.trim().to_string(),
+                        token: self.sync.token.trim().to_string(),
+                        ..Default::default()
+                    };
+                    self.sync.save();
+                }
+                self.bus.send(Request::SyncNow);
+            }
+        }
+        true
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <>
+                <div class="container-fluid" id="settings">
+                    <h5>{ t("settings.practice") }</h5>
+                    { self.view_pomodoro() }
+                    { self.view_case() }
+                    <h6>{ t("settings.phrases") }</h6>
+                    { self.view_separators() }
+                    <h5>{ t("settings.audio") }</h5>
+                    { self.view_audio() }
+                    <h5>{ t("settings.appearance") }</h5>
+                    { self.view_theme() }
+                    <h5>{ t("settings.code") }</h5>
+                    { self.view_snippets() }
+                    <h5>{ t("anki.title") }</h5>
+                    { self.view_user_dicts() }
+                    <h5>{ t("sync.title") }</h5>
+                    { self.view_sync() }
+                    <h5>{ t("settings.backup") }</h5>
+                    { self.view_backup() }
+                    { self.view_preview() }
+                </div>
+            </>
+        }
+    }
+}
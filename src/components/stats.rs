@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+use yew::{html, Component, ComponentLink, Html, ShouldRender};
+
+use crate::common::clock::{self, format_duration};
+use crate::common::history::{History, Totals};
+use crate::common::i18n::{t, tf};
+use crate::common::msg::StatsMsg;
+
+const CALENDAR_WEEKS: i64 = 53;
+const TREND_DAYS: i64 = 30;
+const TREND_WEEKS: i64 = 26;
+
+const RECENT_SESSIONS: usize = 10;
+
+const CHART_WIDTH: f64 = 600.0;
+const CHART_HEIGHT: f64 = 160.0;
+
+pub struct Stats {
+    history: History,
+    today: i64,
+    by_week: bool,
+    link: ComponentLink<Self>,
+}
+
+impl Stats {
+    fn level(words: u32) -> u8 {
+        match words {
+            0 => 0,
+            1..=19 => 1,
+            20..=49 => 2,
+            50..=99 => 3,
+            _ => 4,
+        }
+    }
+
+    fn view_summary(&self) -> Html {
+        let total = self.history.total();
+
+        html! {
+            <div class="row justify-content-center" id="stats-summary">
+                <div class="col-2">{ tf("stats.sessions", &[&total.sessions]) }</div>
+                <div class="col-2">{ tf("stats.time", &[&format_duration(total.seconds)]) }</div>
+                <div class="col-2">{ tf("stats.words", &[&total.words]) }</div>
+                <div class="col-2">{ tf("stats.streak", &[&self.history.streak(self.today)]) }</div>
+                <div class="col-2">{ tf("stats.longest", &[&self.history.longest_streak()]) }</div>
+            </div>
+        }
+    }
+
+    fn view_recent(&self) -> Html {
+        html! {
+            <table class="table table-sm" id="recent">
+                <thead>
+                    <tr>
+                        <th>{ t("stats.date") }</th>
+                        <th>{ t("stats.dictionary") }</th>
+                        <th>{ t("stats.chapter") }</th>
+                        <th>{ t("stats.duration") }</th>
+                        <th>{ t("stats.col_words") }</th>
+                        <th>{ t("stats.wpm") }</th>
+                        <th>{ t("stats.accuracy") }</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {
+                        for self.history.sessions.iter().rev().take(RECENT_SESSIONS).map(|rec| html! {
+                            <tr>
+                                <td>{ &rec.date }</td>
+                                <td>{ &rec.dict }</td>
+                                <td>{ rec.chapter }</td>
+                                <td>{ format_duration(rec.seconds) }</td>
+                                <td>{ rec.words }</td>
+                                <td>{ format!("{:.1}", rec.wpm()) }</td>
+                                <td>{ format!("{:.1}%", rec.accuracy()) }</td>
+                            </tr>
+                        })
+                    }
+                </tbody>
+            </table>
+        }
+    }
+
+    fn view_calendar(&self) -> Html {
+        let days = self.history.daily();
+        let first = clock::week_start(self.today) - (CALENDAR_WEEKS - 1) * 7;
+
+        let view_day = |day: i64| {
+            if day > self.today {
+                return html! { <div class="cal-day"></div> };
+            }
+            let words = days.get(&day).map(|t| t.words).unwrap_or(0);
+            let class = format!("cal-day level-{}", Stats::level(words));
+            let title = tf("stats.day_words", &[&clock::format_day(day), &words]);
+            html! { <div class=class title=title></div> }
+        };
+
+        html! {
+            <div id="calendar">
+                {
+                    for (0..CALENDAR_WEEKS).map(|week| html! {
+                        <div class="cal-week">
+                            { for (0..7).map(|d| view_day(first + week * 7 + d)) }
+                        </div>
+                    })
+                }
+            </div>
+        }
+    }
+
+    fn points(
+        totals: &BTreeMap<i64, Totals>,
+        from: i64,
+        span: i64,
+        value: impl Fn(&Totals) -> f64,
+        max: f64,
+    ) -> String {
+        totals
+            .range(from..)
+            .map(|(day, t)| {
+                let x = (day - from) as f64 * CHART_WIDTH / span as f64;
+                let y = CHART_HEIGHT - value(t) * CHART_HEIGHT / max;
+                format!("{:.1},{:.1}", x, y)
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn view_trend(&self) -> Html {
+        let (totals, from, span) = if self.by_week {
+            let this_week = clock::week_start(self.today);
+            (
+                self.history.weekly(),
+                this_week - (TREND_WEEKS - 1) * 7,
+                TREND_WEEKS * 7,
+            )
+        } else {
+            (
+                self.history.daily(),
+                self.today - (TREND_DAYS - 1),
+                TREND_DAYS,
+            )
+        };
+
+        let max_wpm = totals
+            .range(from..)
+            .map(|(_, t)| t.wpm())
+            .fold(10.0, f64::max)
+            * 1.1;
+        let wpm = Stats::points(&totals, from, span, |t| t.wpm(), max_wpm);
+        let accuracy = Stats::points(&totals, from, span, |t| t.accuracy(), 100.0);
+        let view_box = format!("-5 -5 {} {}", CHART_WIDTH + 10.0, CHART_HEIGHT + 10.0);
+
+        html! {
+            <div id="trend">
+                <div class="btn-group btn-group-sm">
+                    <button type="button"
+                        class=if self.by_week { "btn btn-outline-info" } else { "btn btn-info" }
+                        onclick=self.link.callback(|_| StatsMsg::TrendByWeek(false))>
+                        { t("stats.days") }
+                    </button>
+                    <button type="button"
+                        class=if self.by_week { "btn btn-info" } else { "btn btn-outline-info" }
+                        onclick=self.link.callback(|_| StatsMsg::TrendByWeek(true))>
+                        { t("stats.weeks") }
+                    </button>
+                </div>
+                <svg viewBox=view_box class="trend-chart">
+                    <polyline class="trend-wpm" points=wpm/>
+                    <polyline class="trend-accuracy" points=accuracy/>
+                </svg>
+                <p>
+                    <span class="trend-wpm">{ tf("stats.wpm_max", &[&format!("{:.0}", max_wpm)]) }</span>
+                    <span class="trend-accuracy">{ t("stats.accuracy_axis") }</span>
+                </p>
+            </div>
+        }
+    }
+}
+
+impl Component for Stats {
+    type Message = StatsMsg;
+    type Properties = ();
+
+    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let today = clock::parse_day(&clock::today()).unwrap_or(0);
+
+        Self {
+            history: History::load(),
+            today,
+            by_week: false,
+            link,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            StatsMsg::TrendByWeek(by_week) => self.by_week = by_week,
+        }
+        true
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <>
+                <div class="container-fluid" id="stats">
+                    { self.view_summary() }
+                    { self.view_calendar() }
+                    { self.view_trend() }
+                    { self.view_recent() }
+                </div>
+            </>
+        }
+    }
+}
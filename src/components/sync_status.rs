@@ -0,0 +1,158 @@
+use keypress_protocol::sync::{Pull, Push};
+use std::time::Duration;
+use wasm_bindgen::{prelude::Closure, JsCast};
+use yew::agent::Bridged;
+use yew::format::Text;
+use yew::services::fetch::{FetchService, FetchTask, Request as HttpRequest, Response};
+use yew::services::interval::{IntervalService, IntervalTask};
+use yew::{html, Bridge, Component, ComponentLink, Html, ShouldRender};
+
+use crate::common::clock;
+use crate::common::event_bus::{EventBus, Request};
+use crate::common::i18n::{t, tf};
+use crate::common::msg::SyncMsg;
+use crate::common::settings::Settings;
+use crate::common::sync::{self, SyncConfig};
+use crate::common::theme::Theme;
+
+const RETRY: Duration = Duration::from_secs(60);
+
+enum Status {
+    Idle,
+    Syncing,
+    Synced(f64),
+    Failed(String),
+}
+
+/// Syncs with the configured server after every session, on request and
+/// whenever the browser comes back online, showing the outcome in the footer.
+pub struct SyncStatus {
+    link: ComponentLink<Self>,
+    status: Status,
+    pending: Option<Push>,
+    _task: Option<FetchTask>,
+    _retry: IntervalTask,
+    _online: Closure<dyn FnMut(web_sys::Event)>,
+    bus: Box<dyn Bridge<EventBus>>,
+}
+
+impl SyncStatus {
+    fn start(&mut self) {
+        let config = SyncConfig::load();
+        if !config.enabled() || self.pending.is_some() {
+            return;
+        }
+        let push =
+            config
+                .replica
+                .push(sync::local_state(), &sync::local_entries(), clock::now_ms());
+        let request = HttpRequest::post(config.url())
+            .header("Authorization", format!("Bearer {}", config.token.trim()))
+            .header("Content-Type", "application/json")
+            .body(Ok(serde_json::to_string(&push).unwrap()))
+            .unwrap();
+        let callback = self.link.callback(|response: Response<Text>| {
+            let (meta, body) = response.into_parts();
+            match body {
+                Ok(body) if meta.status.is_success() => SyncMsg::Done(Ok(body)),
+                _ => SyncMsg::Done(Err(meta.status.to_string())),
+            }
+        });
+        match FetchService::fetch(request, callback) {
+            Ok(task) => {
+                self._task = Some(task);
+                self.pending = Some(push);
+                self.status = Status::Syncing;
+            }
+            Err(e) => self.status = Status::Failed(e.to_string()),
+        }
+    }
+
+    fn finish(&mut self, body: &str) -> Result<(), String> {
+        let push = match self.pending.take() {
+            Some(push) => push,
+            None => return Ok(()),
+        };
+        let pull: Pull = serde_json::from_str(body).map_err(|e| e.to_string())?;
+        let mut config = SyncConfig::load();
+        let (state, entries) = config.replica.pulled(push, pull);
+        let adopted = state.is_some();
+        sync::apply(state, entries);
+        if adopted {
+            Settings::load().theme.unwrap_or_else(Theme::detect).apply();
+            self.bus.send(Request::Synced);
+        }
+        let now = clock::now_ms();
+        config.last = Some(now);
+        config.save();
+        self.status = Status::Synced(now);
+        Ok(())
+    }
+}
+
+impl Component for SyncStatus {
+    type Message = SyncMsg;
+    type Properties = ();
+
+    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let on_online = link.clone();
+        let online =
+            Closure::wrap(
+                Box::new(move |_: web_sys::Event| on_online.send_message(SyncMsg::Sync))
+                    as Box<dyn FnMut(_)>,
+            );
+        web_sys::window()
+            .unwrap()
+            .add_event_listener_with_callback("online", online.as_ref().unchecked_ref())
+            .unwrap();
+
+        let mut status = Self {
+            status: Status::Idle,
+            pending: None,
+            _task: None,
+            _retry: IntervalService::spawn(RETRY, link.callback(|_| SyncMsg::Retry)),
+            _online: online,
+            bus: EventBus::bridge(link.batch_callback(|req| match req {
+                Request::SessionPaused | Request::SyncNow => Some(SyncMsg::Sync),
+                _ => None,
+            })),
+            link,
+        };
+        status.start();
+        status
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            SyncMsg::Sync => self.start(),
+            SyncMsg::Retry => {
+                if !matches!(self.status, Status::Failed(_)) {
+                    return false;
+                }
+                self.start();
+            }
+            SyncMsg::Done(result) => {
+                self._task = None;
+                if let Err(e) = result.and_then(|body| self.finish(&body)) {
+                    self.pending = None;
+                    self.status = Status::Failed(e);
+                }
+            }
+        }
+        true
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        let text = match &self.status {
+            Status::Idle => return html! {},
+            Status::Syncing => String::from(t("sync.syncing")),
+            Status::Synced(ms) => tf("sync.synced", &[&clock::format_datetime(*ms)]),
+            Status::Failed(e) => tf("sync.offline", &[e]),
+        };
+        html! { <div id="sync-status">{ text }</div> }
+    }
+}
@@ -0,0 +1,92 @@
+use std::time::Duration;
+use yew::agent::Bridged;
+use yew::services::timeout::{TimeoutService, TimeoutTask};
+use yew::{html, Bridge, Component, ComponentLink, Html, ShouldRender};
+
+use crate::common::event_bus::{EventBus, Request};
+use crate::common::i18n::{t, tf};
+use crate::common::msg::ToastMsg;
+use crate::common::rewards::Reward;
+
+const SHOWN_FOR: Duration = Duration::from_secs(5);
+
+struct Toast {
+    id: u32,
+    text: String,
+    _timeout: TimeoutTask,
+}
+
+/// Level ups, achievements and streak freezes, each shown for a few
+/// seconds in a corner.
+pub struct Toasts {
+    link: ComponentLink<Self>,
+    toasts: Vec<Toast>,
+    next_id: u32,
+    _bus: Box<dyn Bridge<EventBus>>,
+}
+
+fn message(reward: &Reward) -> String {
+    match reward {
+        Reward::LevelUp(level) => tf("toast.level_up", &[level]),
+        Reward::Unlocked(achievement) => tf("toast.unlocked", &[&t(achievement.keys().0)]),
+        Reward::FreezeEarned => t("toast.freeze_earned").to_string(),
+        Reward::FreezeUsed(days) => tf("toast.freeze_used", &[days]),
+    }
+}
+
+impl Component for Toasts {
+    type Message = ToastMsg;
+    type Properties = ();
+
+    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            _bus: EventBus::bridge(link.batch_callback(|req| match req {
+                Request::Rewarded(reward) => Some(ToastMsg::Show(reward)),
+                _ => None,
+            })),
+            link,
+            toasts: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            ToastMsg::Show(reward) => {
+                let id = self.next_id;
+                self.next_id += 1;
+                self.toasts.push(Toast {
+                    id,
+                    text: message(&reward),
+                    _timeout: TimeoutService::spawn(
+                        SHOWN_FOR,
+                        self.link.callback(move |_| ToastMsg::Dismiss(id)),
+                    ),
+                });
+            }
+            ToastMsg::Dismiss(id) => self.toasts.retain(|toast| toast.id != id),
+        }
+        true
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <div id="toasts">
+                {
+                    for self.toasts.iter().map(|toast| {
+                        let id = toast.id;
+                        html! {
+                            <div class="toast-item" onclick=self.link.callback(move |_| ToastMsg::Dismiss(id))>
+                                { &toast.text }
+                            </div>
+                        }
+                    })
+                }
+            </div>
+        }
+    }
+}
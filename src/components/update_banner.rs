@@ -0,0 +1,54 @@
+use yew::{html, Component, ComponentLink, Html, ShouldRender};
+
+use crate::common::i18n::t;
+use crate::common::msg::UpdateMsg;
+use crate::common::pwa;
+
+/// Offers to reload when the service worker has installed a newer build.
+pub struct UpdateBanner {
+    link: ComponentLink<Self>,
+    available: bool,
+}
+
+impl Component for UpdateBanner {
+    type Message = UpdateMsg;
+    type Properties = ();
+
+    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        pwa::register(link.callback(|_| UpdateMsg::Available));
+        Self {
+            link,
+            available: false,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            UpdateMsg::Available => self.available = true,
+            UpdateMsg::Reload => pwa::activate_update(),
+            UpdateMsg::Dismiss => self.available = false,
+        }
+        true
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        if !self.available {
+            return html! {};
+        }
+        html! {
+            <div id="update-banner">
+                <span>{ t("update.available") }</span>
+                <button class="btn" onclick=self.link.callback(|_| UpdateMsg::Reload)>
+                    { t("update.reload") }
+                </button>
+                <button class="btn" onclick=self.link.callback(|_| UpdateMsg::Dismiss)>
+                    { t("update.later") }
+                </button>
+            </div>
+        }
+    }
+}
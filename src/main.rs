@@ -1,28 +1,127 @@
 // From https://www.syntaxsuccess.com/viewarticle/experimenting-with-rust-and-webassembly
 #![recursion_limit = "1024"]
+// yew 0.18 expands component props into statements clippy flags as no-ops
+#![allow(clippy::unnecessary_operation)]
 
 mod common;
 mod components;
 
+use std::cell::Cell;
+use std::rc::Rc;
 use wasm_bindgen::{prelude::Closure, JsCast};
 use web_sys::KeyboardEvent;
 use yew::agent::Dispatched;
 use yew::{html, Component, ComponentLink, Html, ShouldRender};
 
 use crate::common::event_bus::{EventBus, Request};
-use crate::components::{body::Body, footer::Footer, header::Header, keyboard::Keyboard};
+use crate::common::i18n::{self, Locale};
+use crate::common::msg::AppMsg;
+use crate::common::route::Route;
+use crate::common::settings::Settings;
+use crate::common::theme;
+use crate::components::keyboard::START_BUTTON;
+use crate::components::{
+    body::Body, dictionary::Dictionary, footer::Footer, header::Header, keyboard::Keyboard,
+    multiplayer::Multiplayer, placement::PlacementTest, profile::Profile, settings::SettingsView,
+    stats::Stats, sync_status::SyncStatus, toasts::Toasts, update_banner::UpdateBanner,
+};
 
-pub struct Model;
+pub struct Model {
+    route: Route,
+    locale: Option<Locale>,
+    link: ComponentLink<Self>,
+}
+
+impl Model {
+    fn view_page(&self) -> Html {
+        match &self.route {
+            Route::Practice {
+                dict,
+                chapter,
+                word,
+            } => html! {
+                <div>
+                    <Body/>
+                    <Keyboard dict=dict.clone() chapter=*chapter word=*word/>
+                    <Multiplayer/>
+                </div>
+            },
+            Route::Dictionary { dict } => html! {
+                <div>
+                    <Dictionary dict=dict.clone()/>
+                </div>
+            },
+            Route::Stats => html! {
+                <div>
+                    <Stats/>
+                </div>
+            },
+            Route::Profile => html! {
+                <div>
+                    <Profile/>
+                </div>
+            },
+            Route::Placement => html! {
+                <div>
+                    <PlacementTest/>
+                </div>
+            },
+            Route::Settings => html! {
+                <div>
+                    <SettingsView/>
+                </div>
+            },
+        }
+    }
+}
 
 impl Component for Model {
-    type Message = ();
+    type Message = AppMsg;
     type Properties = ();
 
-    fn create(_props: Self::Properties, _link: ComponentLink<Self>) -> Self {
-        Self
+    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let on_route = link.clone();
+        let on_hashchange =
+            move |_: web_sys::Event| on_route.send_message(AppMsg::Navigate(Route::current()));
+        let handler = Box::new(on_hashchange) as Box<dyn FnMut(_)>;
+        let cb = Closure::wrap(handler);
+        web_sys::window()
+            .unwrap()
+            .add_event_listener_with_callback("hashchange", cb.as_ref().unchecked_ref())
+            .unwrap();
+        cb.forget();
+
+        theme::init();
+        let locale = Settings::load().locale;
+        i18n::set_locale(locale.unwrap_or_else(Locale::detect));
+
+        let route = Route::current();
+        route.remember();
+
+        Self {
+            route,
+            locale,
+            link,
+        }
     }
 
-    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            AppMsg::Navigate(route) => {
+                // Pages rewrite the hash with `Route::replace`, so compare
+                // with what is shown rather than the route last navigated to
+                if Route::shown() == route {
+                    return false;
+                }
+                route.remember();
+                self.route = route;
+            }
+            AppMsg::SelectLocale(locale) => {
+                Settings::modify(|s| s.locale = locale);
+                self.locale = locale;
+                i18n::set_locale(locale.unwrap_or_else(Locale::detect));
+            }
+        }
         true
     }
 
@@ -31,29 +130,95 @@ impl Component for Model {
     }
 
     fn view(&self) -> Html {
+        // Keyed on the locale so switching language rebuilds every page
         html! {
             <>
-                <header>
-                    <Header/>
-                </header>
-                <div>
-                    <Body/>
-                    <Keyboard/>
+                <div key=i18n::locale().code()>
+                    <UpdateBanner/>
+                    <Toasts/>
+                    <header>
+                        <Header route=self.route.clone() locale=self.locale
+                            on_locale=self.link.callback(AppMsg::SelectLocale)/>
+                    </header>
+                    { self.view_page() }
+                    <footer>
+                        <SyncStatus/>
+                        <Footer/>
+                    </footer>
                 </div>
-                <footer>
-                    <Footer/>
-                </footer>
             </>
         }
     }
 }
 
+fn session_running() -> bool {
+    web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.get_element_by_id(START_BUTTON))
+        .and_then(|button| button.get_attribute("aria-pressed"))
+        .is_some_and(|pressed| pressed == "true")
+}
+
+/// Forward composition events so practice can ask for English input.
+fn listen_composition(window: &web_sys::Window, composing: Rc<Cell<bool>>) {
+    for (event, started) in [("compositionstart", true), ("compositionend", false)] {
+        let mut event_bus = EventBus::dispatcher();
+        let composing = composing.clone();
+        let handler = move |_: web_sys::Event| {
+            composing.set(started);
+            event_bus.send(Request::Composing(started));
+        };
+        let cb = Closure::wrap(Box::new(handler) as Box<dyn FnMut(_)>);
+        window
+            .add_event_listener_with_callback(event, cb.as_ref().unchecked_ref())
+            .unwrap();
+        cb.forget();
+    }
+}
+
 fn main() {
     let mut event_bus = EventBus::dispatcher();
     let window = web_sys::window().unwrap();
+    let composing = Rc::new(Cell::new(false));
+    listen_composition(&window, composing.clone());
 
+    let mut caps_lock = false;
     let handler_submit = move |e: KeyboardEvent| {
         e.stop_propagation();
+        if e.get_modifier_state("CapsLock") != caps_lock {
+            caps_lock = !caps_lock;
+            event_bus.send(Request::CapsLock(caps_lock));
+        }
+        // Keys typed into a form field, like the room name, are not practice
+        let field = e
+            .target()
+            .and_then(|t| t.dyn_into::<web_sys::Element>().ok())
+            .is_some_and(|el| matches!(el.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT"));
+        if field {
+            return;
+        }
+        // An IME reports "Process" (key code 229) for keys it swallows;
+        // they are not letters of the word, so never count them
+        if e.is_composing() || e.key() == "Process" || e.key_code() == 229 {
+            if !composing.replace(true) {
+                event_bus.send(Request::Composing(true));
+            }
+            return;
+        }
+        if composing.replace(false) {
+            event_bus.send(Request::Composing(false));
+        }
+        // Space and Enter are typed in sentence and code modes, so during a
+        // session they must not scroll the page or press the Start button
+        let target = e
+            .target()
+            .and_then(|t| t.dyn_into::<web_sys::Element>().ok());
+        let typing = target
+            .as_ref()
+            .is_some_and(|el| el.tag_name() == "BODY" || el.id() == START_BUTTON);
+        if matches!(e.key().as_str(), " " | "Enter") && typing && session_running() {
+            e.prevent_default();
+        }
         // link.callback(move | e: KeyboardEvent | Key::SetText(e.key()));
         // event_bus.send(Request::EventBusMsg("Message received".to_owned()));
         event_bus.send(Request::EventBusMsg(e.key()));
@@ -0,0 +1,224 @@
+//! A small self-hosted server keeping every user's progress in one place so
+//! several devices can sync through it.
+//!
+//! Users are listed in a tokens file, one `user token` pair per line, and
+//! authenticate with `Authorization: Bearer <token>`. Each account is kept
+//! in memory and, when a data directory is given, written to `<user>.json`
+//! after every change.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use keypress_protocol::sync::{Account, Push, SYNC_PATH};
+use serde_json::json;
+use tiny_http::{Header, Request, Response, Server};
+
+/// Who may sync, keyed by their token.
+#[derive(Debug, Default)]
+pub struct Tokens {
+    users: HashMap<String, String>,
+}
+
+impl Tokens {
+    pub fn parse(text: &str) -> Result<Tokens, String> {
+        let mut tokens = Tokens::default();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (user, token) = match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [user, token] => (user, token),
+                _ => return Err(format!("line {}: expected `user token`", n + 1)),
+            };
+            // User names become file names
+            if !user
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(format!("line {}: invalid user name {:?}", n + 1, user));
+            }
+            tokens.users.insert(token.to_string(), user.to_string());
+        }
+        Ok(tokens)
+    }
+
+    pub fn user(&self, token: &str) -> Option<&str> {
+        self.users.get(token).map(String::as_str)
+    }
+}
+
+/// All accounts, optionally backed by a directory.
+#[derive(Debug, Default)]
+pub struct Store {
+    dir: Option<PathBuf>,
+    accounts: HashMap<String, Account>,
+}
+
+impl Store {
+    pub fn in_memory() -> Self {
+        Store::default()
+    }
+
+    /// Load the accounts saved in `dir`, creating it if needed.
+    pub fn open(dir: PathBuf) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        let mut accounts = HashMap::new();
+        for file in fs::read_dir(&dir)? {
+            let path = file?.path();
+            let user = match path.file_stem().and_then(|s| s.to_str()) {
+                Some(user) if path.extension().is_some_and(|e| e == "json") => user.to_string(),
+                _ => continue,
+            };
+            let account = serde_json::from_str(&fs::read_to_string(&path)?)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            accounts.insert(user, account);
+        }
+        Ok(Store {
+            dir: Some(dir),
+            accounts,
+        })
+    }
+
+    fn save(&self, user: &str, account: &Account) -> io::Result<()> {
+        let dir = match &self.dir {
+            Some(dir) => dir,
+            None => return Ok(()),
+        };
+        let tmp = dir.join(format!("{}.json.tmp", user));
+        fs::write(&tmp, serde_json::to_string(account)?)?;
+        fs::rename(tmp, dir.join(format!("{}.json", user)))
+    }
+}
+
+/// A response before it is turned into HTTP.
+#[derive(Debug, PartialEq)]
+pub struct Reply {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Reply {
+    fn ok(body: String) -> Self {
+        Reply { status: 200, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Reply {
+            status,
+            body: json!({ "error": message }).to_string(),
+        }
+    }
+}
+
+pub struct SyncServer {
+    tokens: Tokens,
+    store: Mutex<Store>,
+}
+
+impl SyncServer {
+    pub fn new(tokens: Tokens, store: Store) -> Self {
+        SyncServer {
+            tokens,
+            store: Mutex::new(store),
+        }
+    }
+
+    /// Answer one request; `auth` is the `Authorization` header.
+    pub fn handle(&self, method: &str, url: &str, auth: Option<&str>, body: &str) -> Reply {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        if path != SYNC_PATH {
+            return Reply::error(404, "not found");
+        }
+        if method == "OPTIONS" {
+            return Reply {
+                status: 204,
+                body: String::new(),
+            };
+        }
+        let user = match auth
+            .and_then(|a| a.strip_prefix("Bearer "))
+            .and_then(|t| self.tokens.user(t.trim()))
+        {
+            Some(user) => user,
+            None => return Reply::error(401, "unknown token"),
+        };
+
+        let mut store = self.store.lock().unwrap();
+        match method {
+            "GET" => {
+                let since = query
+                    .split('&')
+                    .find_map(|p| p.strip_prefix("since="))
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(0);
+                let account = store.accounts.get(user).cloned().unwrap_or_default();
+                Reply::ok(serde_json::to_string(&account.pull(since)).unwrap())
+            }
+            "POST" => {
+                let push: Push = match serde_json::from_str(body) {
+                    Ok(push) => push,
+                    Err(e) => return Reply::error(400, &e.to_string()),
+                };
+                // Only take the change once it is on disk, so a failed save
+                // leaves the account as it was
+                let mut account = store.accounts.get(user).cloned().unwrap_or_default();
+                let revision = account.revision();
+                let pull = account.push(push);
+                if pull.revision != revision {
+                    if let Err(e) = store.save(user, &account) {
+                        eprintln!("saving {}: {}", user, e);
+                        return Reply::error(500, "could not save");
+                    }
+                    store.accounts.insert(user.to_string(), account);
+                }
+                Reply::ok(serde_json::to_string(&pull).unwrap())
+            }
+            _ => Reply::error(405, "method not allowed"),
+        }
+    }
+
+    fn respond(&self, mut request: Request) -> io::Result<()> {
+        let mut body = String::new();
+        request.as_reader().read_to_string(&mut body)?;
+        let auth = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("Authorization"))
+            .map(|h| h.value.to_string());
+        let reply = self.handle(
+            request.method().as_str(),
+            request.url(),
+            auth.as_deref(),
+            &body,
+        );
+
+        // The app is usually served from another origin
+        let headers = [
+            ("Content-Type", "application/json"),
+            ("Access-Control-Allow-Origin", "*"),
+            ("Access-Control-Allow-Methods", "GET, POST, OPTIONS"),
+            (
+                "Access-Control-Allow-Headers",
+                "Authorization, Content-Type",
+            ),
+        ];
+        let mut response = Response::from_string(reply.body).with_status_code(reply.status);
+        for (field, value) in headers {
+            response.add_header(Header::from_bytes(field, value).unwrap());
+        }
+        request.respond(response)
+    }
+
+    /// Handle requests from `server` one at a time until it shuts down.
+    pub fn serve(&self, server: Server) {
+        for request in server.incoming_requests() {
+            if let Err(e) = self.respond(request) {
+                eprintln!("request failed: {}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+use keypress_sync_server::{Store, SyncServer, Tokens};
+
+const DEFAULT_ADDR: &str = "127.0.0.1:9002";
+const DEFAULT_TOKENS: &str = "tokens.txt";
+const DEFAULT_DATA: &str = "sync-data";
+
+fn fail(message: String) -> ! {
+    eprintln!("{}", message);
+    eprintln!("usage: keypress-sync-server [ADDR] [TOKENS_FILE] [DATA_DIR]");
+    process::exit(1);
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let addr = args.next().unwrap_or_else(|| DEFAULT_ADDR.to_string());
+    let tokens = args.next().unwrap_or_else(|| DEFAULT_TOKENS.to_string());
+    let data = args.next().unwrap_or_else(|| DEFAULT_DATA.to_string());
+
+    let tokens = fs::read_to_string(&tokens)
+        .map_err(|e| format!("{}: {}", tokens, e))
+        .and_then(|text| Tokens::parse(&text))
+        .unwrap_or_else(|e| fail(e));
+    let store =
+        Store::open(PathBuf::from(&data)).unwrap_or_else(|e| fail(format!("{}: {}", data, e)));
+    let server =
+        tiny_http::Server::http(&addr).unwrap_or_else(|e| fail(format!("{}: {}", addr, e)));
+
+    println!("keypress sync server listening on http://{}", addr);
+    SyncServer::new(tokens, store).serve(server);
+}
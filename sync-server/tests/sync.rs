@@ -0,0 +1,207 @@
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::process;
+use std::thread;
+
+use keypress_protocol::sync::{Entry, Pull, Push, Replica, SYNC_PATH};
+use keypress_sync_server::{Store, SyncServer, Tokens};
+use serde_json::{json, Value};
+
+const TOKENS: &str = "# user token\nalice secret-a\nbob secret-b\n";
+
+/// Start a server on an ephemeral port and return its address.
+fn start(store: Store) -> String {
+    let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let addr = server.server_addr().to_ip().unwrap().to_string();
+    let sync = SyncServer::new(Tokens::parse(TOKENS).unwrap(), store);
+    thread::spawn(move || sync.serve(server));
+    addr
+}
+
+/// A bare HTTP/1.1 request, returning the status and body.
+fn request(
+    addr: &str,
+    method: &str,
+    path: &str,
+    token: &str,
+    body: &str,
+) -> io::Result<(u16, String)> {
+    let mut stream = TcpStream::connect(addr)?;
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: {}\r\nAuthorization: Bearer {}\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        method,
+        path,
+        addr,
+        token,
+        body.len(),
+        body
+    )?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+    Ok((status, body.to_string()))
+}
+
+/// One device: its replica plus local state and history.
+struct Device {
+    token: &'static str,
+    replica: Replica,
+    state: Value,
+    history: Vec<Entry>,
+}
+
+impl Device {
+    fn new(token: &'static str) -> Self {
+        Device {
+            token,
+            replica: Replica::default(),
+            state: json!({}),
+            history: Vec::new(),
+        }
+    }
+
+    fn practise(&mut self, key: &str) {
+        self.history.push(Entry {
+            key: String::from(key),
+            data: json!({ "session": key }),
+        });
+    }
+
+    fn sync(&mut self, addr: &str, now: f64) -> io::Result<()> {
+        let push: Push = self.replica.push(self.state.clone(), &self.history, now);
+        let (status, body) = request(
+            addr,
+            "POST",
+            SYNC_PATH,
+            self.token,
+            &serde_json::to_string(&push).unwrap(),
+        )?;
+        assert_eq!(status, 200, "{}", body);
+        let pull: Pull = serde_json::from_str(&body).unwrap();
+        let (state, entries) = self.replica.pulled(push, pull);
+        if let Some(state) = state {
+            self.state = state;
+        }
+        self.history.extend(entries);
+        Ok(())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self.history.iter().map(|e| e.key.as_str()).collect();
+        keys.sort();
+        keys
+    }
+}
+
+#[test]
+fn rejects_unknown_tokens_and_paths() {
+    let addr = start(Store::in_memory());
+    let status = |method, path, token, body| request(&addr, method, path, token, body).unwrap().0;
+    assert_eq!(status("GET", SYNC_PATH, "nope", ""), 401);
+    assert_eq!(status("GET", "/other", "secret-a", ""), 404);
+    assert_eq!(status("POST", SYNC_PATH, "secret-a", "{"), 400);
+    assert_eq!(status("OPTIONS", SYNC_PATH, "", ""), 204);
+
+    let (status, body) = request(&addr, "GET", SYNC_PATH, "secret-a", "").unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(
+        serde_json::from_str::<Pull>(&body).unwrap(),
+        Pull::default()
+    );
+}
+
+#[test]
+fn devices_reconcile_after_working_offline() {
+    let addr = start(Store::in_memory());
+    let mut laptop = Device::new("secret-a");
+    let mut phone = Device::new("secret-a");
+
+    laptop.state = json!({ "theme": "dark" });
+    laptop.practise("laptop-1");
+    laptop.sync(&addr, 1000.0).unwrap();
+    phone.sync(&addr, 1500.0).unwrap();
+    assert_eq!(phone.state, json!({ "theme": "dark" }));
+    assert_eq!(phone.keys(), vec!["laptop-1"]);
+
+    // Both practise without a connection; the phone changes settings later
+    let offline = "127.0.0.1:1";
+    laptop.practise("laptop-2");
+    laptop.state = json!({ "theme": "light" });
+    assert!(laptop.sync(offline, 2000.0).is_err());
+    phone.practise("phone-1");
+    phone.state = json!({ "theme": "solarized" });
+    assert!(phone.sync(offline, 3000.0).is_err());
+
+    laptop.sync(&addr, 2000.0).unwrap();
+    phone.sync(&addr, 3000.0).unwrap();
+    laptop.sync(&addr, 3500.0).unwrap();
+
+    for device in [&laptop, &phone] {
+        assert_eq!(device.keys(), vec!["laptop-1", "laptop-2", "phone-1"]);
+        assert_eq!(device.state, json!({ "theme": "solarized" }));
+    }
+
+    // Another user sees none of it
+    let mut bob = Device::new("secret-b");
+    bob.sync(&addr, 4000.0).unwrap();
+    assert!(bob.history.is_empty());
+    assert_eq!(bob.state, json!({}));
+}
+
+#[test]
+fn accounts_survive_a_restart() {
+    let dir = std::env::temp_dir().join(format!("keypress-sync-{}", process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let addr = start(Store::open(dir.clone()).unwrap());
+    let mut laptop = Device::new("secret-a");
+    laptop.practise("laptop-1");
+    laptop.sync(&addr, 1000.0).unwrap();
+
+    let addr = start(Store::open(dir.clone()).unwrap());
+    let mut phone = Device::new("secret-a");
+    phone.sync(&addr, 2000.0).unwrap();
+    assert_eq!(phone.keys(), vec!["laptop-1"]);
+    assert_eq!(phone.state, json!({}));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn failed_saves_change_nothing() {
+    let dir = std::env::temp_dir().join(format!("keypress-sync-fail-{}", process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let addr = start(Store::open(dir.clone()).unwrap());
+    let mut laptop = Device::new("secret-a");
+    laptop.practise("laptop-1");
+    laptop.sync(&addr, 1000.0).unwrap();
+
+    // Without its directory the store can't write the next push
+    std::fs::remove_dir_all(&dir).unwrap();
+    laptop.practise("laptop-2");
+    laptop.state = json!({ "theme": "dark" });
+    let push = laptop
+        .replica
+        .push(laptop.state.clone(), &laptop.history, 2000.0);
+    let body = serde_json::to_string(&push).unwrap();
+    let (status, _) = request(&addr, "POST", SYNC_PATH, "secret-a", &body).unwrap();
+    assert_eq!(status, 500);
+
+    let mut phone = Device::new("secret-a");
+    phone.sync(&addr, 3000.0).unwrap();
+    assert_eq!(phone.keys(), vec!["laptop-1"]);
+    assert_eq!(phone.state, json!({}));
+}
+
+#[test]
+fn tokens_file_is_validated() {
+    let tokens = Tokens::parse(TOKENS).unwrap();
+    assert_eq!(tokens.user("secret-b"), Some("bob"));
+    assert_eq!(tokens.user("bob"), None);
+    assert!(Tokens::parse("alice").is_err());
+    assert!(Tokens::parse("../alice token").is_err());
+}
@@ -0,0 +1,228 @@
+use keypress_engine::dict::{self, nr_chapter, Word, CHAPTER_SIZE, DICT_INDEX};
+use keypress_engine::typing::{Attempt, Rules};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::progress::{Position, Progress};
+
+/// State of the terminal frontend: the open chapter and the word being typed.
+pub struct App {
+    pub dict: usize, // index into DICT_INDEX
+    pub chapter: usize,
+    pub word: usize,
+    pub words: Vec<Word>,
+    pub attempt: Attempt,
+    pub rules: Rules,
+    pub wrong: bool,              // the last key was a mistake
+    pub last: Option<(f64, f64)>, // wpm and accuracy of the last word
+    pub progress: Progress,
+    pub changed: bool, // progress needs saving
+    pub quit: bool,
+}
+
+impl App {
+    pub fn new(progress: Progress) -> Self {
+        let dict = DICT_INDEX
+            .iter()
+            .position(|d| *d == progress.dict)
+            .unwrap_or(0);
+        let mut app = App {
+            dict,
+            chapter: 1,
+            word: 0,
+            words: Vec::new(),
+            attempt: Attempt::default(),
+            rules: Rules::default(),
+            wrong: false,
+            last: None,
+            progress,
+            changed: false,
+            quit: false,
+        };
+        app.open(dict);
+        app
+    }
+
+    pub fn dict_name(&self) -> &'static str {
+        DICT_INDEX[self.dict]
+    }
+
+    pub fn nr_chapter(&self) -> usize {
+        nr_chapter(self.words.len()).max(1)
+    }
+
+    /// The words of the open chapter.
+    pub fn chapter_words(&self) -> &[Word] {
+        let start = ((self.chapter - 1) * CHAPTER_SIZE).min(self.words.len());
+        let end = (start + CHAPTER_SIZE).min(self.words.len());
+        &self.words[start..end]
+    }
+
+    pub fn current(&self) -> Option<&Word> {
+        self.chapter_words().get(self.word)
+    }
+
+    /// Switch to dictionary `dict`, resuming where it was left.
+    fn open(&mut self, dict: usize) {
+        self.dict = dict;
+        self.words = dict::words(self.dict_name());
+        let position = self
+            .progress
+            .positions
+            .get(self.dict_name())
+            .copied()
+            .unwrap_or_default();
+        self.chapter = position.chapter.clamp(1, self.nr_chapter());
+        self.word = position
+            .word
+            .min(self.chapter_words().len().saturating_sub(1));
+        self.progress.dict = self.dict_name().to_string();
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.attempt = Attempt::default();
+        self.wrong = false;
+        self.progress.positions.insert(
+            self.dict_name().to_string(),
+            Position {
+                chapter: self.chapter,
+                word: self.word,
+            },
+        );
+        self.changed = true;
+    }
+
+    fn set_chapter(&mut self, chapter: usize) {
+        self.chapter = chapter.clamp(1, self.nr_chapter());
+        self.word = 0;
+        self.reset();
+    }
+
+    /// Move to the next word, continuing into the next chapter.
+    fn next_word(&mut self) {
+        if self.word + 1 < self.chapter_words().len() {
+            self.word += 1;
+            self.reset();
+        } else if self.chapter < self.nr_chapter() {
+            self.set_chapter(self.chapter + 1);
+        } else {
+            self.set_chapter(1);
+        }
+    }
+
+    fn prev_word(&mut self) {
+        self.word = self.word.saturating_sub(1);
+        self.reset();
+    }
+
+    fn type_char(&mut self, c: char, now: f64) {
+        let target = match self.current() {
+            Some(word) => word.name.clone(),
+            None => return,
+        };
+        self.progress.keystrokes += 1;
+        if !self.attempt.key(&self.rules, &target, c, now) {
+            self.progress.mistakes += 1;
+            self.attempt.typed.clear();
+            self.wrong = true;
+            return;
+        }
+        self.wrong = false;
+        if self.attempt.is_complete(&target) {
+            self.last = Some(self.attempt.score(now));
+            self.progress.words += 1;
+            self.next_word();
+        }
+    }
+
+    /// Handle a key pressed at `now` ms.
+    pub fn key(&mut self, key: KeyEvent, now: f64) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => self.quit = true,
+            KeyCode::Char('c') if ctrl => self.quit = true,
+            KeyCode::Tab => self.open((self.dict + 1) % DICT_INDEX.len()),
+            KeyCode::BackTab => self.open((self.dict + DICT_INDEX.len() - 1) % DICT_INDEX.len()),
+            KeyCode::PageDown => self.set_chapter(self.chapter + 1),
+            KeyCode::PageUp => self.set_chapter(self.chapter.saturating_sub(1)),
+            KeyCode::Right => self.next_word(),
+            KeyCode::Left => self.prev_word(),
+            KeyCode::Backspace => self.reset(),
+            KeyCode::Char(c) if !ctrl => self.type_char(c, now),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(app: &mut App, code: KeyCode, now: f64) {
+        app.key(KeyEvent::from(code), now);
+    }
+
+    fn type_str(app: &mut App, text: &str, now: f64) {
+        for c in text.chars() {
+            press(app, KeyCode::Char(c), now);
+        }
+    }
+
+    #[test]
+    fn typing_a_word_moves_on_and_records_progress() {
+        let mut app = App::new(Progress::default());
+        assert_eq!(app.dict_name(), DICT_INDEX[0]);
+        let first = app.current().unwrap().name.clone();
+        let second = app.chapter_words()[1].name.clone();
+
+        press(&mut app, KeyCode::Char('#'), 0.0);
+        assert!(app.wrong);
+        let (head, tail) = first.split_at(first.len() - 1);
+        type_str(&mut app, head, 0.0);
+        assert_eq!(app.attempt.typed, head);
+        type_str(&mut app, tail, 6000.0);
+        assert_eq!(app.word, 1);
+        assert_eq!(app.current().unwrap().name, second);
+        assert_eq!(app.progress.words, 1);
+        assert_eq!(app.progress.mistakes, 1);
+        assert_eq!(
+            app.progress.positions[DICT_INDEX[0]],
+            Position {
+                chapter: 1,
+                word: 1
+            }
+        );
+        assert!(!app.wrong);
+        let (wpm, accuracy) = app.last.unwrap();
+        assert!(wpm > 0.0 && accuracy < 100.0);
+    }
+
+    #[test]
+    fn navigation_wraps_and_resumes() {
+        let mut app = App::new(Progress::default());
+        press(&mut app, KeyCode::PageUp, 0.0);
+        assert_eq!(app.chapter, 1);
+        press(&mut app, KeyCode::PageDown, 0.0);
+        press(&mut app, KeyCode::Right, 0.0);
+        assert_eq!((app.chapter, app.word), (2, 1));
+
+        for _ in 1..app.chapter_words().len() {
+            press(&mut app, KeyCode::Right, 0.0);
+        }
+        assert_eq!((app.chapter, app.word), (3, 0));
+
+        press(&mut app, KeyCode::Tab, 0.0);
+        assert_eq!(app.dict_name(), DICT_INDEX[1]);
+        assert_eq!(app.chapter, 1);
+        press(&mut app, KeyCode::BackTab, 0.0);
+        assert_eq!((app.chapter, app.word), (3, 0));
+
+        let app = App::new(app.progress);
+        assert_eq!(app.dict_name(), DICT_INDEX[0]);
+        assert_eq!((app.chapter, app.word), (3, 0));
+
+        let mut app = App::new(Progress::default());
+        press(&mut app, KeyCode::Esc, 0.0);
+        assert!(app.quit);
+    }
+}
@@ -0,0 +1,49 @@
+mod app;
+mod progress;
+mod ui;
+
+use std::io;
+use std::path::Path;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use ratatui::DefaultTerminal;
+
+use app::App;
+use progress::Progress;
+
+fn now_ms() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .unwrap_or_default()
+}
+
+fn run(terminal: &mut DefaultTerminal, app: &mut App, path: &Path) -> io::Result<()> {
+    while !app.quit {
+        terminal.draw(|frame| ui::draw(frame, app))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                app.key(key, now_ms());
+            }
+        }
+        if app.changed {
+            app.progress.save(path)?;
+            app.changed = false;
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    let path = progress::default_path();
+    let mut app = App::new(Progress::load(&path));
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &mut app, &path);
+    ratatui::restore();
+    if let Err(e) = result {
+        eprintln!("{}: {}", path.display(), e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Where practice stopped in one dictionary.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Position {
+    pub chapter: usize, // 1-based, 0 if never opened
+    pub word: usize,    // index inside the chapter
+}
+
+/// Everything the terminal frontend remembers between runs.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Progress {
+    pub dict: String,
+    pub positions: BTreeMap<String, Position>,
+    pub words: u32,
+    pub keystrokes: u32,
+    pub mistakes: u32,
+}
+
+impl Progress {
+    /// The saved progress, or a fresh one if the file is missing or broken.
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self).unwrap())?;
+        fs::rename(tmp, path)
+    }
+}
+
+/// `$KEYPRESS_PROGRESS`, else `progress.json` in the XDG data directory.
+pub fn default_path() -> PathBuf {
+    if let Some(path) = env::var_os("KEYPRESS_PROGRESS") {
+        return PathBuf::from(path);
+    }
+    let data = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+        .unwrap_or_default();
+    data.join("keypress").join("progress.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process;
+
+    #[test]
+    fn progress_round_trips_through_a_file() {
+        let dir = env::temp_dir().join(format!("keypress-tui-{}", process::id()));
+        let path = dir.join("nested").join("progress.json");
+        assert_eq!(Progress::load(&path), Progress::default());
+
+        let mut progress = Progress {
+            dict: String::from("CET4"),
+            words: 12,
+            keystrokes: 80,
+            mistakes: 3,
+            ..Default::default()
+        };
+        progress.positions.insert(
+            String::from("CET4"),
+            Position {
+                chapter: 2,
+                word: 5,
+            },
+        );
+        progress.save(&path).unwrap();
+        assert_eq!(Progress::load(&path), progress);
+
+        fs::write(&path, "not json").unwrap();
+        assert_eq!(Progress::load(&path), Progress::default());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
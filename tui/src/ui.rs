@@ -0,0 +1,125 @@
+use ratatui::layout::{Alignment, Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Gauge, Paragraph, Wrap};
+use ratatui::Frame;
+
+use crate::app::App;
+
+const HELP: &str = "Tab dictionary · PgUp/PgDn chapter · ←/→ word · Backspace retry · Esc quit";
+
+/// The word with the typed part highlighted, or in red after a mistake.
+fn word_line(app: &App, name: &str) -> Line<'static> {
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    if app.wrong {
+        return Line::from(Span::styled(name.to_string(), bold.fg(Color::Red)));
+    }
+    let typed = app.attempt.typed.len().min(name.len());
+    Line::from(vec![
+        Span::styled(name[..typed].to_string(), bold.fg(Color::Green)),
+        Span::styled(name[typed..].to_string(), bold),
+    ])
+}
+
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [header, body, gauge, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(5),
+        Constraint::Length(3),
+        Constraint::Length(2),
+    ])
+    .areas(frame.area());
+
+    let title = format!(
+        "keypress · {} · chapter {}/{}",
+        app.dict_name(),
+        app.chapter,
+        app.nr_chapter()
+    );
+    frame.render_widget(
+        Paragraph::new(title).style(Style::default().add_modifier(Modifier::REVERSED)),
+        header,
+    );
+
+    let mut lines = vec![Line::default()];
+    if let Some(word) = app.current() {
+        lines.push(word_line(app, &word.name));
+        let phone: Vec<String> = [("US", &word.usphone), ("UK", &word.ukphone)]
+            .iter()
+            .filter(|(_, p)| !p.is_empty())
+            .map(|(region, p)| format!("{} /{}/", region, p))
+            .collect();
+        lines.push(Line::from(phone.join("  ")).style(Style::default().fg(Color::DarkGray)));
+        lines.push(Line::default());
+        lines.extend(word.trans.iter().map(|t| Line::from(t.clone())));
+    }
+    frame.render_widget(
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        body,
+    );
+
+    let total = app.chapter_words().len().max(1);
+    frame.render_widget(
+        Gauge::default()
+            .block(Block::default().borders(Borders::ALL).title("Chapter"))
+            .gauge_style(Style::default().fg(Color::Cyan))
+            .ratio(app.word as f64 / total as f64)
+            .label(format!("{}/{}", app.word, total)),
+        gauge,
+    );
+
+    let last = match app.last {
+        Some((wpm, accuracy)) => format!("last word {:.0} WPM · {:.0}% accuracy", wpm, accuracy),
+        None => String::from("start typing"),
+    };
+    let stats = format!("{} · {} words typed in total", last, app.progress.words);
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(stats),
+            Line::from(HELP).style(Style::default().fg(Color::DarkGray)),
+        ]),
+        footer,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress::Progress;
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::{KeyCode, KeyEvent};
+    use ratatui::Terminal;
+
+    fn render(app: &App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|frame| draw(frame, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn shows_word_translation_and_progress() {
+        let mut app = App::new(Progress::default());
+        let screen = render(&app);
+        let word = app.current().unwrap().clone();
+        assert!(screen.contains("chapter 1/"), "{}", screen);
+        assert!(screen.contains(&word.name), "{}", screen);
+        assert!(screen.contains("start typing"), "{}", screen);
+        assert!(screen.contains("0/20"), "{}", screen);
+
+        for c in word.name.chars() {
+            app.key(KeyEvent::from(KeyCode::Char(c)), 1000.0);
+        }
+        let screen = render(&app);
+        assert!(screen.contains("1/20"), "{}", screen);
+        assert!(screen.contains("WPM"), "{}", screen);
+        assert!(screen.contains("1 words typed"), "{}", screen);
+    }
+}